@@ -1,81 +1,134 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use super::{AnimalComponent, AnimalSprite};
+use super::{AnimalComponent, AnimalKind};
 use crate::{
-    behaviors::{self, UnitBehavior},
-    constants,
+    balance::{BehaviorsBalance, GameBalance},
+    behaviors::{self, Wander},
+    navigation::{NavGrid, NavPath},
     player::Player,
+    rng::{GameRng, RngStream},
+    schedule::tick_delta,
 };
 
+// States of an animal
+#[derive(Clone)]
+pub enum AnimalBehavior {
+    Idle(Wander),
+    // going back to the other animals of its species
+    Herd { target: Option<Vec2> },
+    RunAway { target: Option<Vec2> },
+}
+
+impl AnimalBehavior {
+    pub fn idle(balance: &BehaviorsBalance) -> Self {
+        AnimalBehavior::Idle(Wander::new(
+            balance.animal_idle_duration,
+            balance.animal_idle_duration_spread,
+        ))
+    }
+}
+
 // Handles animals behaving according to their current behavior
 pub fn animal_behavior_system(
-    time: Res<Time>,
-    mut animal_query: Query<(&mut AnimalComponent, &mut Velocity, &Transform, &Children)>,
-    mut sprite_query: Query<&mut Sprite, With<AnimalSprite>>,
+    mut animal_query: Query<(
+        &mut AnimalComponent,
+        &mut Velocity,
+        &mut NavPath,
+        &Transform,
+    )>,
     player_query: Query<&Transform, With<Player>>,
+    grid: Res<NavGrid>,
+    balance: Res<GameBalance>,
+    mut rng: ResMut<GameRng>,
 ) {
-    for (mut animal, mut vel, transform, children) in animal_query.iter_mut() {
-        let sprite_entity = children.iter().next().unwrap();
+    let balance = &balance.behaviors;
 
-        let mut sprite = sprite_query.get_mut(*sprite_entity).unwrap();
+    let animal_positions: Vec<(AnimalKind, Vec2)> = animal_query
+        .iter()
+        .map(|(animal, _, _, transform)| {
+            (animal.stats.kind.clone(), transform.translation.truncate())
+        })
+        .collect();
 
+    for (mut animal, mut vel, mut path, transform) in animal_query.iter_mut() {
         let animal_position = Vec2::new(transform.translation.x, transform.translation.y);
-        let stats = animal.stats;
+        let stats: behaviors::UnitStats = (&animal.stats).into();
         let player_transform = player_query.iter().next().unwrap();
         let player_position = Vec2::new(
             player_transform.translation.x,
             player_transform.translation.y,
         );
+
+        // the herd is the animals of the same species around, itself included
+        let herd: Vec<Vec2> = animal_positions
+            .iter()
+            .filter(|(kind, position)| {
+                *kind == animal.stats.kind
+                    && position.distance(animal_position) < balance.animal_herd_range
+            })
+            .map(|(_, position)| *position)
+            .collect();
+        let herd_center = (herd.len() > 1)
+            .then(|| {
+                herd.iter()
+                    .fold(Vec2::ZERO, |sum, position| sum + *position)
+                    / herd.len() as f32
+            })
+            .filter(|center| center.distance(animal_position) > balance.animal_herd_distance);
+
+        let player_close = animal_position.distance(player_position) < balance.animal_runaway_range;
+
         match &mut animal.behavior {
-            UnitBehavior::Idle {
-                timer,
-                base_duration,
-                duration_spread,
-                direction,
-                is_moving,
-            } => {
+            AnimalBehavior::Idle(wander) => {
                 behaviors::idle_behavior(
                     &mut vel,
-                    vec![&mut sprite],
-                    &time,
-                    timer,
-                    base_duration,
-                    duration_spread,
-                    direction,
-                    is_moving,
-                    stats.into(),
+                    tick_delta(),
+                    wander,
+                    stats,
+                    rng.stream(RngStream::Behaviors),
                 );
-                if animal_position.distance(player_position) < constants::ANIMAL_RUNAWAY_RANGE {
-                    animal.behavior = UnitBehavior::RunAway {
+
+                if player_close {
+                    animal.behavior = AnimalBehavior::RunAway {
                         target: Some(player_position),
                     }
+                } else if herd_center.is_some() {
+                    animal.behavior = AnimalBehavior::Herd {
+                        target: herd_center,
+                    }
                 }
             }
-            UnitBehavior::Pursue { target: _ } => todo!(),
-            UnitBehavior::Follow {
-                target: _,
-                distance: _,
-            } => todo!(),
-            UnitBehavior::RunAway { target } => {
-                behaviors::run_away_behavior(
+            AnimalBehavior::Herd { target } => {
+                behaviors::follow_behavior(
                     &mut vel,
-                    vec![&mut sprite],
-                    stats.into(),
+                    stats,
                     animal_position,
                     *target,
+                    balance.animal_herd_distance,
+                    &grid,
+                    &mut path,
                 );
 
-                if animal_position.distance(player_position) > constants::ANIMAL_IDLE_RANGE {
-                    animal.behavior = UnitBehavior::Idle {
-                        timer: Timer::from_seconds(constants::ANIMAL_IDLE_DURATION, false),
-                        base_duration: constants::ANIMAL_IDLE_DURATION,
-                        duration_spread: constants::ANIMAL_IDLE_DURATION_SPREAD,
-                        direction: Vec2::default(),
-                        is_moving: false,
-                    };
+                if player_close {
+                    animal.behavior = AnimalBehavior::RunAway {
+                        target: Some(player_position),
+                    }
+                } else if herd_center.is_some() {
+                    animal.behavior = AnimalBehavior::Herd {
+                        target: herd_center,
+                    }
+                } else {
+                    animal.behavior = AnimalBehavior::idle(balance);
+                }
+            }
+            AnimalBehavior::RunAway { target } => {
+                behaviors::run_away_behavior(&mut vel, stats, animal_position, *target);
+
+                if animal_position.distance(player_position) > balance.animal_idle_range {
+                    animal.behavior = AnimalBehavior::idle(balance);
                 } else {
-                    animal.behavior = UnitBehavior::RunAway {
+                    animal.behavior = AnimalBehavior::RunAway {
                         target: Some(player_position),
                     }
                 }
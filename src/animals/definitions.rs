@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail};
+use bevy::{
+    asset::{AssetLoader, AssetPath, BoxedFuture, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use serde::Deserialize;
+
+use super::{AnimalAttributes, AnimalKind};
+use crate::chimeras::PartTraits;
+
+// Species described by an animals file, in a stable (alphabetical) order
+#[derive(Debug, TypeUuid)]
+#[uuid = "3cc24aca-9a5c-4c5a-841a-9ecf9c35b1e3"]
+pub struct AnimalDefinitions {
+    pub species: Vec<(AnimalKind, AnimalAttributes)>,
+}
+
+// One entry of the animals file, as written by the designers
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AnimalDefinition {
+    speed: f32,
+    accel: f32,
+    decel: f32,
+    health: f32,
+    attack: f32,
+    regen: f32,
+    range: f32,
+    collider_size: Vec2,
+    texture: String,
+    head_texture: String,
+    tail_texture: String,
+    // species without them give no part of these slots
+    #[serde(default)]
+    body_texture: Option<String>,
+    #[serde(default)]
+    legs_texture: Option<String>,
+    #[serde(default)]
+    wings_texture: Option<String>,
+    #[serde(default)]
+    traits: PartTraits,
+}
+
+impl AnimalDefinition {
+    // checks the values that would otherwise break the game at runtime
+    fn validate(&self) -> anyhow::Result<()> {
+        let positive_stats = [
+            ("speed", self.speed),
+            ("accel", self.accel),
+            ("decel", self.decel),
+            ("health", self.health),
+            ("range", self.range),
+            ("collider_size.x", self.collider_size.x),
+            ("collider_size.y", self.collider_size.y),
+        ];
+        for (name, value) in positive_stats {
+            if !value.is_finite() || value <= 0.0 {
+                bail!("`{}` must be strictly positive, got {}", name, value);
+            }
+        }
+
+        for (name, value) in [("attack", self.attack), ("regen", self.regen)] {
+            if !value.is_finite() || value < 0.0 {
+                bail!("`{}` must be positive, got {}", name, value);
+            }
+        }
+
+        for (name, path) in [
+            ("texture", &self.texture),
+            ("head_texture", &self.head_texture),
+            ("tail_texture", &self.tail_texture),
+        ] {
+            if path.is_empty() {
+                bail!("`{}` must point to an image", name);
+            }
+        }
+
+        for (name, path) in [
+            ("body_texture", &self.body_texture),
+            ("legs_texture", &self.legs_texture),
+            ("wings_texture", &self.wings_texture),
+        ] {
+            if matches!(path, Some(path) if path.is_empty()) {
+                bail!("`{}` must point to an image", name);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Parses the content of an animals file, the error names the faulty entry
+fn parse_definitions(bytes: &[u8]) -> anyhow::Result<BTreeMap<String, AnimalDefinition>> {
+    let entries: BTreeMap<String, ron::Value> =
+        ron::de::from_bytes(bytes).map_err(|err| anyhow!("malformed animals file: {}", err))?;
+
+    if entries.is_empty() {
+        bail!("the animals file does not define any species");
+    }
+
+    let mut definitions = BTreeMap::new();
+    for (name, value) in entries {
+        let definition: AnimalDefinition = value
+            .into_rust()
+            .map_err(|err| anyhow!("invalid animal `{}`: {}", name, err))?;
+        definition
+            .validate()
+            .map_err(|err| anyhow!("invalid animal `{}`: {}", name, err))?;
+        definitions.insert(name, definition);
+    }
+
+    Ok(definitions)
+}
+
+impl AnimalDefinitions {
+    // builds the species of an animals file, textures are resolved by the caller
+    pub fn from_bytes(
+        bytes: &[u8],
+        mut load_texture: impl FnMut(String) -> Handle<Image>,
+    ) -> anyhow::Result<Self> {
+        let definitions = parse_definitions(bytes)?;
+
+        let species = definitions
+            .into_iter()
+            .map(|(name, definition)| {
+                let attributes = AnimalAttributes {
+                    speed: definition.speed,
+                    accel: definition.accel,
+                    decel: definition.decel,
+                    attack: definition.attack,
+                    health: definition.health,
+                    regen: definition.regen,
+                    range: definition.range,
+                    collider_size: definition.collider_size,
+                    texture: load_texture(definition.texture),
+                    head_texture: load_texture(definition.head_texture),
+                    tail_texture: load_texture(definition.tail_texture),
+                    body_texture: definition.body_texture.map(&mut load_texture),
+                    legs_texture: definition.legs_texture.map(&mut load_texture),
+                    wings_texture: definition.wings_texture.map(&mut load_texture),
+                    part_traits: definition.traits,
+                };
+
+                (AnimalKind::new(name), attributes)
+            })
+            .collect();
+
+        Ok(AnimalDefinitions { species })
+    }
+}
+
+#[derive(Default)]
+pub struct AnimalDefinitionsLoader;
+
+impl AssetLoader for AnimalDefinitionsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let mut dependencies = Vec::new();
+            let definitions = AnimalDefinitions::from_bytes(bytes, |path| {
+                let asset_path = AssetPath::new(path.into(), None);
+                let handle = load_context.get_handle(asset_path.clone());
+                dependencies.push(asset_path);
+                handle
+            })?;
+
+            load_context
+                .set_default_asset(LoadedAsset::new(definitions).with_dependencies(dependencies));
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["animals.ron"]
+    }
+}
@@ -1,154 +1,93 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
+use rand::seq::SliceRandom;
 use rand::Rng;
-use rand_distr::{Distribution, Standard, UnitCircle};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 
 use crate::animations::BobbingAnim;
 use crate::assets_manager::AssetsManager;
-use crate::behaviors::UnitBehavior;
-use crate::constants::{self, ANIMAL_STATS_DEVIATION as STATS_DEVIATION};
+use crate::balance::GameBalance;
+use crate::behaviors::Facing;
+use crate::chimeras::{ChimeraSlot, PartTraits};
 use crate::health::Health;
-use crate::states::GameStates;
+use crate::navigation::NavPath;
+use crate::rng::{GameRng, RngStream};
+use crate::schedule::{self, FixedUpdateStage};
+use crate::states::{GameEntity, GameStates};
 
 mod behavior;
+mod definitions;
 mod spawn;
+pub use self::behavior::AnimalBehavior;
+pub use self::definitions::AnimalDefinitions;
 pub use self::spawn::AnimalSpawner;
 
 pub struct AnimalsPlugin;
 
 impl Plugin for AnimalsPlugin {
     fn build(&self, app: &mut App) {
+        app.add_asset::<AnimalDefinitions>()
+            .init_asset_loader::<definitions::AnimalDefinitionsLoader>();
+
         app.add_system_set(
             SystemSet::on_enter(GameStates::MainMenu)
                 .with_system(setup_animal_attributes)
                 .label("setup_attributes"),
         );
 
-        app.insert_resource(spawn::AnimalSpawner {
-            spawn_timer: Timer::from_seconds(constants::ANIMAL_BASE_SPAWN_DURATION, false),
-        });
+        // configured from the game balance when the game starts
+        app.init_resource::<AnimalSpawner>();
 
-        app.add_system_set(SystemSet::on_enter(GameStates::Game).with_system(spawn_test_system));
         app.add_system_set(
-            SystemSet::on_update(GameStates::Game)
-                .with_system(behavior::animal_behavior_system)
+            SystemSet::on_enter(GameStates::Game)
+                .with_system(spawn::setup_animal_spawner)
+                .with_system(spawn_test_system),
+        );
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            schedule::on_fixed_update()
+                .with_system(behavior::animal_behavior_system.after("nav_grid"))
                 .with_system(spawn::spawn_animals_system),
         );
     }
 }
 
-fn setup_animal_attributes(mut commands: Commands, assets: Res<AssetsManager>) {
-    let mut animal_attr_res = AnimalAttributesResource::default();
-    animal_attr_res.insert(
-        AnimalKind::Pig,
-        AnimalAttributes {
-            speed: 60.0,
-            accel: 1.5,
-            decel: 6.0,
-            health: 120.0,
-            attack: 10.0,
-            regen: 1.0,
-            range: 150.0,
-            collider_size: Vec2::new(20.0, 10.0),
-            texture: assets.texture_pig.clone(),
-            head_texture: assets.texture_pig_head.clone(),
-            tail_texture: assets.texture_pig_tail.clone(),
-        },
-    );
-    animal_attr_res.insert(
-        AnimalKind::Cow,
-        AnimalAttributes {
-            speed: 50.0,
-            accel: 1.75,
-            decel: 6.0,
-            attack: 8.0,
-            health: 150.0,
-            regen: 2.0,
-            range: 150.0,
-            collider_size: Vec2::new(20.0, 10.0),
-            texture: assets.texture_cow.clone(),
-            head_texture: assets.texture_cow_head.clone(),
-            tail_texture: assets.texture_cow_tail.clone(),
-        },
-    );
-    animal_attr_res.insert(
-        AnimalKind::Dog,
-        AnimalAttributes {
-            speed: 80.0,
-            accel: 2.2,
-            decel: 6.0,
-            attack: 15.0,
-            health: 100.0,
-            regen: 0.7,
-            range: 150.0,
-            collider_size: Vec2::new(20.0, 10.0),
-            texture: assets.texture_dog.clone(),
-            head_texture: assets.texture_dog_head.clone(),
-            tail_texture: assets.texture_dog_tail.clone(),
-        },
-    );
-    animal_attr_res.insert(
-        AnimalKind::Chicken,
-        AnimalAttributes {
-            speed: 70.0,
-            accel: 2.0,
-            decel: 6.0,
-            health: 75.0,
-            attack: 18.0,
-            regen: 0.7,
-            range: 150.0,
-            collider_size: Vec2::new(20.0, 10.0),
-            texture: assets.texture_chicken.clone(),
-            head_texture: assets.texture_chicken_head.clone(),
-            tail_texture: assets.texture_chicken_tail.clone(),
-        },
-    );
-    animal_attr_res.insert(
-        AnimalKind::Horse,
-        AnimalAttributes {
-            speed: 100.0,
-            accel: 3.0,
-            decel: 6.0,
-            health: 140.0,
-            attack: 12.0,
-            regen: 1.0,
-            range: 150.0,
-            collider_size: Vec2::new(20.0, 10.0),
-            texture: assets.texture_horse.clone(),
-            head_texture: assets.texture_horse_head.clone(),
-            tail_texture: assets.texture_horse_tail.clone(),
-        },
-    );
+fn setup_animal_attributes(
+    mut commands: Commands,
+    assets: Res<AssetsManager>,
+    definitions: Res<Assets<AnimalDefinitions>>,
+) {
+    // the assets manager only leaves the loading state once the definitions are loaded
+    let definitions = definitions
+        .get(&assets.animal_definitions)
+        .expect("animal definitions should be loaded");
 
-    commands.insert_resource(animal_attr_res);
+    commands.insert_resource(AnimalAttributesResource::from_definitions(definitions));
 }
 
-#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
-pub enum AnimalKind {
-    Pig,
-    Cow,
-    Dog,
-    Horse,
-    Chicken,
+// Name of an animal species, as defined in the animals file
+#[derive(Hash, PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AnimalKind(String);
+
+impl AnimalKind {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
 }
 
-impl Distribution<AnimalKind> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> AnimalKind {
-        match rng.gen_range(0..5) {
-            0 => AnimalKind::Pig,
-            1 => AnimalKind::Cow,
-            2 => AnimalKind::Dog,
-            3 => AnimalKind::Horse,
-            _ => AnimalKind::Chicken,
-        }
+impl fmt::Display for AnimalKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
     }
 }
 
 // Core component of animal
 #[derive(Component)]
 pub struct AnimalComponent {
-    pub behavior: UnitBehavior,
+    pub behavior: AnimalBehavior,
     pub stats: AnimalStats,
 }
 
@@ -156,7 +95,7 @@ pub struct AnimalComponent {
 pub struct AnimalSprite;
 
 // Stores stats for animals
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AnimalStats {
     pub speed: f32,
     pub accel: f32,
@@ -168,6 +107,7 @@ pub struct AnimalStats {
     pub range: f32,
 }
 
+#[derive(Clone, Debug)]
 pub struct AnimalAttributes {
     pub speed: f32,
     pub accel: f32,
@@ -180,60 +120,89 @@ pub struct AnimalAttributes {
     pub texture: Handle<Image>,
     pub head_texture: Handle<Image>,
     pub tail_texture: Handle<Image>,
+    pub body_texture: Option<Handle<Image>>,
+    pub legs_texture: Option<Handle<Image>>,
+    pub wings_texture: Option<Handle<Image>>,
+    pub part_traits: PartTraits,
 }
 
-pub type AnimalAttributesResource = HashMap<AnimalKind, AnimalAttributes>;
+impl AnimalAttributes {
+    // texture of the chimera part given for a slot, if the species has one
+    pub fn part_texture(&self, slot: ChimeraSlot) -> Option<&Handle<Image>> {
+        match slot {
+            ChimeraSlot::Head => Some(&self.head_texture),
+            ChimeraSlot::Tail => Some(&self.tail_texture),
+            ChimeraSlot::Body => self.body_texture.as_ref(),
+            ChimeraSlot::Legs => self.legs_texture.as_ref(),
+            ChimeraSlot::Wings => self.wings_texture.as_ref(),
+        }
+    }
+}
 
-// Test function, spawns one of each animal
-fn spawn_test_system(mut commands: Commands, animal_attr_res: Res<AnimalAttributesResource>) {
-    spawn_animal(
-        &AnimalKind::Pig,
-        Vec2::new(400.0, 50.0),
-        &animal_attr_res,
-        &mut commands,
-    );
+// Attributes of every known species, kept in insertion order so that random
+// picks do not depend on the hashing of the kinds
+#[derive(Default)]
+pub struct AnimalAttributesResource {
+    kinds: Vec<AnimalKind>,
+    attributes: HashMap<AnimalKind, AnimalAttributes>,
+}
 
-    spawn_animal(
-        &AnimalKind::Cow,
-        Vec2::new(-50.0, -200.0),
-        &animal_attr_res,
-        &mut commands,
-    );
+impl AnimalAttributesResource {
+    pub fn from_definitions(definitions: &AnimalDefinitions) -> Self {
+        let mut animal_attr_res = Self::default();
+        for (kind, attributes) in definitions.species.iter() {
+            animal_attr_res.insert(kind.clone(), attributes.clone());
+        }
 
-    spawn_animal(
-        &AnimalKind::Dog,
-        Vec2::new(-200.0, 200.0),
-        &animal_attr_res,
-        &mut commands,
-    );
+        animal_attr_res
+    }
 
-    spawn_animal(
-        &AnimalKind::Horse,
-        Vec2::new(0.0, -300.0),
-        &animal_attr_res,
-        &mut commands,
-    );
+    pub fn insert(&mut self, kind: AnimalKind, attributes: AnimalAttributes) {
+        if self.attributes.insert(kind.clone(), attributes).is_none() {
+            self.kinds.push(kind);
+        }
+    }
 
-    spawn_animal(
-        &AnimalKind::Chicken,
-        Vec2::new(200.0, 75.0),
-        &animal_attr_res,
-        &mut commands,
-    );
+    pub fn get(&self, kind: &AnimalKind) -> Option<&AnimalAttributes> {
+        self.attributes.get(kind)
+    }
 
-    spawn_animal(
-        &AnimalKind::Chicken,
-        Vec2::new(300.0, 70.0),
-        &animal_attr_res,
-        &mut commands,
-    );
+    pub fn iter(&self) -> impl Iterator<Item = (&AnimalKind, &AnimalAttributes)> {
+        self.kinds.iter().map(|kind| (kind, &self.attributes[kind]))
+    }
 
-    spawn_animal(
-        &AnimalKind::Chicken,
-        Vec2::new(200.0, 50.0),
-        &animal_attr_res,
-        &mut commands,
-    );
+    pub fn random_kind<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&AnimalKind> {
+        self.kinds.choose(rng)
+    }
+}
+
+// Test function, spawns one of each animal
+fn spawn_test_system(
+    mut commands: Commands,
+    animal_attr_res: Res<AnimalAttributesResource>,
+    balance: Res<GameBalance>,
+    mut rng: ResMut<GameRng>,
+) {
+    let test_animals = [
+        ("pig", Vec2::new(400.0, 50.0)),
+        ("cow", Vec2::new(-50.0, -200.0)),
+        ("dog", Vec2::new(-200.0, 200.0)),
+        ("horse", Vec2::new(0.0, -300.0)),
+        ("chicken", Vec2::new(200.0, 75.0)),
+        ("chicken", Vec2::new(300.0, 70.0)),
+        ("chicken", Vec2::new(200.0, 50.0)),
+    ];
+
+    for (kind, position) in test_animals {
+        spawn_animal(
+            &AnimalKind::new(kind),
+            position,
+            &animal_attr_res,
+            &balance,
+            &mut rng,
+            &mut commands,
+        );
+    }
 }
 
 // Spawn the indicated animal at the position
@@ -241,70 +210,74 @@ pub fn spawn_animal(
     animal_kind: &AnimalKind,
     position: Vec2,
     animal_attr_res: &AnimalAttributesResource,
+    balance: &GameBalance,
+    rng: &mut GameRng,
     commands: &mut Commands,
 ) {
-    let attributes = &animal_attr_res[animal_kind];
+    let attributes = if let Some(attributes) = animal_attr_res.get(animal_kind) {
+        attributes
+    } else {
+        error!("cannot spawn unknown animal kind `{}`", animal_kind);
+        return;
+    };
 
-    let mut random_direction = Vec2::new(0.0, 0.0);
-    let dir: [f32; 2] = UnitCircle.sample(&mut rand::thread_rng());
-    random_direction.x = dir[0];
-    random_direction.y = dir[1];
+    let stats_deviation = balance.stats.animal_deviation;
+    let stats_rng = rng.stream(RngStream::Stats);
 
-    let animal_health = rand::thread_rng().gen_range(
-        attributes.health * (1.0 - STATS_DEVIATION)..attributes.health * (1.0 + STATS_DEVIATION),
-    );
+    // inclusive, a stat of 0 or no deviation draws the stat itself
+    let mut deviate = |value: f32| {
+        stats_rng.gen_range(value * (1.0 - stats_deviation)..=value * (1.0 + stats_deviation))
+    };
+
+    let animal_health = deviate(attributes.health);
+    let animal_regen = deviate(attributes.regen);
 
-    let animal_regen = rand::thread_rng().gen_range(
-        attributes.regen * (1.0 - STATS_DEVIATION)..attributes.regen * (1.0 + STATS_DEVIATION),
+    let animal = AnimalComponent {
+        behavior: AnimalBehavior::idle(&balance.behaviors),
+        stats: AnimalStats {
+            attack: deviate(attributes.attack),
+            range: deviate(attributes.range),
+            speed: deviate(attributes.speed),
+            accel: deviate(attributes.accel),
+            decel: deviate(attributes.decel),
+            health: animal_health,
+            regen: animal_regen,
+            kind: animal_kind.clone(),
+        },
+    };
+    let health = Health::new(
+        animal_health,
+        animal_regen,
+        balance.behaviors.animal_regen_rate,
     );
 
+    spawn_animal_entity(animal, health, position, attributes, rng, commands);
+}
+
+// Spawn the entity of an already rolled animal, also used when loading a save
+pub fn spawn_animal_entity(
+    animal: AnimalComponent,
+    health: Health,
+    position: Vec2,
+    attributes: &AnimalAttributes,
+    rng: &mut GameRng,
+    commands: &mut Commands,
+) -> Entity {
+    let bobbing_anim_val = rng.stream(RngStream::Visuals).gen::<f32>() * 32.0;
+
     commands
         .spawn_bundle(TransformBundle::from(Transform::from_translation(
             position.extend(1.0),
         )))
+        .insert(GameEntity)
         .insert(Velocity {
             linvel: Vec2::new(0.0, 0.0),
             angvel: 0.0,
         })
-        .insert(AnimalComponent {
-            behavior: UnitBehavior::Idle {
-                timer: Timer::from_seconds(constants::ANIMAL_IDLE_DURATION, false),
-                base_duration: constants::ANIMAL_IDLE_DURATION,
-                duration_spread: constants::ANIMAL_IDLE_DURATION_SPREAD,
-                direction: Vec2::default(),
-                is_moving: false,
-            },
-            stats: AnimalStats {
-                attack: rand::thread_rng().gen_range(
-                    attributes.attack * (1.0 - STATS_DEVIATION)
-                        ..attributes.attack * (1.0 + STATS_DEVIATION),
-                ),
-                range: rand::thread_rng().gen_range(
-                    attributes.range * (1.0 - STATS_DEVIATION)
-                        ..attributes.range * (1.0 + STATS_DEVIATION),
-                ),
-                speed: rand::thread_rng().gen_range(
-                    attributes.speed * (1.0 - STATS_DEVIATION)
-                        ..attributes.speed * (1.0 + STATS_DEVIATION),
-                ),
-                accel: rand::thread_rng().gen_range(
-                    attributes.accel * (1.0 - STATS_DEVIATION)
-                        ..attributes.accel * (1.0 + STATS_DEVIATION),
-                ),
-                decel: rand::thread_rng().gen_range(
-                    attributes.decel * (1.0 - STATS_DEVIATION)
-                        ..attributes.decel * (1.0 + STATS_DEVIATION),
-                ),
-                health: animal_health,
-                regen: animal_regen,
-                kind: *animal_kind,
-            },
-        })
-        .insert(Health::new(
-            animal_health,
-            animal_regen,
-            constants::ANIMAL_REGEN_RATE,
-        ))
+        .insert(animal)
+        .insert(NavPath::default())
+        .insert(Facing::default())
+        .insert(health)
         .insert(RigidBody::Dynamic)
         .insert(Collider::cuboid(
             attributes.collider_size.x,
@@ -319,7 +292,8 @@ pub fn spawn_animal(
                 })
                 .insert(AnimalSprite)
                 .insert(BobbingAnim {
-                    anim: rand::thread_rng().gen::<f32>() * 32.0,
+                    anim: bobbing_anim_val,
                 });
-        });
+        })
+        .id()
 }
@@ -1,35 +1,51 @@
 use bevy::prelude::*;
 
-use crate::animals::{spawn_animal, AnimalAttributesResource, AnimalKind};
-use crate::constants::{self, ANIMAL_BASE_SPAWN_DURATION, ANIMAL_SPAWN_HOURS};
+use crate::animals::{spawn_animal, AnimalAttributesResource};
+use crate::balance::GameBalance;
 use crate::day_cycle::DayCycleResource;
+use crate::rng::{GameRng, RngStream};
+use crate::schedule::tick_delta;
 use rand::Rng;
 use std::time::Duration;
 
+#[derive(Default)]
 pub struct AnimalSpawner {
     pub spawn_timer: Timer,
 }
 
+pub fn setup_animal_spawner(mut animal_spawner: ResMut<AnimalSpawner>, balance: Res<GameBalance>) {
+    animal_spawner.spawn_timer =
+        Timer::from_seconds(balance.spawning.animal_base_spawn_duration, false);
+}
+
 pub fn spawn_animals_system(
     mut commands: Commands,
-    time: Res<Time>,
     day_cycle: Res<DayCycleResource>,
     mut animal_spawner: ResMut<AnimalSpawner>,
     animal_attr_res: Res<AnimalAttributesResource>,
+    balance: Res<GameBalance>,
+    mut rng: ResMut<GameRng>,
 ) {
-    if day_cycle.get_hour() > ANIMAL_SPAWN_HOURS.0 || day_cycle.get_hour() < ANIMAL_SPAWN_HOURS.1 {
-        animal_spawner.spawn_timer.tick(time.delta());
+    let spawning = &balance.spawning;
+    let spawn_hours = spawning.animal_spawn_hours;
+
+    if day_cycle.get_hour() > spawn_hours.0 || day_cycle.get_hour() < spawn_hours.1 {
+        animal_spawner.spawn_timer.tick(tick_delta());
 
         if animal_spawner.spawn_timer.just_finished() {
-            let random_animal: AnimalKind = rand::random();
+            let spawning_rng = rng.stream(RngStream::Spawning);
+            let random_animal = match animal_attr_res.random_kind(spawning_rng) {
+                Some(kind) => kind.clone(),
+                None => return,
+            };
 
             // choose random position in village
-            let rand_x_pos: f32 = rand::thread_rng()
-                .gen_range(constants::ANIMAL_SPAWN_MIN_X..=constants::ANIMAL_SPAWN_MAX_X);
-            let rand_y_pos: f32 = rand::thread_rng()
-                .gen_range(constants::ANIMAL_SPAWN_MIN_Y..=constants::ANIMAL_SPAWN_MAX_Y);
+            let rand_x_pos: f32 =
+                spawning_rng.gen_range(spawning.animal_spawn_min.x..=spawning.animal_spawn_max.x);
+            let rand_y_pos: f32 =
+                spawning_rng.gen_range(spawning.animal_spawn_min.y..=spawning.animal_spawn_max.y);
             info!(
-                "spawning {:?} at position ({},{}) at time {}",
+                "spawning {} at position ({},{}) at time {}",
                 random_animal,
                 rand_x_pos,
                 rand_y_pos,
@@ -39,11 +55,13 @@ pub fn spawn_animals_system(
                 &random_animal,
                 Vec2::new(rand_x_pos, rand_y_pos),
                 &animal_attr_res,
+                &balance,
+                &mut rng,
                 &mut commands,
             );
 
-            let new_spawn_duration = ANIMAL_BASE_SPAWN_DURATION
-                * (-(day_cycle.days_passed as f32 + 1.0) / constants::ANIMAL_SPAWN_FACTOR).exp();
+            let new_spawn_duration = spawning.animal_base_spawn_duration
+                * (-(day_cycle.days_passed as f32 + 1.0) / spawning.animal_spawn_factor).exp();
 
             animal_spawner
                 .spawn_timer
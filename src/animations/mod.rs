@@ -6,7 +6,9 @@ use bevy_rapier2d::prelude::Velocity;
 use crate::chimeras::ChimeraComponent;
 
 use crate::animals::AnimalComponent;
+use crate::behaviors;
 use crate::constants;
+use crate::pause::GameClock;
 use crate::states::GameStates;
 use crate::villagers::VillagerComponent;
 
@@ -14,7 +16,11 @@ pub struct AnimationsPlugin;
 
 impl Plugin for AnimationsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_update(GameStates::Game).with_system(bob_animation));
+        app.add_system_set(
+            SystemSet::on_update(GameStates::Game)
+                .with_system(bob_animation)
+                .with_system(behaviors::facing_system),
+        );
     }
 }
 
@@ -24,7 +30,7 @@ pub struct BobbingAnim {
 }
 
 pub fn bob_animation(
-    time: Res<Time>,
+    clock: Res<GameClock>,
     q_velocity: Query<
         &Velocity,
         Or<(
@@ -36,8 +42,11 @@ pub fn bob_animation(
     mut q_bobbing: Query<(&Parent, &mut Transform, &mut BobbingAnim), With<Sprite>>,
 ) {
     for (parent, mut transform, mut animation) in q_bobbing.iter_mut() {
-        // fetch velocity from parent
-        let velocity = q_velocity.get(parent.0).unwrap();
+        // fetch velocity from parent, corpses do not move anymore
+        let velocity = match q_velocity.get(parent.0) {
+            Ok(velocity) => velocity,
+            Err(_) => continue,
+        };
 
         // compute speed from velocity and offsets
         let speed = velocity.linvel.length();
@@ -46,10 +55,12 @@ pub fn bob_animation(
 
         // update the animation circle (sin(2*pi) = sin(0))
         animation.anim +=
-            (speed * time.delta_seconds() * constants::ANIMATION_SPEED_FACTOR) % (16.0 * 2.0 * PI);
+            (speed * clock.delta_seconds() * constants::ANIMATION_SPEED_FACTOR) % (16.0 * 2.0 * PI);
 
-        // apply the transform to the component
+        // apply the transform to the component, the vertical scale is the size
+        // of the sprite
         transform.translation.y = offset_y;
-        transform.scale.x = transform.scale.x / transform.scale.x.abs() * (1.0 - offset_x * 0.02);
+        transform.scale.x =
+            transform.scale.x.signum() * transform.scale.y * (1.0 - offset_x * 0.02);
     }
 }
@@ -1,7 +1,10 @@
 use bevy::prelude::*;
 use bevy_asset_loader::*;
 
-use crate::states::GameStates;
+use crate::{
+    animals::AnimalDefinitions, balance::GameBalance, states::GameStates,
+    villagers::VillagerDefinitions,
+};
 
 pub struct AssetsManagerPlugin;
 
@@ -30,6 +33,8 @@ pub struct AssetsManager {
     pub sound_spawn_chimera: Handle<bevy_kira_audio::AudioSource>,
     #[asset(path = "sounds/button.ogg")]
     pub sound_button: Handle<bevy_kira_audio::AudioSource>,
+    #[asset(path = "sounds/dying_chimera.wav")]
+    pub sound_dying_chimera: Handle<bevy_kira_audio::AudioSource>,
     // images
     #[asset(path = "lighting.png")]
     pub texture_lightning: Handle<Image>,
@@ -41,35 +46,21 @@ pub struct AssetsManager {
     pub texture_ui_background: Handle<Image>,
     #[asset(path = "target.png")]
     pub texture_target: Handle<Image>,
-    // chimera parts
-    #[asset(path = "pig.png")]
-    pub texture_pig: Handle<Image>,
-    #[asset(path = "pighead.png")]
-    pub texture_pig_head: Handle<Image>,
-    #[asset(path = "pigtail.png")]
-    pub texture_pig_tail: Handle<Image>,
-    #[asset(path = "cow.png")]
-    pub texture_cow: Handle<Image>,
-    #[asset(path = "cowhead.png")]
-    pub texture_cow_head: Handle<Image>,
-    #[asset(path = "cowtail.png")]
-    pub texture_cow_tail: Handle<Image>,
-    #[asset(path = "dog.png")]
-    pub texture_dog: Handle<Image>,
-    #[asset(path = "doghead.png")]
-    pub texture_dog_head: Handle<Image>,
-    #[asset(path = "dogtail.png")]
-    pub texture_dog_tail: Handle<Image>,
-    #[asset(path = "chicken.png")]
-    pub texture_chicken: Handle<Image>,
-    #[asset(path = "chickenhead.png")]
-    pub texture_chicken_head: Handle<Image>,
-    #[asset(path = "chickentail.png")]
-    pub texture_chicken_tail: Handle<Image>,
-    #[asset(path = "horse.png")]
-    pub texture_horse: Handle<Image>,
-    #[asset(path = "horsehead.png")]
-    pub texture_horse_head: Handle<Image>,
-    #[asset(path = "horsetail.png")]
-    pub texture_horse_tail: Handle<Image>,
+    #[asset(path = "small_triangle.png")]
+    pub texture_projectile: Handle<Image>,
+    #[asset(path = "traits/bite.png")]
+    pub texture_trait_bite: Handle<Image>,
+    #[asset(path = "traits/flutter.png")]
+    pub texture_trait_flutter: Handle<Image>,
+    #[asset(path = "traits/charge.png")]
+    pub texture_trait_charge: Handle<Image>,
+    #[asset(path = "traits/sturdy.png")]
+    pub texture_trait_sturdy: Handle<Image>,
+    // data
+    #[asset(path = "data/farm.animals.ron")]
+    pub animal_definitions: Handle<AnimalDefinitions>,
+    #[asset(path = "data/village.villagers.ron")]
+    pub villager_definitions: Handle<VillagerDefinitions>,
+    #[asset(path = "data/game.balance.ron")]
+    pub game_balance: Handle<GameBalance>,
 }
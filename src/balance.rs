@@ -0,0 +1,384 @@
+use anyhow::{anyhow, bail};
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use serde::Deserialize;
+
+use crate::{
+    animals::AnimalKind,
+    assets_manager::AssetsManager,
+    chimeras::{ChimeraSlot, TraitSet},
+    states::GameStates,
+};
+
+pub struct BalancePlugin;
+
+impl Plugin for BalancePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<GameBalance>()
+            .init_asset_loader::<GameBalanceLoader>();
+
+        // the balance file is available once the assets are loaded
+        app.add_system_set(
+            SystemSet::on_enter(GameStates::MainMenu)
+                .with_system(setup_game_balance)
+                .label("setup_balance"),
+        );
+
+        // hot reloading can happen in any state
+        app.add_system(reload_game_balance);
+    }
+}
+
+// Every gameplay tuning value, read from the balance file and inserted as a resource.
+// The resource is replaced when the file changes on disk so that the next
+// gameplay decisions use the new values.
+#[derive(Debug, Clone, Deserialize, TypeUuid)]
+#[uuid = "c36f0f77-081a-4ecb-b85c-520cccd58bfb"]
+#[serde(deny_unknown_fields)]
+pub struct GameBalance {
+    pub day_cycle: DayCycleBalance,
+    pub head_split: PartStatsSplit,
+    pub tail_split: PartStatsSplit,
+    pub body_split: PartStatsSplit,
+    pub legs_split: PartStatsSplit,
+    pub wings_split: PartStatsSplit,
+    pub behaviors: BehaviorsBalance,
+    pub spawning: SpawningBalance,
+    pub stats: StatsBalance,
+    pub breeding: BreedingBalance,
+    pub synergies: SynergiesBalance,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DayCycleBalance {
+    // in seconds
+    pub day_length: f32,
+    pub starting_hour: f32,
+    pub max_alpha: f32,
+    pub min_alpha: f32,
+    pub night_hours: f32,
+}
+
+// Share of the animal stats given to a chimera part
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PartStatsSplit {
+    pub speed: f32,
+    pub accel: f32,
+    pub decel: f32,
+    pub health: f32,
+    pub attack: f32,
+    pub regen: f32,
+    pub range: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BehaviorsBalance {
+    pub chimera_follow_range: f32,
+    pub chimera_follow_distance: f32,
+    pub chimera_pursue_range: f32,
+    // villagers this close to a guarded point are pursued
+    pub chimera_guard_range: f32,
+    // chimeras go back to the player below this share of their health, until
+    // healed past the recover share
+    pub chimera_retreat_health: f32,
+    pub chimera_recover_health: f32,
+    pub animal_runaway_range: f32,
+    pub animal_idle_range: f32,
+    // animals of the same species this close form a herd
+    pub animal_herd_range: f32,
+    // animals farther than this from the center of their herd go back to it
+    pub animal_herd_distance: f32,
+    pub chimera_idle_duration: f32,
+    pub chimera_idle_duration_spread: f32,
+    pub animal_idle_duration: f32,
+    pub animal_idle_duration_spread: f32,
+    pub animal_regen_rate: f32,
+    pub villager_regen_rate: f32,
+    // villagers run away below this share of their health, until healed past
+    // the recover share
+    pub villager_flee_health: f32,
+    pub villager_recover_health: f32,
+    // threats this close are run away from
+    pub villager_flee_range: f32,
+    // threat felt by a villager from a target at its feet, fading to nothing
+    // at the threat range
+    pub villager_threat_proximity: f32,
+    pub villager_threat_range: f32,
+    // threat the player always has, so that villagers with nothing around go
+    // after it
+    pub villager_player_threat: f32,
+    // threat gained per point of damage taken from a unit
+    pub villager_threat_per_damage: f32,
+    // share of the threat from damage and taunts lost every second
+    pub villager_threat_decay: f32,
+    pub chimera_attack_rate: f32,
+    // chimeras draw the attention of the villagers this close to them, once
+    // every cooldown
+    pub chimera_taunt_range: f32,
+    pub chimera_taunt_threat: f32,
+    pub chimera_taunt_cooldown: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpawningBalance {
+    pub animal_spawn_min: Vec2,
+    pub animal_spawn_max: Vec2,
+    pub animal_base_spawn_duration: f32,
+    pub animal_spawn_factor: f32,
+    pub animal_spawn_hours: (u8, u8),
+    pub villager_base_spawn_duration: f32,
+    pub villager_spawn_factor: f32,
+    pub villager_spawn_hours: (u8, u8),
+    pub villager_spawn_locations: Vec<Vec2>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatsBalance {
+    pub animal_deviation: f32,
+    pub villager_deviation: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BreedingBalance {
+    // distance under which two chimeras ordered to breed pair up
+    pub range: f32,
+    // no chimera is bred once the ranch has this many
+    pub max_chimeras: usize,
+    // in seconds, also the delay before a new chimera can breed
+    pub cooldown: f32,
+    // highest share of a stat gained or lost by an offspring
+    pub mutation: f32,
+}
+
+// Traits given to a chimera by its head and tail
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SynergiesBalance {
+    // head and tail from the same species
+    pub same_species: TraitSet,
+    pub pairs: Vec<SynergyPair>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SynergyPair {
+    pub head: AnimalKind,
+    pub tail: AnimalKind,
+    pub traits: TraitSet,
+}
+
+impl GameBalance {
+    // share of the animal stats given to the chimera part of a slot
+    pub fn part_split(&self, slot: ChimeraSlot) -> &PartStatsSplit {
+        match slot {
+            ChimeraSlot::Head => &self.head_split,
+            ChimeraSlot::Body => &self.body_split,
+            ChimeraSlot::Legs => &self.legs_split,
+            ChimeraSlot::Wings => &self.wings_split,
+            ChimeraSlot::Tail => &self.tail_split,
+        }
+    }
+
+    // parses and validates the content of a balance file
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let balance: GameBalance =
+            ron::de::from_bytes(bytes).map_err(|err| anyhow!("malformed balance file: {}", err))?;
+
+        balance
+            .validate()
+            .map_err(|err| anyhow!("invalid balance file: {}", err))?;
+
+        Ok(balance)
+    }
+
+    // checks the values that would otherwise break the game at runtime
+    fn validate(&self) -> anyhow::Result<()> {
+        let positive_values = [
+            ("day_cycle.day_length", self.day_cycle.day_length),
+            (
+                "spawning.animal_base_spawn_duration",
+                self.spawning.animal_base_spawn_duration,
+            ),
+            (
+                "spawning.villager_base_spawn_duration",
+                self.spawning.villager_base_spawn_duration,
+            ),
+            (
+                "behaviors.chimera_attack_rate",
+                self.behaviors.chimera_attack_rate,
+            ),
+            (
+                "behaviors.chimera_taunt_cooldown",
+                self.behaviors.chimera_taunt_cooldown,
+            ),
+            (
+                "behaviors.villager_threat_range",
+                self.behaviors.villager_threat_range,
+            ),
+            ("breeding.cooldown", self.breeding.cooldown),
+            (
+                "behaviors.chimera_idle_duration",
+                self.behaviors.chimera_idle_duration,
+            ),
+            (
+                "behaviors.animal_idle_duration",
+                self.behaviors.animal_idle_duration,
+            ),
+            (
+                "behaviors.animal_regen_rate",
+                self.behaviors.animal_regen_rate,
+            ),
+            (
+                "behaviors.villager_regen_rate",
+                self.behaviors.villager_regen_rate,
+            ),
+        ];
+        for (name, value) in positive_values {
+            if !value.is_finite() || value <= 0.0 {
+                bail!("`{}` must be strictly positive, got {}", name, value);
+            }
+        }
+
+        for (name, value) in [
+            ("stats.animal_deviation", self.stats.animal_deviation),
+            ("stats.villager_deviation", self.stats.villager_deviation),
+            ("breeding.mutation", self.breeding.mutation),
+            (
+                "behaviors.villager_threat_decay",
+                self.behaviors.villager_threat_decay,
+            ),
+        ] {
+            if !(0.0..1.0).contains(&value) {
+                bail!("`{}` must be in [0, 1), got {}", name, value);
+            }
+        }
+
+        // the idle durations are drawn in duration +/- spread, and must stay positive
+        let behaviors = &self.behaviors;
+        for (name, duration, spread) in [
+            (
+                "behaviors.chimera_idle_duration_spread",
+                behaviors.chimera_idle_duration,
+                behaviors.chimera_idle_duration_spread,
+            ),
+            (
+                "behaviors.animal_idle_duration_spread",
+                behaviors.animal_idle_duration,
+                behaviors.animal_idle_duration_spread,
+            ),
+        ] {
+            if !(0.0..duration).contains(&spread) {
+                bail!("`{}` must be in [0, {}), got {}", name, duration, spread);
+            }
+        }
+
+        // units recovering under the health they retreat at would switch every tick
+        for (retreat_name, retreat, recover_name, recover) in [
+            (
+                "behaviors.chimera_retreat_health",
+                behaviors.chimera_retreat_health,
+                "behaviors.chimera_recover_health",
+                behaviors.chimera_recover_health,
+            ),
+            (
+                "behaviors.villager_flee_health",
+                behaviors.villager_flee_health,
+                "behaviors.villager_recover_health",
+                behaviors.villager_recover_health,
+            ),
+        ] {
+            if retreat >= recover {
+                bail!(
+                    "`{}` must be below `{}`, got {} and {}",
+                    retreat_name,
+                    recover_name,
+                    retreat,
+                    recover
+                );
+            }
+        }
+
+        let spawning = &self.spawning;
+        if spawning.animal_spawn_min.x > spawning.animal_spawn_max.x
+            || spawning.animal_spawn_min.y > spawning.animal_spawn_max.y
+        {
+            bail!("`spawning.animal_spawn_min` must be below `spawning.animal_spawn_max`");
+        }
+
+        if spawning.villager_spawn_locations.is_empty() {
+            bail!("`spawning.villager_spawn_locations` must not be empty");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct GameBalanceLoader;
+
+impl AssetLoader for GameBalanceLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let balance = GameBalance::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(balance));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["balance.ron"]
+    }
+}
+
+fn setup_game_balance(
+    mut commands: Commands,
+    assets: Res<AssetsManager>,
+    balances: Res<Assets<GameBalance>>,
+) {
+    // the assets manager only leaves the loading state once the balance is loaded
+    let balance = balances
+        .get(&assets.game_balance)
+        .expect("game balance should be loaded");
+
+    commands.insert_resource(balance.clone());
+}
+
+// replaces the balance resource when the file is modified on disk
+fn reload_game_balance(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<GameBalance>>,
+    balances: Res<Assets<GameBalance>>,
+    assets: Option<Res<AssetsManager>>,
+) {
+    let assets = if let Some(assets) = assets {
+        assets
+    } else {
+        return;
+    };
+
+    for event in asset_events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            if *handle != assets.game_balance {
+                continue;
+            }
+
+            if let Some(balance) = balances.get(handle) {
+                info!("game balance reloaded");
+                commands.insert_resource(balance.clone());
+            }
+        }
+    }
+}
@@ -1,79 +1,134 @@
 use bevy::prelude::*;
-use bevy_kira_audio::AudioChannel;
-use rand::Rng;
 
 use crate::{
     assets_manager::AssetsManager,
-    chimeras::{ChimeraComponent, ChimeraSprite},
+    balance::GameBalance,
+    chimeras::{trait_total, ChimeraBehavior, ChimeraComponent, TraitKind},
+    damage::{DamageEvent, DamageKind},
     health::Health,
     player::Player,
-    sound_manager::ChimeraHitAudioChannel,
-    villagers::{VillagerComponent, VillagerSprite},
+    projectile::{spawn_projectile, Projectile, ProjectileHits},
+    schedule::tick_delta,
+    villagers::{ThreatTable, VillagerAbility, VillagerComponent},
 };
 
+#[allow(clippy::too_many_arguments)]
 pub fn villager_attack_system(
-    assets: Res<AssetsManager>,
-    mut villager_query: Query<(&mut VillagerComponent, &Transform)>,
-    mut chimera_query: Query<(&mut Health, &Transform, &Children, &mut ChimeraComponent)>,
-    mut player_query: Query<
-        (&mut Health, &Transform, &Player, &mut TextureAtlasSprite),
-        (
-            Without<ChimeraComponent>,
-            Without<VillagerComponent>,
-            Without<ChimeraSprite>,
-        ),
-    >,
-    mut chimera_sprite_query: Query<&mut Sprite, With<ChimeraSprite>>,
-    time: Res<Time>,
-    hit_audio: Res<AudioChannel<ChimeraHitAudioChannel>>,
+    mut commands: Commands,
+    mut villager_query: Query<(Entity, &mut VillagerComponent, &Transform, &ThreatTable)>,
+    mut health_query: Query<(Entity, &mut Health, &Transform), With<VillagerComponent>>,
+    chimera_query: Query<(Entity, &Transform), With<ChimeraComponent>>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    target_query: Query<&Transform, Without<VillagerComponent>>,
+    mut damage_events: EventWriter<DamageEvent>,
+    assets: Option<Res<AssetsManager>>,
 ) {
-    for (mut villager, villager_transform) in villager_query.iter_mut() {
-        villager.attack_timer.tick(time.delta());
-        let villager_pos = Vec2::new(
-            villager_transform.translation.x,
-            villager_transform.translation.y,
-        );
+    let player = player_query
+        .iter()
+        .next()
+        .map(|(entity, transform)| (entity, transform.translation.truncate()));
+    let chimeras: Vec<(Entity, Vec2)> = chimera_query
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation.truncate()))
+        .collect();
 
-        if villager.attack_timer.just_finished() {
-            //execute attack on first chimera in range
-            if let Some((mut player_health, player_transform, player, mut player_sprite)) =
-                player_query.iter_mut().next()
-            {
-                info!("player_damaged");
-                let player_pos = Vec2::new(
-                    player_transform.translation.x,
-                    player_transform.translation.y,
-                );
-                if villager_pos.distance(player_pos) < villager.stats.range {
-                    //hit_audio.set_playback_rate(rand::thread_rng().gen_range(0.3..1.8));
-                    //hit_audio.play(assets.sound_hit.clone());
-                    player_health.health -= villager.stats.attack;
-                    player_sprite.color.set_r(255.0);
-                    info!("player_damaged");
-                    break;
+    for (villager_entity, mut villager, villager_transform, threats) in villager_query.iter_mut() {
+        villager.attack_timer.tick(tick_delta());
+        if !villager.attack_timer.just_finished() {
+            continue;
+        }
+
+        let villager_pos = villager_transform.translation.truncate();
+        let range = villager.stats.range;
+        let in_range = |(_, position): &(Entity, Vec2)| villager_pos.distance(*position) < range;
+
+        // the target picked from the threat table, when it is in range
+        let threat_target = threats
+            .target
+            .and_then(|target| {
+                target_query
+                    .get(target)
+                    .ok()
+                    .map(|transform| (target, transform.translation.truncate()))
+            })
+            .filter(in_range);
+
+        match villager.ability {
+            // otherwise the player, then the first chimera in range
+            VillagerAbility::Melee => {
+                let target = threat_target
+                    .or_else(|| player.filter(in_range))
+                    .or_else(|| chimeras.iter().copied().find(in_range));
+
+                if let Some((target, _)) = target {
+                    // torches burn chimeras harder
+                    let bonus = if chimera_query.contains(target) {
+                        villager.stats.chimera_bonus
+                    } else {
+                        0.0
+                    };
+                    damage_events.send(DamageEvent {
+                        source: villager_entity,
+                        target,
+                        amount: villager.stats.attack * (1.0 + bonus),
+                        kind: DamageKind::Melee,
+                    });
                 }
             }
-            for (mut chimera_health, chimera_transform, children, mut chimera) in
-                chimera_query.iter_mut()
-            {
-                let chimera_pos = Vec2::new(
-                    chimera_transform.translation.x,
-                    chimera_transform.translation.y,
-                );
+            // otherwise shoots at the closest of the player and the chimeras
+            VillagerAbility::Ranged {
+                projectile_speed, ..
+            } => {
+                let target = threat_target.or_else(|| {
+                    player
+                        .iter()
+                        .chain(chimeras.iter())
+                        .copied()
+                        .filter(in_range)
+                        .min_by(|(_, a), (_, b)| {
+                            villager_pos
+                                .distance(*a)
+                                .total_cmp(&villager_pos.distance(*b))
+                        })
+                });
 
-                if villager_pos.distance(chimera_pos) < villager.stats.range {
-                    // play sound
-                    hit_audio.set_playback_rate(rand::thread_rng().gen_range(0.3..1.8));
-                    hit_audio.play(assets.sound_hit.clone());
-
-                    chimera_health.health -= villager.stats.attack;
-                    for &child in children.iter() {
-                        if let Ok(mut chimera_sprite) = chimera_sprite_query.get_mut(child) {
-                            chimera_sprite.color.set_r(255.0);
-                            chimera.damage_timer.reset();
-                        }
-                    }
-                    break;
+                if let Some((_, target_pos)) = target {
+                    let direction = (target_pos - villager_pos).normalize_or_zero();
+                    let projectile = Projectile {
+                        despawn_timer: Timer::from_seconds(range / projectile_speed, false),
+                        damage: villager.stats.attack,
+                        owner: villager_entity,
+                        hits: ProjectileHits::Ranch,
+                    };
+
+                    // there is no texture when running headless
+                    spawn_projectile(
+                        &mut commands,
+                        projectile,
+                        villager_pos + direction * 30.0,
+                        direction * projectile_speed,
+                        assets
+                            .as_ref()
+                            .map(|assets| assets.texture_projectile.clone())
+                            .unwrap_or_default(),
+                    );
+                }
+            }
+            // heals the most hurt of the other villagers in range
+            VillagerAbility::Heal => {
+                let hurt = health_query
+                    .iter_mut()
+                    .filter(|(entity, health, transform)| {
+                        *entity != villager_entity
+                            && health.health < health.max_health
+                            && villager_pos.distance(transform.translation.truncate()) < range
+                    })
+                    .min_by(|(_, a, _), (_, b, _)| {
+                        (a.health / a.max_health).total_cmp(&(b.health / b.max_health))
+                    });
+
+                if let Some((_, mut health, _)) = hurt {
+                    health.health = (health.health + villager.stats.attack).min(health.max_health);
                 }
             }
         }
@@ -81,38 +136,72 @@ pub fn villager_attack_system(
 }
 
 pub fn chimera_attack_system(
-    mut chimera_query: Query<(&mut ChimeraComponent, &Transform)>,
-    mut villager_query: Query<(&mut Health, &Transform, &Children, &mut VillagerComponent)>,
-    mut villager_sprite_query: Query<&mut Sprite, With<VillagerSprite>>,
-    time: Res<Time>,
+    mut chimera_query: Query<(Entity, &mut ChimeraComponent, &Transform)>,
+    villager_query: Query<(Entity, &Transform), With<VillagerComponent>>,
+    mut damage_events: EventWriter<DamageEvent>,
 ) {
-    for (mut chimera, chimera_transform) in chimera_query.iter_mut() {
-        chimera.attack_timer.tick(time.delta());
+    for (chimera_entity, mut chimera, chimera_transform) in chimera_query.iter_mut() {
+        chimera.attack_timer.tick(tick_delta());
         let chimera_pos = Vec2::new(
             chimera_transform.translation.x,
             chimera_transform.translation.y,
         );
 
         if chimera.attack_timer.just_finished() {
-            for (mut villager_health, villager_transform, children, mut villager) in
-                villager_query.iter_mut()
-            {
+            for (villager_entity, villager_transform) in villager_query.iter() {
                 let villager_pos = Vec2::new(
                     villager_transform.translation.x,
                     villager_transform.translation.y,
                 );
 
                 if chimera_pos.distance(villager_pos) < chimera.stats.range {
-                    villager_health.health -= chimera.stats.attack;
-                    for &child in children.iter() {
-                        if let Ok(mut villager_sprite) = villager_sprite_query.get_mut(child) {
-                            villager_sprite.color.set_r(255.0);
-                            villager.damage_timer.reset();
-                        }
-                    }
+                    let bite = trait_total(&chimera.traits, TraitKind::Bite);
+                    damage_events.send(DamageEvent {
+                        source: chimera_entity,
+                        target: villager_entity,
+                        amount: chimera.stats.attack * (1.0 + bite),
+                        kind: DamageKind::Melee,
+                    });
                     break;
                 }
             }
         }
     }
 }
+
+// Chimeras draw the attention of the villagers around them, so that they go
+// after the chimeras instead of the player
+pub fn chimera_taunt_system(
+    mut chimera_query: Query<(Entity, &mut ChimeraComponent, &Transform)>,
+    mut villager_query: Query<(&mut ThreatTable, &Transform)>,
+    balance: Res<GameBalance>,
+) {
+    let balance = &balance.behaviors;
+
+    for (chimera_entity, mut chimera, chimera_transform) in chimera_query.iter_mut() {
+        chimera.taunt_timer.tick(tick_delta());
+
+        // a retreating chimera would rather be left alone
+        let retreating = matches!(chimera.behavior, ChimeraBehavior::Retreat { .. });
+        if !chimera.taunt_timer.just_finished() || retreating {
+            continue;
+        }
+
+        let chimera_pos = chimera_transform.translation.truncate();
+        let mut taunted = 0;
+        for (mut threats, villager_transform) in villager_query.iter_mut() {
+            let villager_pos = villager_transform.translation.truncate();
+            if chimera_pos.distance(villager_pos) < balance.chimera_taunt_range {
+                threats.add(chimera_entity, balance.chimera_taunt_threat);
+                taunted += 1;
+            }
+        }
+
+        if taunted > 0 {
+            info!(
+                "chimera {} taunted {} villager(s)",
+                chimera.lineage.id, taunted
+            );
+        }
+    }
+}
@@ -6,13 +6,14 @@ use std::time::Duration;
 
 use crate::animals::AnimalStats;
 use crate::chimeras::ChimeraStats;
+use crate::navigation::{NavGrid, NavPath};
+use crate::selection::SelectionMarker;
 use crate::villagers::VillagerStats;
 
-const ROUND_ZERO_RANGE: f32 = 10.0;
-
 mod attack;
+pub mod steering;
 
-pub use self::attack::{chimera_attack_system, villager_attack_system};
+pub use self::attack::{chimera_attack_system, chimera_taunt_system, villager_attack_system};
 
 pub struct UnitStats {
     pub speed: f32,
@@ -20,8 +21,8 @@ pub struct UnitStats {
     pub decel: f32,
 }
 
-impl From<AnimalStats> for UnitStats {
-    fn from(stats: AnimalStats) -> Self {
+impl From<&AnimalStats> for UnitStats {
+    fn from(stats: &AnimalStats) -> Self {
         Self {
             speed: stats.speed,
             accel: stats.accel,
@@ -50,187 +51,136 @@ impl From<VillagerStats> for UnitStats {
     }
 }
 
-// Enum that describes behaviors for animals
+// Each unit type has its own behavior enum, listing the states it supports:
+// `AnimalBehavior`, `ChimeraBehavior` and `VillagerBehavior`. The functions
+// below move a unit according to a state and are shared between them
+
+// Wandering around, moving in a random direction then pausing in turn
 #[derive(Clone)]
-pub enum UnitBehavior {
-    Idle {
-        timer: Timer,
-        base_duration: f32,
-        duration_spread: f32,
-        direction: Vec2,
-        is_moving: bool,
-    },
-    Pursue {
-        target: Option<Vec2>,
-    },
-    Follow {
-        target: Option<Vec2>,
-        distance: f32,
-    },
-    RunAway {
-        target: Option<Vec2>,
-    },
+pub struct Wander {
+    pub timer: Timer,
+    pub base_duration: f32,
+    pub duration_spread: f32,
+    pub direction: Vec2,
+    pub is_moving: bool,
 }
-// Handle animal idling behavior
+
+impl Wander {
+    pub fn new(base_duration: f32, duration_spread: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(base_duration, false),
+            base_duration,
+            duration_spread,
+            direction: Vec2::default(),
+            is_moving: false,
+        }
+    }
+}
+
+// Which way the sprites of a unit look, they are drawn facing right
+#[derive(Component, Default, Clone, Copy)]
+pub struct Facing {
+    pub left: bool,
+}
+
+// Handle idling behavior
 pub fn idle_behavior(
     vel: &mut Velocity,
-    sprites: Vec<&mut Sprite>,
-    time: &Time,
-    timer: &mut Timer,
-    base_duration: &f32,
-    duration_spread: &f32,
-    direction: &mut Vec2,
-    is_moving: &mut bool,
+    delta: Duration,
+    wander: &mut Wander,
     stats: UnitStats,
+    rng: &mut impl Rng,
 ) {
-    timer.tick(time.delta());
-    let old_linvel = vel.linvel;
+    wander.timer.tick(delta);
 
-    if timer.just_finished() {
-        timer.set_duration(Duration::from_secs_f32(
-            base_duration - rand::thread_rng().gen_range(-duration_spread..*duration_spread),
+    if wander.timer.just_finished() {
+        let spread = wander.duration_spread;
+        wander.timer.set_duration(Duration::from_secs_f32(
+            wander.base_duration - rng.gen_range(-spread..=spread),
         ));
-        timer.reset();
+        wander.timer.reset();
 
-        let dir: [f32; 2] = UnitCircle.sample(&mut rand::thread_rng());
+        let dir: [f32; 2] = UnitCircle.sample(rng);
 
-        direction.x = dir[0];
-        direction.y = dir[1];
+        wander.direction.x = dir[0];
+        wander.direction.y = dir[1];
 
-        *is_moving ^= true;
+        wander.is_moving ^= true;
     }
 
-    if *is_moving {
-        if vel.linvel.x.abs() < (stats.speed * direction.x).abs() {
-            vel.linvel.x += stats.accel * direction.x;
-        }
-
-        if vel.linvel.y.abs() < (stats.speed * direction.y).abs() {
-            vel.linvel.y += stats.accel * direction.y;
-        }
-    } else if (vel.linvel.x.abs().powf(2.0) + vel.linvel.y.abs().powf(2.0)).sqrt()
-        - ROUND_ZERO_RANGE
-        < 0.0
-    {
-        vel.linvel.x = 0.0;
-        vel.linvel.y = 0.0;
-    } else {
-        if vel.linvel.x > 0.0 {
-            vel.linvel.x -= stats.decel * direction.x.abs();
-        } else {
-            vel.linvel.x += stats.decel * direction.x.abs();
-        }
-        if vel.linvel.y > 0.0 {
-            vel.linvel.y -= stats.decel * direction.y.abs();
-        } else {
-            vel.linvel.y += stats.decel * direction.y.abs();
-        }
-    }
-
-    if old_linvel.x.is_sign_positive() != vel.linvel.x.is_sign_positive() {
-        for sprite in sprites {
-            sprite.flip_x ^= true;
-        }
-    }
+    vel.linvel = steering::wander(vel.linvel, wander.direction, wander.is_moving, &stats);
 }
 
-// Handle pursue behavior
+// Handle pursue behavior, going around the obstacles
 pub fn pursue_behavior(
     vel: &mut Velocity,
-    sprites: Vec<&mut Sprite>,
     stats: UnitStats,
     position: Vec2,
     target: Option<Vec2>,
+    grid: &NavGrid,
+    path: &mut NavPath,
 ) {
     if let Some(target) = target {
-        let direction = (target - position).normalize();
-        let old_linvel = vel.linvel;
-
-        vel.linvel.x += stats.accel * direction.x;
-        vel.linvel.y += stats.accel * direction.y;
-
-        if vel.linvel.x.abs() > stats.speed * direction.x.abs() {
-            vel.linvel.x = stats.speed * direction.x;
-        }
-        if vel.linvel.y.abs() > stats.speed * direction.y.abs() {
-            vel.linvel.y = stats.speed * direction.y;
-        }
-
-        if old_linvel.x.is_sign_positive() != vel.linvel.x.is_sign_positive() {
-            for sprite in sprites {
-                sprite.flip_x ^= true;
-            }
-        }
+        let direction = path.direction(grid, position, target);
+        vel.linvel = steering::seek(vel.linvel, direction, &stats);
     }
 }
 
-// Handle pursue behavior
+// Slows down until stopping
+pub fn stop_behavior(vel: &mut Velocity, stats: UnitStats) {
+    vel.linvel = steering::stop(vel.linvel, &stats);
+}
+
+// Handle follow behavior, going around the obstacles
 pub fn follow_behavior(
     vel: &mut Velocity,
-    sprites: Vec<&mut Sprite>,
     stats: UnitStats,
     position: Vec2,
     target: Option<Vec2>,
     distance: f32,
+    grid: &NavGrid,
+    path: &mut NavPath,
 ) {
     if let Some(target) = target {
-        let direction = (target - position).normalize();
-
-        let old_linvel = vel.linvel;
-
-        if position.distance(target) > distance {
-            vel.linvel.x += stats.accel * direction.x;
-            vel.linvel.y += stats.accel * direction.y;
-        } else if (vel.linvel.x.abs().powf(2.0) + vel.linvel.y.abs().powf(2.0)).sqrt()
-            - ROUND_ZERO_RANGE
-            < 0.0
-        {
-            vel.linvel.x = 0.0;
-            vel.linvel.y = 0.0;
-        } else {
-            vel.linvel.x -= stats.decel * direction.x;
-            vel.linvel.y -= stats.decel * direction.y;
-        }
-
-        if vel.linvel.x.abs() > stats.speed * direction.x.abs() {
-            vel.linvel.x = stats.speed * direction.x;
-        }
-        if vel.linvel.y.abs() > stats.speed * direction.y.abs() {
-            vel.linvel.y = stats.speed * direction.y;
-        }
-
-        if old_linvel.x.is_sign_positive() != vel.linvel.x.is_sign_positive() {
-            for sprite in sprites {
-                sprite.flip_x ^= true;
-            }
-        }
+        let direction = path.direction(grid, position, target);
+        vel.linvel = steering::arrive(
+            vel.linvel,
+            direction,
+            position.distance(target),
+            distance,
+            &stats,
+        );
     }
 }
 
-// Handle pursue behavior
+// Handle run away behavior
 pub fn run_away_behavior(
     vel: &mut Velocity,
-    sprites: Vec<&mut Sprite>,
     stats: UnitStats,
     position: Vec2,
     target: Option<Vec2>,
 ) {
     if let Some(target) = target {
-        let direction = (position - target).normalize();
-        let old_linvel = vel.linvel;
-
-        vel.linvel.x += stats.accel * direction.x;
-        vel.linvel.y += stats.accel * direction.y;
+        vel.linvel = steering::flee(vel.linvel, position, target, &stats);
+    }
+}
 
-        if vel.linvel.x.abs() > stats.speed * direction.x.abs() {
-            vel.linvel.x = stats.speed * direction.x;
-        }
-        if vel.linvel.y.abs() > stats.speed * direction.y.abs() {
-            vel.linvel.y = stats.speed * direction.y;
+// Turns the sprites of the units around when they change horizontal direction
+pub fn facing_system(
+    mut unit_query: Query<(&Velocity, &mut Facing, &Children)>,
+    mut sprite_query: Query<&mut Sprite, Without<SelectionMarker>>,
+) {
+    for (vel, mut facing, children) in unit_query.iter_mut() {
+        let left = steering::faces_left(vel.linvel, facing.left);
+        if left == facing.left {
+            continue;
         }
+        facing.left = left;
 
-        if old_linvel.x.is_sign_positive() != vel.linvel.x.is_sign_positive() {
-            for sprite in sprites {
+        // parts of a chimera can be flipped on their own, so sprites are
+        // toggled rather than set
+        for child in children.iter() {
+            if let Ok(mut sprite) = sprite_query.get_mut(*child) {
                 sprite.flip_x ^= true;
             }
         }
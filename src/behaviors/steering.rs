@@ -0,0 +1,223 @@
+use bevy::math::Vec2;
+
+use super::UnitStats;
+
+// slower velocities are rounded to a stop
+pub const STOP_SPEED: f32 = 10.0;
+// units slow down over this distance before arriving
+pub const ARRIVE_SLOWING_DISTANCE: f32 = 50.0;
+// units closer than this push each other away
+pub const SEPARATION_RANGE: f32 = 40.0;
+// sprites only turn around past this horizontal speed, so that they do not
+// flicker while a unit moves vertically or stops
+pub const TURN_SPEED: f32 = 5.0;
+
+// changes a velocity toward the desired one, by at most the acceleration of the
+// unit when speeding up and its deceleration when slowing down
+pub fn steer(velocity: Vec2, desired: Vec2, stats: &UnitStats) -> Vec2 {
+    let max_force = if desired.length() < velocity.length() {
+        stats.decel
+    } else {
+        stats.accel
+    };
+
+    velocity + (desired - velocity).clamp_length_max(max_force)
+}
+
+// full speed in a direction
+pub fn seek(velocity: Vec2, direction: Vec2, stats: &UnitStats) -> Vec2 {
+    steer(velocity, direction.normalize_or_zero() * stats.speed, stats)
+}
+
+// full speed away from a threat
+pub fn flee(velocity: Vec2, position: Vec2, threat: Vec2, stats: &UnitStats) -> Vec2 {
+    seek(velocity, position - threat, stats)
+}
+
+// moves in a direction until being `stop_distance` away from the destination,
+// slowing down on the way
+pub fn arrive(
+    velocity: Vec2,
+    direction: Vec2,
+    distance: f32,
+    stop_distance: f32,
+    stats: &UnitStats,
+) -> Vec2 {
+    if distance <= stop_distance {
+        return stop(velocity, stats);
+    }
+
+    let slowing = ((distance - stop_distance) / ARRIVE_SLOWING_DISTANCE).min(1.0);
+    steer(
+        velocity,
+        direction.normalize_or_zero() * stats.speed * slowing,
+        stats,
+    )
+}
+
+// moves along a heading while `moving`, stands still otherwise
+pub fn wander(velocity: Vec2, heading: Vec2, moving: bool, stats: &UnitStats) -> Vec2 {
+    if moving {
+        seek(velocity, heading, stats)
+    } else {
+        stop(velocity, stats)
+    }
+}
+
+// slows down until stopping
+pub fn stop(velocity: Vec2, stats: &UnitStats) -> Vec2 {
+    let velocity = steer(velocity, Vec2::ZERO, stats);
+    if velocity.length() < STOP_SPEED {
+        Vec2::ZERO
+    } else {
+        velocity
+    }
+}
+
+// push away from the neighbors too close, stronger the closer they are. The
+// position of the unit itself can be part of the neighbors
+pub fn separation(position: Vec2, neighbors: impl Iterator<Item = Vec2>) -> Vec2 {
+    neighbors
+        .map(|neighbor| {
+            let offset = position - neighbor;
+            let distance = offset.length();
+            if distance < SEPARATION_RANGE {
+                offset.normalize_or_zero() * (1.0 - distance / SEPARATION_RANGE)
+            } else {
+                Vec2::ZERO
+            }
+        })
+        .fold(Vec2::ZERO, |sum, push| sum + push)
+}
+
+// adds a separation push to a velocity, the push can not make a unit faster
+// than its max speed
+pub fn separate(velocity: Vec2, push: Vec2, stats: &UnitStats) -> Vec2 {
+    let max_speed = stats.speed.max(velocity.length());
+    (velocity + push * stats.accel).clamp_length_max(max_speed)
+}
+
+// whether a unit moving at a velocity faces left, it keeps its facing while
+// moving slowly horizontally
+pub fn faces_left(velocity: Vec2, facing_left: bool) -> bool {
+    if velocity.x < -TURN_SPEED {
+        true
+    } else if velocity.x > TURN_SPEED {
+        false
+    } else {
+        facing_left
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATS: UnitStats = UnitStats {
+        speed: 60.0,
+        accel: 2.0,
+        decel: 6.0,
+    };
+
+    fn approx(a: Vec2, b: Vec2) -> bool {
+        a.abs_diff_eq(b, 1e-4)
+    }
+
+    #[test]
+    fn steer_limits_acceleration_and_deceleration() {
+        let faster = steer(Vec2::ZERO, Vec2::new(60.0, 0.0), &STATS);
+        assert!(approx(faster, Vec2::new(2.0, 0.0)));
+
+        let slower = steer(Vec2::new(60.0, 0.0), Vec2::ZERO, &STATS);
+        assert!(approx(slower, Vec2::new(54.0, 0.0)));
+    }
+
+    #[test]
+    fn seek_reaches_the_same_speed_in_every_direction() {
+        for direction in [Vec2::X, Vec2::new(1.0, 1.0), Vec2::new(-3.0, 1.0)] {
+            let mut velocity = Vec2::ZERO;
+            for _ in 0..100 {
+                velocity = seek(velocity, direction, &STATS);
+            }
+
+            assert!((velocity.length() - STATS.speed).abs() < 1e-3);
+            assert!(approx(velocity.normalize(), direction.normalize()));
+        }
+    }
+
+    #[test]
+    fn seek_turns_without_exceeding_max_speed() {
+        let mut velocity = Vec2::new(60.0, 0.0);
+        for _ in 0..100 {
+            velocity = seek(velocity, Vec2::Y, &STATS);
+            assert!(velocity.length() <= STATS.speed + 1e-3);
+        }
+
+        assert!(approx(velocity, Vec2::new(0.0, 60.0)));
+    }
+
+    #[test]
+    fn flee_moves_away_from_the_threat() {
+        let velocity = flee(Vec2::ZERO, Vec2::new(10.0, 10.0), Vec2::ZERO, &STATS);
+        assert!(velocity.x > 0.0 && velocity.y > 0.0);
+        assert!((velocity.length() - STATS.accel).abs() < 1e-4);
+    }
+
+    #[test]
+    fn arrive_slows_down_then_stops() {
+        let far = arrive(Vec2::new(60.0, 0.0), Vec2::X, 500.0, 100.0, &STATS);
+        assert!(approx(far, Vec2::new(60.0, 0.0)));
+
+        // halfway through the slowing distance, the unit aims at half speed
+        let slowing = arrive(Vec2::new(60.0, 0.0), Vec2::X, 125.0, 100.0, &STATS);
+        assert!(approx(slowing, Vec2::new(54.0, 0.0)));
+
+        let arrived = arrive(Vec2::new(8.0, 0.0), Vec2::X, 90.0, 100.0, &STATS);
+        assert_eq!(arrived, Vec2::ZERO);
+    }
+
+    #[test]
+    fn stop_brakes_along_the_velocity() {
+        let velocity = stop(Vec2::new(30.0, 40.0), &STATS);
+        assert!(approx(velocity, Vec2::new(26.4, 35.2)));
+
+        assert_eq!(stop(Vec2::new(3.0, -4.0), &STATS), Vec2::ZERO);
+    }
+
+    #[test]
+    fn wander_stands_still_when_not_moving() {
+        let moving = wander(Vec2::ZERO, Vec2::Y, true, &STATS);
+        assert!(approx(moving, Vec2::new(0.0, 2.0)));
+
+        assert_eq!(wander(Vec2::ZERO, Vec2::Y, false, &STATS), Vec2::ZERO);
+    }
+
+    #[test]
+    fn separation_pushes_away_from_close_neighbors() {
+        let position = Vec2::ZERO;
+        let neighbors = [position, Vec2::new(10.0, 0.0), Vec2::new(0.0, 100.0)];
+
+        let push = separation(position, neighbors.into_iter());
+        assert!(approx(push, Vec2::new(-0.75, 0.0)));
+    }
+
+    #[test]
+    fn separate_does_not_exceed_max_speed() {
+        let velocity = separate(Vec2::new(60.0, 0.0), Vec2::new(1.0, 0.0), &STATS);
+        assert!(approx(velocity, Vec2::new(60.0, 0.0)));
+
+        let velocity = separate(Vec2::ZERO, Vec2::new(0.0, 0.5), &STATS);
+        assert!(approx(velocity, Vec2::new(0.0, 1.0)));
+    }
+
+    #[test]
+    fn facing_ignores_slow_horizontal_moves() {
+        assert!(faces_left(Vec2::new(-20.0, 0.0), false));
+        assert!(!faces_left(Vec2::new(20.0, 0.0), true));
+
+        // stopping, moving vertically and -0.0 keep the facing
+        assert!(faces_left(Vec2::ZERO, true));
+        assert!(faces_left(Vec2::new(2.0, 60.0), true));
+        assert!(!faces_left(Vec2::new(-0.0, 0.0), false));
+    }
+}
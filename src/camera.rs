@@ -1,6 +1,6 @@
-use bevy::{prelude::*, render::camera::RenderTarget};
+use bevy::prelude::*;
 
-use crate::states::GameStates;
+use crate::{game_input::WorldCursor, settings::Settings, states::GameStates};
 
 pub struct CameraPlugin;
 
@@ -8,6 +8,8 @@ impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         // cameras should always be there
         app.add_startup_system(setup_camera);
+        // every frame, the camera follows the settings
+        app.add_system(apply_camera_settings);
 
         // on update
         app.add_system_set(
@@ -25,12 +27,12 @@ pub struct MainCamera {
 #[derive(Component)]
 pub struct CameraTarget;
 
-fn setup_camera(mut commands: Commands) {
+fn setup_camera(mut commands: Commands, settings: Res<Settings>) {
     commands
         .spawn_bundle(OrthographicCameraBundle::new_2d())
         .insert(MainCamera {
-            smooth_speed: 0.1,
-            target_to_mouse_percent: 0.1,
+            smooth_speed: settings.camera_smooth_speed,
+            target_to_mouse_percent: settings.camera_target_to_mouse_percent,
         });
 
     // let window = windows.get_primary().unwrap();
@@ -41,15 +43,32 @@ fn setup_camera(mut commands: Commands) {
     commands.spawn_bundle(UiCameraBundle::default());
 }
 
+// the window shrinks in logical pixels as the UI scale grows, the camera zooms
+// out as much so that the ranch looks the same
+fn apply_camera_settings(
+    settings: Res<Settings>,
+    mut q_camera: Query<(&mut MainCamera, &mut OrthographicProjection)>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (mut camera, mut projection) in q_camera.iter_mut() {
+        camera.smooth_speed = settings.camera_smooth_speed;
+        camera.target_to_mouse_percent = settings.camera_target_to_mouse_percent;
+        projection.scale = 1. / settings.effective_ui_scale();
+    }
+}
+
 // the camera movement system grabs the position between the player
 // and the mouse, then lerps the camera to this position
 fn camera_movement_system(
-    windows: Res<Windows>,
-    mut q_camera: Query<(&Camera, &GlobalTransform, &mut Transform, &MainCamera)>,
+    world_cursor: Res<WorldCursor>,
+    mut q_camera: Query<(&mut Transform, &MainCamera)>,
     q_target: Query<&GlobalTransform, With<CameraTarget>>,
 ) {
     // get the camera
-    let (camera, camera_gl_transform, mut camera_transform, camera_config) = q_camera.single_mut();
+    let (mut camera_transform, camera_config) = q_camera.single_mut();
 
     for target_gl_transform in q_target.iter() {
         // get target coordinates
@@ -58,37 +77,10 @@ fn camera_movement_system(
             target_gl_transform.translation.y,
         );
 
-        // get the current window
-        let curr_window = if let RenderTarget::Window(id) = camera.target {
-            windows.get(id).unwrap()
-        } else {
-            windows.get_primary().unwrap()
-        };
-
-        // check if the cursor is inside the window and get its position
-        // else set it to the player pos (no lerp between them)
-        let cursor_pos = if let Some(screen_pos) = curr_window.cursor_position() {
-            // get the size of the window
-            let window_size = Vec2::new(curr_window.width() as f32, curr_window.height() as f32);
-
-            // convert screen position [0..resolution] to ndc [-1..1] (gpu coordinates)
-            let ndc = (screen_pos / window_size) * 2.0 - Vec2::ONE;
-
-            // matrix for undoing the projection and camera transform
-            let ndc_to_world =
-                camera_gl_transform.compute_matrix() * camera.projection_matrix.inverse();
-
-            // use it to convert ndc to world-space coordinates
-            let world_pos = ndc_to_world.project_point3(ndc.extend(-1.0));
-
-            // reduce it to a 2D value
-            let world_pos: Vec2 = world_pos.truncate();
-
-            // use this value
-            world_pos
-        } else {
-            target_coords
-        };
+        // the cursor in the world, or the target itself (no lerp between them)
+        // when the cursor is outside of the window. The cursor can be moved by
+        // the mouse or by a gamepad
+        let cursor_pos = world_cursor.position.unwrap_or(target_coords);
 
         // set target position, 60% player, 40% cursor
         let camera_target_pos = Vec2::lerp(
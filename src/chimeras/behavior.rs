@@ -1,39 +1,91 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use super::{ChimeraComponent, ChimeraSprite};
-use crate::behaviors::UnitBehavior;
-use crate::constants::{CHIMERA_IDLE_DURATION, CHIMERA_IDLE_DURATION_SPREAD};
+use super::{current_order, trait_total, ChimeraComponent, ChimeraStats, OrderKind, TraitKind};
+use crate::balance::{BehaviorsBalance, GameBalance};
+use crate::behaviors::{self, steering, Wander};
+use crate::constants;
+use crate::health::Health;
+use crate::navigation::{NavGrid, NavPath};
 use crate::player::Player;
+use crate::rng::{GameRng, RngStream};
+use crate::schedule::tick_delta;
 use crate::villagers::VillagerComponent;
-use crate::{behaviors, constants};
 
-// Handles animals behaving according to their current behavior
+// States of a chimera
+#[derive(Clone)]
+pub enum ChimeraBehavior {
+    Idle(Wander),
+    Pursue {
+        target: Option<Vec2>,
+    },
+    Follow {
+        target: Option<Vec2>,
+        distance: f32,
+    },
+    // back to the player while badly hurt, villagers are ignored. The order it
+    // interrupted is carried out again once healed
+    Retreat {
+        target: Option<Vec2>,
+        resume: Option<Box<ChimeraBehavior>>,
+    },
+    // orders given by the player to a chimera
+    Stay {
+        position: Vec2,
+    },
+    Guard {
+        point: Vec2,
+    },
+    Attack {
+        target: Entity,
+    },
+}
+
+impl ChimeraBehavior {
+    pub fn idle(balance: &BehaviorsBalance) -> Self {
+        ChimeraBehavior::Idle(Wander::new(
+            balance.chimera_idle_duration,
+            balance.chimera_idle_duration_spread,
+        ))
+    }
+
+    // how new chimeras behave, they find the player on their own
+    pub fn follow(balance: &BehaviorsBalance) -> Self {
+        ChimeraBehavior::Follow {
+            target: None,
+            distance: balance.chimera_follow_distance,
+        }
+    }
+}
+
+// Moves the chimeras according to their current behavior or order, and
+// switches between following the player, pursuing villagers and retreating
 pub fn chimera_behavior_system(
-    time: Res<Time>,
-    mut chimera_query: Query<(&mut ChimeraComponent, &mut Velocity, &Transform, &Children)>,
-    mut sprite_query: Query<&mut Sprite, With<ChimeraSprite>>,
+    mut chimera_query: Query<(
+        &mut ChimeraComponent,
+        &mut Velocity,
+        &mut NavPath,
+        &Transform,
+        &Health,
+    )>,
     player_query: Query<&Transform, With<Player>>,
-    villager_query: Query<&Transform, With<VillagerComponent>>,
+    villager_query: Query<(Entity, &Transform), With<VillagerComponent>>,
+    grid: Res<NavGrid>,
+    balance: Res<GameBalance>,
+    mut rng: ResMut<GameRng>,
 ) {
-    for (mut chimera, mut vel, transform, children) in chimera_query.iter_mut() {
-        let sprite_entities = children.iter().take(2).copied().collect::<Vec<Entity>>();
-
-        let mut sprites: [Mut<Sprite>; 2] = sprite_query
-            .get_many_mut((*sprite_entities).try_into().unwrap())
-            .unwrap();
+    let balance = &balance.behaviors;
 
-        let (sprite_1, sprite_2) = sprites.split_at_mut(1);
-
-        if sprite_1[0].color.r() > 1.0 {
-            chimera.damage_timer.tick(time.delta());
-            if chimera.damage_timer.just_finished() {
-                sprite_1[0].color.set_r(1.0);
-                sprite_2[0].color.set_r(1.0);
-            }
-        }
+    let chimera_positions: Vec<Vec2> = chimera_query
+        .iter()
+        .map(|(_, _, _, transform, _)| transform.translation.truncate())
+        .collect();
 
+    for (mut chimera, mut vel, mut path, transform, health) in chimera_query.iter_mut() {
         let stats = chimera.stats;
+        // charging chimeras are faster while pursuing a villager
+        let mut charge_stats = stats;
+        charge_stats.speed *= 1.0 + trait_total(&chimera.traits, TraitKind::Charge);
 
         let player_transform = player_query.iter().next().unwrap();
 
@@ -46,7 +98,7 @@ pub fn chimera_behavior_system(
 
         let mut pursue_villager_pos = None;
 
-        for villager_transform in villager_query.iter() {
+        for (_, villager_transform) in villager_query.iter() {
             let test_pos = Vec2::new(
                 villager_transform.translation.x,
                 villager_transform.translation.y,
@@ -56,98 +108,179 @@ pub fn chimera_behavior_system(
                 if position.distance(test_pos) < position.distance(lowest_pos) {
                     pursue_villager_pos = Some(test_pos);
                 }
-            } else if position.distance(test_pos) < constants::CHIMERA_PURSUE_RANGE {
+            } else if position.distance(test_pos) < balance.chimera_pursue_range {
                 pursue_villager_pos = Some(test_pos);
             }
         }
 
+        // badly hurt chimeras drop what they are doing, unless told to stay
+        let health_share = health.health / health.max_health;
+        let can_retreat = !matches!(
+            chimera.behavior,
+            ChimeraBehavior::Retreat { .. } | ChimeraBehavior::Stay { .. }
+        );
+        if can_retreat && health_share < balance.chimera_retreat_health {
+            let resume = (current_order(&chimera.behavior) != OrderKind::Follow)
+                .then(|| Box::new(chimera.behavior.clone()));
+            chimera.behavior = ChimeraBehavior::Retreat {
+                target: Some(player_position),
+                resume,
+            };
+        }
+
         match &mut chimera.behavior {
-            UnitBehavior::Idle {
-                timer,
-                base_duration,
-                duration_spread,
-                direction,
-                is_moving,
-            } => {
+            ChimeraBehavior::Idle(wander) => {
                 behaviors::idle_behavior(
                     &mut vel,
-                    vec![&mut sprite_1[0], &mut sprite_2[0]],
-                    &time,
-                    timer,
-                    base_duration,
-                    duration_spread,
-                    direction,
-                    is_moving,
+                    tick_delta(),
+                    wander,
                     stats.into(),
+                    rng.stream(RngStream::Behaviors),
                 );
 
                 if let Some(villager_pos) = pursue_villager_pos {
-                    chimera.behavior = behaviors::UnitBehavior::Pursue {
+                    chimera.behavior = ChimeraBehavior::Pursue {
                         target: Some(villager_pos),
                     };
-                } else if position.distance(player_position) < constants::CHIMERA_FOLLOW_RANGE {
-                    chimera.behavior = behaviors::UnitBehavior::Follow {
+                } else if position.distance(player_position) < balance.chimera_follow_range {
+                    chimera.behavior = ChimeraBehavior::Follow {
                         target: Some(player_position),
-                        distance: constants::CHIMERA_FOLLOW_DISTANCE,
+                        distance: balance.chimera_follow_distance,
                     }
                 }
             }
-            UnitBehavior::Pursue { target } => {
+            ChimeraBehavior::Pursue { target } => {
                 behaviors::pursue_behavior(
                     &mut vel,
-                    vec![&mut sprite_1[0], &mut sprite_2[0]],
-                    stats.into(),
+                    charge_stats.into(),
                     position,
                     *target,
+                    &grid,
+                    &mut path,
                 );
 
                 if pursue_villager_pos.is_some() {
                     // target the player
-                    chimera.behavior = behaviors::UnitBehavior::Pursue {
+                    chimera.behavior = ChimeraBehavior::Pursue {
                         target: pursue_villager_pos,
                     }
                 } else {
-                    chimera.behavior = behaviors::UnitBehavior::Idle {
-                        timer: Timer::from_seconds(constants::CHIMERA_IDLE_DURATION, false),
-                        base_duration: CHIMERA_IDLE_DURATION,
-                        duration_spread: CHIMERA_IDLE_DURATION_SPREAD,
-                        direction: Vec2::default(),
-                        is_moving: false,
-                    }
+                    chimera.behavior = ChimeraBehavior::idle(balance)
                 }
             }
-            UnitBehavior::Follow { target, distance } => {
+            ChimeraBehavior::Follow { target, distance } => {
                 behaviors::follow_behavior(
                     &mut vel,
-                    vec![&mut sprite_1[0], &mut sprite_2[0]],
                     stats.into(),
                     position,
                     *target,
                     *distance,
+                    &grid,
+                    &mut path,
                 );
 
                 // target the player
 
-                chimera.behavior = behaviors::UnitBehavior::Follow {
+                chimera.behavior = ChimeraBehavior::Follow {
                     target: Some(player_position),
                     distance: *distance,
                 };
 
                 if let Some(villager_pos) = pursue_villager_pos {
-                    chimera.behavior = behaviors::UnitBehavior::Pursue {
+                    chimera.behavior = ChimeraBehavior::Pursue {
                         target: Some(villager_pos),
                     };
-                } else if position.distance(player_position) > constants::CHIMERA_FOLLOW_RANGE {
-                    chimera.behavior = behaviors::UnitBehavior::Idle {
-                        timer: Timer::from_seconds(constants::CHIMERA_IDLE_DURATION, false),
-                        base_duration: constants::CHIMERA_IDLE_DURATION,
-                        duration_spread: constants::CHIMERA_IDLE_DURATION_SPREAD,
-                        direction: Vec2::default(),
-                        is_moving: false,
-                    }
+                } else if position.distance(player_position) > balance.chimera_follow_range {
+                    chimera.behavior = ChimeraBehavior::idle(balance)
+                }
+            }
+            ChimeraBehavior::Retreat { target, resume } => {
+                behaviors::follow_behavior(
+                    &mut vel,
+                    stats.into(),
+                    position,
+                    *target,
+                    balance.chimera_follow_distance,
+                    &grid,
+                    &mut path,
+                );
+
+                if health_share >= balance.chimera_recover_health {
+                    chimera.behavior = match resume.take() {
+                        Some(order) => *order,
+                        None => ChimeraBehavior::follow(balance),
+                    };
+                } else {
+                    *target = Some(player_position);
                 }
             }
-            UnitBehavior::RunAway { target: _ } => todo!(),
+            ChimeraBehavior::Stay { position: hold } => {
+                hold_position(&mut vel, stats, position, *hold, &grid, &mut path);
+            }
+            ChimeraBehavior::Guard { point } => {
+                let point = *point;
+
+                // the closest villager near the guarded point is pursued
+                let intruder = villager_query
+                    .iter()
+                    .map(|(_, transform)| transform.translation.truncate())
+                    .filter(|villager_pos| {
+                        villager_pos.distance(point) < balance.chimera_guard_range
+                    })
+                    .min_by(|a, b| position.distance(*a).total_cmp(&position.distance(*b)));
+
+                match intruder {
+                    Some(villager_pos) => behaviors::pursue_behavior(
+                        &mut vel,
+                        charge_stats.into(),
+                        position,
+                        Some(villager_pos),
+                        &grid,
+                        &mut path,
+                    ),
+                    None => hold_position(&mut vel, stats, position, point, &grid, &mut path),
+                }
+            }
+            ChimeraBehavior::Attack { target } => match villager_query.get(*target) {
+                Ok((_, villager_transform)) => behaviors::pursue_behavior(
+                    &mut vel,
+                    charge_stats.into(),
+                    position,
+                    Some(villager_transform.translation.truncate()),
+                    &grid,
+                    &mut path,
+                ),
+                // the villager is dead, back to following the player
+                Err(_) => chimera.behavior = ChimeraBehavior::follow(balance),
+            },
         }
+
+        // chimeras spread out instead of piling up
+        let push = steering::separation(position, chimera_positions.iter().copied());
+        vel.linvel = steering::separate(vel.linvel, push, &stats.into());
+    }
+}
+
+// moves back to a point, then stops there
+fn hold_position(
+    vel: &mut Velocity,
+    stats: ChimeraStats,
+    position: Vec2,
+    point: Vec2,
+    grid: &NavGrid,
+    path: &mut NavPath,
+) {
+    if position.distance(point) > constants::ORDER_HOLD_DISTANCE {
+        behaviors::follow_behavior(
+            vel,
+            stats.into(),
+            position,
+            Some(point),
+            constants::ORDER_HOLD_DISTANCE,
+            grid,
+            path,
+        );
+    } else {
+        behaviors::stop_behavior(vel, stats.into());
     }
 }
@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+use bevy_kira_audio::AudioChannel;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    chimera_traits, sort_parts, spawn_chimera_entity, ChimeraBehavior, ChimeraComponent,
+    ChimeraPartAttributes, ChimeraSlot, ChimeraStats, OrderKind,
+};
+use crate::{
+    assets_manager::AssetsManager,
+    balance::GameBalance,
+    constants,
+    game_input::GameInput,
+    health::Health,
+    rng::{GameRng, RngStream},
+    schedule::tick_delta,
+    sound_manager::SpawnChimeraAudioChannel,
+};
+
+// Ancestry of a chimera, ids are unique within a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lineage {
+    pub id: u32,
+    // 0 for chimeras made from animal parts
+    pub generation: u32,
+    pub parents: Option<(u32, u32)>,
+}
+
+// Ids given to the chimeras of the current game
+#[derive(Debug, Default)]
+pub struct ChimeraIds {
+    pub next: u32,
+}
+
+impl ChimeraIds {
+    // lineage of a chimera made from animal parts
+    pub fn first_generation(&mut self) -> Lineage {
+        Lineage {
+            id: self.take(),
+            generation: 0,
+            parents: None,
+        }
+    }
+
+    fn take(&mut self) -> u32 {
+        let id = self.next;
+        self.next += 1;
+        id
+    }
+}
+
+// Chimeras ordered to breed and ready to, pair up with the ones close to them.
+// Each pair produces an offspring, as long as the ranch has room for it
+#[allow(clippy::too_many_arguments)]
+pub fn chimera_breeding_system(
+    game_input: Res<GameInput>,
+    mut commands: Commands,
+    mut chimera_query: Query<(Entity, &mut ChimeraComponent, &Transform)>,
+    balance: Res<GameBalance>,
+    mut ids: ResMut<ChimeraIds>,
+    mut rng: ResMut<GameRng>,
+    assets: Option<Res<AssetsManager>>,
+    spawn_audio: Option<Res<AudioChannel<SpawnChimeraAudioChannel>>>,
+) {
+    for (_, mut chimera, _) in chimera_query.iter_mut() {
+        chimera.breeding_timer.tick(tick_delta());
+    }
+
+    let order = match &game_input.order {
+        Some(order) if order.kind == OrderKind::Breed => order,
+        _ => return,
+    };
+
+    let ready: Vec<(Entity, Vec2)> = chimera_query
+        .iter()
+        .filter(|(_, chimera, _)| {
+            order.chimeras.is_empty() || order.chimeras.contains(&chimera.lineage.id)
+        })
+        .filter(|(_, chimera, _)| chimera.breeding_timer.finished())
+        .map(|(entity, _, transform)| (entity, transform.translation.truncate()))
+        .collect();
+    let mut population = chimera_query.iter().count();
+
+    // a chimera breeds at most once per order
+    let mut bred = Vec::new();
+    for (idx, (entity_a, position_a)) in ready.iter().enumerate() {
+        if population >= balance.breeding.max_chimeras {
+            info!("the ranch is full, no chimera is bred");
+            break;
+        }
+        if bred.contains(entity_a) {
+            continue;
+        }
+
+        let partner = ready[idx + 1..].iter().find(|(entity_b, position_b)| {
+            !bred.contains(entity_b) && position_a.distance(*position_b) < balance.breeding.range
+        });
+        let (entity_b, position_b) = match partner {
+            Some(partner) => partner,
+            None => continue,
+        };
+        bred.push(*entity_a);
+        bred.push(*entity_b);
+
+        let offspring = {
+            let (_, parent_a, _) = chimera_query.get(*entity_a).unwrap();
+            let (_, parent_b, _) = chimera_query.get(*entity_b).unwrap();
+            breed(parent_a, parent_b, &mut ids, &balance, &mut rng)
+        };
+
+        for parent in [entity_a, entity_b] {
+            let (_, mut parent, _) = chimera_query.get_mut(*parent).unwrap();
+            parent.breeding_timer.reset();
+        }
+
+        // there is no audio when running headless
+        if let (Some(spawn_audio), Some(assets)) = (&spawn_audio, &assets) {
+            spawn_audio.play(assets.sound_spawn_chimera.clone());
+        }
+
+        population += 1;
+        let health = Health::new(offspring.stats.health, 1.0, 1.0);
+        spawn_chimera_entity(
+            offspring,
+            health,
+            (*position_a + *position_b) / 2.0,
+            &mut rng,
+            &mut commands,
+        );
+    }
+}
+
+// the offspring gets the head of a parent, the tail of the other and some of
+// their middle parts, each of its stats is picked between the ones of its
+// parents then mutated
+fn breed(
+    parent_a: &ChimeraComponent,
+    parent_b: &ChimeraComponent,
+    ids: &mut ChimeraIds,
+    balance: &GameBalance,
+    rng: &mut GameRng,
+) -> ChimeraComponent {
+    let rng = rng.stream(RngStream::Stats);
+    let mutation = balance.breeding.mutation;
+    let mut inherit = |a: f32, b: f32| {
+        let mixed = a + (b - a) * rng.gen::<f32>();
+        mixed * (1.0 + rng.gen_range(-mutation..=mutation))
+    };
+
+    let (a, b) = (&parent_a.stats, &parent_b.stats);
+    let stats = ChimeraStats {
+        speed: inherit(a.speed, b.speed),
+        accel: inherit(a.accel, b.accel),
+        decel: inherit(a.decel, b.decel),
+        health: inherit(a.health, b.health),
+        attack: inherit(a.attack, b.attack),
+        regen: inherit(a.regen, b.regen),
+        range: inherit(a.range, b.range),
+    };
+
+    let (front, back) = if rng.gen_bool(0.5) {
+        (parent_a, parent_b)
+    } else {
+        (parent_b, parent_a)
+    };
+    let mut parts = vec![
+        front.parts.first().unwrap().clone(),
+        back.parts.last().unwrap().clone(),
+    ];
+
+    // each middle slot found on a parent is given by one of them
+    for slot in [ChimeraSlot::Body, ChimeraSlot::Legs, ChimeraSlot::Wings] {
+        if parts.len() == constants::CHIMERA_MAX_PARTS {
+            break;
+        }
+
+        let candidates: Vec<&ChimeraPartAttributes> = middle_parts(parent_a)
+            .iter()
+            .chain(middle_parts(parent_b))
+            .filter(|part| part.kind.slot() == slot)
+            .collect();
+        if !candidates.is_empty() {
+            parts.push(candidates[rng.gen_range(0..candidates.len())].clone());
+        }
+    }
+    sort_parts(&mut parts);
+
+    let lineage = Lineage {
+        id: ids.take(),
+        generation: parent_a.lineage.generation.max(parent_b.lineage.generation) + 1,
+        parents: Some((parent_a.lineage.id, parent_b.lineage.id)),
+    };
+    info!(
+        "chimeras #{} and #{} bred #{} (generation {})",
+        parent_a.lineage.id, parent_b.lineage.id, lineage.id, lineage.generation
+    );
+
+    ChimeraComponent {
+        behavior: ChimeraBehavior::follow(&balance.behaviors),
+        stats,
+        traits: chimera_traits(&parts, &balance.synergies),
+        attack_timer: Timer::from_seconds(balance.behaviors.chimera_attack_rate, true),
+        taunt_timer: Timer::from_seconds(balance.behaviors.chimera_taunt_cooldown, true),
+        parts,
+        kills: 0,
+        lineage,
+        breeding_timer: Timer::from_seconds(balance.breeding.cooldown, false),
+    }
+}
+
+// parts of a chimera between its front and its back, none with less than 3 parts
+fn middle_parts(chimera: &ChimeraComponent) -> &[ChimeraPartAttributes] {
+    let count = chimera.parts.len();
+    chimera
+        .parts
+        .get(1..count.saturating_sub(1))
+        .unwrap_or_default()
+}
@@ -1,39 +1,126 @@
 use bevy::prelude::*;
-use bevy_kira_audio::AudioChannel;
 use bevy_rapier2d::prelude::*;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+pub use self::breeding::{ChimeraIds, Lineage};
+pub use self::orders::{current_order, ChimeraOrder, OrderKind};
+pub use self::traits::{
+    chimera_traits, trait_total, ChimeraTrait, PartTraits, TraitKind, TraitSet,
+};
+
+pub use self::behavior::ChimeraBehavior;
 
 use self::behavior::chimera_behavior_system;
 use crate::{
     animals::AnimalKind,
     animations::BobbingAnim,
-    assets_manager::AssetsManager,
-    behaviors::{self, UnitBehavior},
-    constants,
+    balance::GameBalance,
+    behaviors::{self, Facing},
     health::Health,
-    inventory_parts::interaction::InventoryManagement,
-    player::Player,
-    sound_manager::SpawnChimeraAudioChannel,
-    states::GameStates,
+    navigation::NavPath,
+    rng::{GameRng, RngStream},
+    schedule::{self, FixedUpdateStage},
+    states::{GameEntity, GameStates},
 };
 
 mod behavior;
+mod breeding;
+mod orders;
+mod traits;
 
-#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+#[derive(Hash, PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub enum ChimeraPartKind {
     Head(AnimalKind),
     Tail(AnimalKind),
+    Body(AnimalKind),
+    Legs(AnimalKind),
+    Wings(AnimalKind),
+}
+
+impl ChimeraPartKind {
+    pub fn new(slot: ChimeraSlot, animal: AnimalKind) -> Self {
+        match slot {
+            ChimeraSlot::Head => ChimeraPartKind::Head(animal),
+            ChimeraSlot::Body => ChimeraPartKind::Body(animal),
+            ChimeraSlot::Legs => ChimeraPartKind::Legs(animal),
+            ChimeraSlot::Wings => ChimeraPartKind::Wings(animal),
+            ChimeraSlot::Tail => ChimeraPartKind::Tail(animal),
+        }
+    }
+
+    pub fn slot(&self) -> ChimeraSlot {
+        match self {
+            ChimeraPartKind::Head(_) => ChimeraSlot::Head,
+            ChimeraPartKind::Body(_) => ChimeraSlot::Body,
+            ChimeraPartKind::Legs(_) => ChimeraSlot::Legs,
+            ChimeraPartKind::Wings(_) => ChimeraSlot::Wings,
+            ChimeraPartKind::Tail(_) => ChimeraSlot::Tail,
+        }
+    }
+
+    pub fn animal(&self) -> &AnimalKind {
+        match self {
+            ChimeraPartKind::Head(animal)
+            | ChimeraPartKind::Body(animal)
+            | ChimeraPartKind::Legs(animal)
+            | ChimeraPartKind::Wings(animal)
+            | ChimeraPartKind::Tail(animal) => animal,
+        }
+    }
+}
+
+// Where a part goes on a chimera, parts are kept in this order from front to back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChimeraSlot {
+    Head,
+    Body,
+    Legs,
+    Wings,
+    Tail,
+}
+
+impl ChimeraSlot {
+    pub const ALL: [ChimeraSlot; 5] = [
+        ChimeraSlot::Head,
+        ChimeraSlot::Body,
+        ChimeraSlot::Legs,
+        ChimeraSlot::Wings,
+        ChimeraSlot::Tail,
+    ];
+
+    // z offset of the sprite of the part, the middle parts are drawn over the
+    // head and tail
+    pub fn layer(self) -> f32 {
+        match self {
+            ChimeraSlot::Tail => 0.0,
+            ChimeraSlot::Head => 0.1,
+            ChimeraSlot::Body => 0.2,
+            ChimeraSlot::Legs => 0.3,
+            ChimeraSlot::Wings => 0.4,
+        }
+    }
 }
 
 #[derive(Component)]
 pub struct ChimeraComponent {
-    pub behavior: UnitBehavior,
-    pub damage_timer: Timer,
+    pub behavior: ChimeraBehavior,
     pub stats: ChimeraStats,
     pub attack_timer: Timer,
+    // time before this chimera draws the attention of the villagers again
+    pub taunt_timer: Timer,
+    // 2 to 4 parts the chimera was made from, ordered by slot
+    pub parts: Vec<ChimeraPartAttributes>,
+    // villagers killed by this chimera
+    pub kills: u32,
+    pub lineage: Lineage,
+    // time before this chimera can breed again
+    pub breeding_timer: Timer,
+    // given by its parts and by its head and tail pair
+    pub traits: Vec<ChimeraTrait>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
 pub struct ChimeraStats {
     pub speed: f32,
     pub accel: f32,
@@ -57,6 +144,7 @@ pub struct ChimeraPartAttributes {
     pub collider_size: Vec2,
     pub texture: Handle<Image>,
     pub kind: ChimeraPartKind,
+    pub traits: Vec<ChimeraTrait>,
 }
 
 #[derive(Component)]
@@ -66,156 +154,152 @@ pub struct ChimerasPlugin;
 
 impl Plugin for ChimerasPlugin {
     fn build(&self, app: &mut App) {
-        // on update
-        app.add_system_set(
-            SystemSet::on_update(GameStates::Game)
-                //.with_system(test_spawn_chimera_system)
-                .with_system(chimera_behavior_system)
-                .with_system(behaviors::chimera_attack_system),
+        app.init_resource::<ChimeraIds>();
+
+        // on tick
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            schedule::on_fixed_update()
+                .with_system(
+                    orders::chimera_orders_system
+                        .label("chimera_orders")
+                        .after("game_input"),
+                )
+                .with_system(
+                    chimera_behavior_system
+                        .after("chimera_orders")
+                        .after("nav_grid"),
+                )
+                .with_system(behaviors::chimera_attack_system.before("apply_damage"))
+                .with_system(
+                    behaviors::chimera_taunt_system
+                        .label("chimera_taunt")
+                        .before("villager_target"),
+                )
+                .with_system(breeding::chimera_breeding_system.after("game_input")),
         );
+
+        // on exit
+        app.add_system_set(SystemSet::on_exit(GameStates::Game).with_system(reset_chimera_ids));
     }
 }
 
-// spawns a random chimera from 2 parts in the player's inventory
-pub fn _test_spawn_chimera_system(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut commands: Commands,
-    assets: Res<AssetsManager>,
-    mut player_query: Query<(&mut Player, &Transform)>,
-    spawn_audio: Res<AudioChannel<SpawnChimeraAudioChannel>>,
-    mut inv_man: ResMut<InventoryManagement>,
-) {
-    let capture_input = keyboard_input.just_pressed(KeyCode::P);
-
-    if capture_input {
-        // if there are 2 items selected
-        if let Some((_, part1)) = inv_man.target_1.selection.clone() {
-            if let Some((_, part2)) = inv_man.target_2.selection.clone() {
-                if let Some((mut player, player_transform)) = player_query.iter_mut().next() {
-                    let part_1_idx = player
-                        .inventory
-                        .chimera_parts
-                        .iter()
-                        .position(|part| part == &part1)
-                        .unwrap();
-
-                    player.inventory.chimera_parts.remove(part_1_idx);
-
-                    let part_2_idx = player
-                        .inventory
-                        .chimera_parts
-                        .iter()
-                        .position(|part| part == &part2)
-                        .unwrap();
-
-                    player.inventory.chimera_parts.remove(part_2_idx);
-
-                    // reset inv_man
-                    inv_man.reset();
-
-                    // play audio
-                    spawn_audio.set_playback_rate(rand::thread_rng().gen_range(0.7..1.8));
-                    spawn_audio.play(assets.sound_spawn_chimera.clone());
-
-                    spawn_chimera(
-                        (part1, part2),
-                        Vec2::new(
-                            player_transform.translation.x,
-                            player_transform.translation.y + 150.0,
-                        ),
-                        &mut commands,
-                    )
-                }
-            }
-        }
-    }
+fn reset_chimera_ids(mut ids: ResMut<ChimeraIds>) {
+    *ids = ChimeraIds::default();
+}
+
+// puts the parts of a chimera in slot order, a tail given before a head ends up
+// at the back instead of creating a backwards chimera
+pub fn sort_parts(parts: &mut [ChimeraPartAttributes]) {
+    parts.sort_by_key(|part| part.kind.slot());
 }
 
-// spawns a chimera from two chimera parts
+// spawns a chimera from 2 to 4 chimera parts, its stats are the sum of the ones
+// of its parts
 pub fn spawn_chimera(
-    chimera_parts: (ChimeraPartAttributes, ChimeraPartAttributes),
+    mut chimera_parts: Vec<ChimeraPartAttributes>,
     position: Vec2,
+    balance: &GameBalance,
+    ids: &mut ChimeraIds,
+    rng: &mut GameRng,
     commands: &mut Commands,
 ) {
-    let mut head_attributes = chimera_parts.0.clone();
-    let mut tail_attributes = chimera_parts.1;
-
-    // swap head and tail if they would otherwise create a backwards chimera
-    if matches!(head_attributes.kind, ChimeraPartKind::Tail(_))
-        && matches!(tail_attributes.kind, ChimeraPartKind::Head(_))
-    {
-        let temp = head_attributes.clone();
-        head_attributes = tail_attributes;
-        tail_attributes = temp;
+    sort_parts(&mut chimera_parts);
+
+    let mut stats = ChimeraStats::default();
+    for part in chimera_parts.iter() {
+        stats.speed += part.speed;
+        stats.accel += part.accel;
+        stats.decel += part.decel;
+        stats.health += part.health;
+        stats.attack += part.attack;
+        stats.regen += part.regen;
+        stats.range += part.range;
     }
 
-    let chimera_health = head_attributes.health + tail_attributes.health;
-    let chimera_regen = head_attributes.regen + tail_attributes.regen;
+    let chimera = ChimeraComponent {
+        attack_timer: Timer::from_seconds(balance.behaviors.chimera_attack_rate, true),
+        taunt_timer: Timer::from_seconds(balance.behaviors.chimera_taunt_cooldown, true),
+        behavior: ChimeraBehavior::follow(&balance.behaviors),
+        stats,
+        traits: chimera_traits(&chimera_parts, &balance.synergies),
+        parts: chimera_parts,
+        kills: 0,
+        lineage: ids.first_generation(),
+        breeding_timer: Timer::from_seconds(balance.breeding.cooldown, false),
+    };
+
+    spawn_chimera_entity(
+        chimera,
+        Health::new(stats.health, 1.0, 1.0),
+        position,
+        rng,
+        commands,
+    );
+}
+
+// spawns the entity of an already built chimera, also used when loading a save
+pub fn spawn_chimera_entity(
+    chimera: ChimeraComponent,
+    health: Health,
+    position: Vec2,
+    rng: &mut GameRng,
+    commands: &mut Commands,
+) -> Entity {
+    let parts = chimera.parts.clone();
+    let bobbing_anim_val = rng.stream(RngStream::Visuals).gen::<f32>() * 32.0;
+
+    // as wide as its parts on average, as tall as the tallest one
+    let collider_width =
+        parts.iter().map(|part| part.collider_size.x).sum::<f32>() / parts.len() as f32;
+    let collider_height = parts
+        .iter()
+        .map(|part| part.collider_size.y)
+        .fold(0.0, f32::max);
 
-    // spawn the chimera
     commands
         .spawn()
         .insert_bundle(TransformBundle::from(Transform::from_translation(
             position.extend(10.0),
         )))
+        .insert(GameEntity)
         .insert(Velocity {
             linvel: Vec2::new(0.0, 0.0),
             angvel: 0.0,
         })
-        .insert(ChimeraComponent {
-            damage_timer: Timer::from_seconds(constants::DAMAGE_RED_DURATION, true),
-            attack_timer: Timer::from_seconds(constants::CHIMERA_ATTACK_RATE, true),
-            behavior: UnitBehavior::Follow {
-                target: None,
-                distance: constants::CHIMERA_FOLLOW_DISTANCE,
-            },
-            stats: ChimeraStats {
-                attack: head_attributes.attack + tail_attributes.attack,
-                range: head_attributes.range + tail_attributes.range,
-                speed: head_attributes.speed + tail_attributes.speed,
-                accel: head_attributes.accel + tail_attributes.accel,
-                decel: head_attributes.decel + tail_attributes.decel,
-                health: chimera_health,
-                regen: chimera_regen,
-            },
-        })
-        .insert(Health::new(chimera_health, 1.0, 1.0))
+        .insert(chimera)
+        .insert(NavPath::default())
+        .insert(Facing::default())
+        .insert(health)
         .insert(RigidBody::Dynamic)
-        .insert(Collider::cuboid(
-            head_attributes.collider_size.x / 2.0 + tail_attributes.collider_size.x / 2.0,
-            head_attributes.collider_size.y,
-        ))
+        .insert(Collider::cuboid(collider_width, collider_height))
         .insert(LockedAxes::ROTATION_LOCKED)
         .insert(ActiveEvents::COLLISION_EVENTS)
         .with_children(|parent| {
-            let bobbing_anim_val = rand::thread_rng().gen::<f32>() * 32.0;
+            for (idx, part) in parts.into_iter().enumerate() {
+                // the first part faces forward, heads behind it are turned
+                // backwards as are tails in front
+                let flip_x = match part.kind.slot() {
+                    ChimeraSlot::Head => idx > 0,
+                    ChimeraSlot::Tail => idx == 0,
+                    _ => false,
+                };
 
-            parent
-                .spawn_bundle(SpriteBundle {
-                    texture: head_attributes.texture,
-                    sprite: Sprite {
-                        flip_x: matches!(head_attributes.kind, ChimeraPartKind::Tail(_)),
-                        ..default()
-                    },
-                    ..default()
-                })
-                .insert(ChimeraSprite)
-                .insert(BobbingAnim {
-                    anim: bobbing_anim_val,
-                });
-
-            parent
-                .spawn_bundle(SpriteBundle {
-                    texture: tail_attributes.texture,
-                    sprite: Sprite {
-                        flip_x: matches!(tail_attributes.kind, ChimeraPartKind::Head(_)),
+                parent
+                    .spawn_bundle(SpriteBundle {
+                        texture: part.texture,
+                        sprite: Sprite {
+                            flip_x,
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0.0, 0.0, part.kind.slot().layer()),
                         ..default()
-                    },
-                    ..default()
-                })
-                .insert(ChimeraSprite)
-                .insert(BobbingAnim {
-                    anim: bobbing_anim_val,
-                });
-        });
+                    })
+                    .insert(ChimeraSprite)
+                    .insert(BobbingAnim {
+                        anim: bobbing_anim_val,
+                    });
+            }
+        })
+        .id()
 }
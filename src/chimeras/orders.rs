@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{ChimeraBehavior, ChimeraComponent};
+use crate::{balance::GameBalance, constants, game_input::GameInput, villagers::VillagerComponent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderKind {
+    // hold the current position
+    Stay,
+    // follow the player and fight the villagers around, as new chimeras do
+    Follow,
+    // fight the villagers coming close to the cursor position
+    Guard,
+    // chase the villager under the cursor
+    Attack,
+    // pair up with a chimera close by, once, to breed an offspring
+    Breed,
+}
+
+impl OrderKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            OrderKind::Stay => "stay",
+            OrderKind::Follow => "follow",
+            OrderKind::Guard => "guard",
+            OrderKind::Attack => "attack",
+            OrderKind::Breed => "breed",
+        }
+    }
+}
+
+// Order given by the player, read from the inputs of a tick
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChimeraOrder {
+    pub kind: OrderKind,
+    // lineage ids of the ordered chimeras, every chimera is ordered when empty
+    pub chimeras: Vec<u32>,
+}
+
+// order a chimera is carrying out, chimeras without one follow the player
+pub fn current_order(behavior: &ChimeraBehavior) -> OrderKind {
+    match behavior {
+        ChimeraBehavior::Retreat {
+            resume: Some(order),
+            ..
+        } => current_order(order),
+        ChimeraBehavior::Stay { .. } => OrderKind::Stay,
+        ChimeraBehavior::Guard { .. } => OrderKind::Guard,
+        ChimeraBehavior::Attack { .. } => OrderKind::Attack,
+        ChimeraBehavior::Idle(_)
+        | ChimeraBehavior::Pursue { .. }
+        | ChimeraBehavior::Follow { .. }
+        | ChimeraBehavior::Retreat { .. } => OrderKind::Follow,
+    }
+}
+
+pub fn chimera_orders_system(
+    game_input: Res<GameInput>,
+    mut chimera_query: Query<(&mut ChimeraComponent, &Transform)>,
+    villager_query: Query<(Entity, &Transform), With<VillagerComponent>>,
+    balance: Res<GameBalance>,
+) {
+    // breeding does not change the behavior, see `chimera_breeding_system`
+    let order = match &game_input.order {
+        Some(order) if order.kind != OrderKind::Breed => order,
+        _ => return,
+    };
+
+    // the guarded point and the attacked villager are under the cursor
+    let cursor = game_input.cursor_position;
+    let villager = cursor.and_then(|cursor| {
+        villager_query
+            .iter()
+            .map(|(entity, transform)| (entity, cursor.distance(transform.translation.truncate())))
+            .filter(|(_, distance)| *distance < constants::ORDER_TARGET_RANGE)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity)
+    });
+
+    let order_behavior = |position: Vec2| match order.kind {
+        OrderKind::Stay => Some(ChimeraBehavior::Stay { position }),
+        OrderKind::Follow => Some(ChimeraBehavior::follow(&balance.behaviors)),
+        OrderKind::Guard => cursor.map(|point| ChimeraBehavior::Guard { point }),
+        OrderKind::Attack => villager.map(|target| ChimeraBehavior::Attack { target }),
+        OrderKind::Breed => None,
+    };
+
+    let mut ordered = 0;
+    for (mut chimera, transform) in chimera_query.iter_mut() {
+        if !order.chimeras.is_empty() && !order.chimeras.contains(&chimera.lineage.id) {
+            continue;
+        }
+
+        if let Some(behavior) = order_behavior(transform.translation.truncate()) {
+            chimera.behavior = behavior;
+            ordered += 1;
+        }
+    }
+
+    info!(
+        "{} order given to {} chimera(s)",
+        order.kind.name(),
+        ordered
+    );
+}
@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{ChimeraPartAttributes, ChimeraSlot};
+use crate::{assets_manager::AssetsManager, balance::SynergiesBalance};
+
+// dodge chance and armor stop growing past this share
+pub const MAX_PROTECTION: f32 = 0.75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TraitKind {
+    // extra share of the damage dealt to villagers
+    Bite,
+    // chance to avoid a hit
+    Flutter,
+    // extra share of speed while pursuing a villager
+    Charge,
+    // share of the damage of a hit that is ignored
+    Sturdy,
+}
+
+impl TraitKind {
+    pub const ALL: [TraitKind; 4] = [
+        TraitKind::Bite,
+        TraitKind::Flutter,
+        TraitKind::Charge,
+        TraitKind::Sturdy,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TraitKind::Bite => "bite",
+            TraitKind::Flutter => "flutter",
+            TraitKind::Charge => "charge",
+            TraitKind::Sturdy => "sturdy",
+        }
+    }
+
+    pub fn icon(self, assets: &AssetsManager) -> Handle<Image> {
+        match self {
+            TraitKind::Bite => assets.texture_trait_bite.clone(),
+            TraitKind::Flutter => assets.texture_trait_flutter.clone(),
+            TraitKind::Charge => assets.texture_trait_charge.clone(),
+            TraitKind::Sturdy => assets.texture_trait_sturdy.clone(),
+        }
+    }
+}
+
+// Special ability given by a chimera part or by a head and tail pair
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChimeraTrait {
+    pub kind: TraitKind,
+    pub value: f32,
+}
+
+// Traits written in the data files as a map of names to values, e.g. `{"bite": 0.5}`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(try_from = "BTreeMap<String, f32>")]
+pub struct TraitSet(pub Vec<ChimeraTrait>);
+
+impl TryFrom<BTreeMap<String, f32>> for TraitSet {
+    type Error = anyhow::Error;
+
+    fn try_from(values: BTreeMap<String, f32>) -> anyhow::Result<Self> {
+        let mut traits = Vec::new();
+        for (name, value) in values {
+            let kind = match TraitKind::ALL.into_iter().find(|kind| kind.name() == name) {
+                Some(kind) => kind,
+                None => bail!("unknown trait `{}`", name),
+            };
+
+            let max = match kind {
+                TraitKind::Flutter | TraitKind::Sturdy => 1.0,
+                TraitKind::Bite | TraitKind::Charge => f32::INFINITY,
+            };
+            if !value.is_finite() || !(0.0..max).contains(&value) {
+                bail!("trait `{}` must be in [0, {}), got {}", name, max, value);
+            }
+
+            traits.push(ChimeraTrait { kind, value });
+        }
+
+        Ok(TraitSet(traits))
+    }
+}
+
+// Traits given by each part of a species
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PartTraits {
+    #[serde(default)]
+    pub head: TraitSet,
+    #[serde(default)]
+    pub body: TraitSet,
+    #[serde(default)]
+    pub legs: TraitSet,
+    #[serde(default)]
+    pub wings: TraitSet,
+    #[serde(default)]
+    pub tail: TraitSet,
+}
+
+impl PartTraits {
+    pub fn get(&self, slot: ChimeraSlot) -> &[ChimeraTrait] {
+        match slot {
+            ChimeraSlot::Head => &self.head.0,
+            ChimeraSlot::Body => &self.body.0,
+            ChimeraSlot::Legs => &self.legs.0,
+            ChimeraSlot::Wings => &self.wings.0,
+            ChimeraSlot::Tail => &self.tail.0,
+        }
+    }
+}
+
+// traits of the parts of a chimera, plus the set bonus of its head and tail
+pub fn chimera_traits(
+    parts: &[ChimeraPartAttributes],
+    synergies: &SynergiesBalance,
+) -> Vec<ChimeraTrait> {
+    let mut traits: Vec<ChimeraTrait> = parts
+        .iter()
+        .flat_map(|part| part.traits.iter().copied())
+        .collect();
+
+    let find = |slot| parts.iter().find(|part| part.kind.slot() == slot);
+    if let (Some(head), Some(tail)) = (find(ChimeraSlot::Head), find(ChimeraSlot::Tail)) {
+        let (head, tail) = (head.kind.animal(), tail.kind.animal());
+
+        if head == tail {
+            traits.extend(synergies.same_species.0.iter().copied());
+        }
+        for pair in synergies.pairs.iter() {
+            if *head == pair.head && *tail == pair.tail {
+                traits.extend(pair.traits.0.iter().copied());
+            }
+        }
+    }
+
+    traits
+}
+
+// sum of the values of the traits of a kind
+pub fn trait_total(traits: &[ChimeraTrait], kind: TraitKind) -> f32 {
+    let total = traits
+        .iter()
+        .filter(|chimera_trait| chimera_trait.kind == kind)
+        .map(|chimera_trait| chimera_trait.value)
+        .sum();
+
+    match kind {
+        TraitKind::Flutter | TraitKind::Sturdy => f32::min(total, MAX_PROTECTION),
+        TraitKind::Bite | TraitKind::Charge => total,
+    }
+}
@@ -0,0 +1,85 @@
+use std::process;
+
+const USAGE: &str = "\
+usage: chimera-rancher [--seed <seed> | --replay <file>] [--headless [--days <count>]]
+
+options:
+    --headless        run the simulation without window nor audio, a bot plays the game
+    --days <count>    number of days simulated by a headless run (default: 10)
+    --seed <seed>     seed of every gameplay random roll, random when omitted
+    --replay <file>   play the inputs recorded in a replay file, such as `last.replay.ron`
+    --help            print this message";
+
+// Options given on the command line
+pub struct CliArgs {
+    pub headless: bool,
+    pub days: u8,
+    pub seed: Option<u64>,
+    pub replay: Option<String>,
+}
+
+impl Default for CliArgs {
+    fn default() -> Self {
+        Self {
+            headless: false,
+            days: 10,
+            seed: None,
+            replay: None,
+        }
+    }
+}
+
+impl CliArgs {
+    // parses the process arguments, prints the usage and exits on error
+    pub fn from_env() -> Self {
+        match Self::parse(std::env::args().skip(1)) {
+            Ok(Some(args)) => args,
+            Ok(None) => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            Err(err) => {
+                eprintln!("error: {}\n\n{}", err, USAGE);
+                process::exit(2);
+            }
+        }
+    }
+
+    // returns None when the usage was requested
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut cli_args = CliArgs::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => cli_args.headless = true,
+                "--days" => {
+                    let value = args.next().ok_or("`--days` expects a number of days")?;
+                    cli_args.days = value
+                        .parse()
+                        .map_err(|_| format!("invalid number of days `{}`", value))?;
+                }
+                "--seed" => {
+                    let value = args.next().ok_or("`--seed` expects a number")?;
+                    cli_args.seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("invalid seed `{}`", value))?,
+                    );
+                }
+                "--replay" => {
+                    let value = args.next().ok_or("`--replay` expects a file")?;
+                    cli_args.replay = Some(value);
+                }
+                "--help" | "-h" => return Ok(None),
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+
+        // the replay brings its own seed and needs the player systems
+        if cli_args.replay.is_some() && (cli_args.headless || cli_args.seed.is_some()) {
+            return Err("`--replay` cannot be combined with `--headless` nor `--seed`".to_string());
+        }
+
+        Ok(Some(cli_args))
+    }
+}
@@ -1,81 +1,28 @@
-use std::collections::HashMap;
-
 use bevy::prelude::*;
 
-use crate::animals::{AnimalAttributes, AnimalKind};
+use crate::{animals::AnimalAttributesResource, balance::GameBalance};
 
 // Zindexes
 pub const Z_UI: f32 = 100.;
 pub const Z_DAY_CYCLE: f32 = 50.;
 
-// day-night cycle
-pub const DAY_LENGTH: f32 = 60.0; // in seconds
-pub const STARTING_HOUR: f32 = 20.0;
-pub const MAX_ALPHA: f32 = 0.85;
-pub const MIN_ALPHA: f32 = 0.1;
-pub const NIGHT_HOURS: f32 = 8.0;
-
-// stats splitting
-pub const HEAD_SPEED_PERCENT: f32 = 0.35;
-pub const TAIL_SPEED_PERCENT: f32 = 0.65;
-pub const HEAD_ACCEL_PERCENT: f32 = 0.35;
-pub const TAIL_ACCEL_PERCENT: f32 = 0.65;
-pub const HEAD_DECEL_PERCENT: f32 = 0.35;
-pub const TAIL_DECEL_PERCENT: f32 = 0.65;
-pub const HEAD_HEALTH_PERCENT: f32 = 0.50;
-pub const TAIL_HEALTH_PERCENT: f32 = 0.50;
-pub const HEAD_ATTACK_PERCENT: f32 = 0.7;
-pub const TAIL_ATTACK_PERCENT: f32 = 0.3;
-pub const HEAD_REGEN_PERECENT: f32 = 0.5;
-pub const TAIL_REGEN_PERECENT: f32 = 0.5;
-pub const HEAD_RANGE_PERCENT: f32 = 0.6;
-pub const TAIL_RANGE_PERCENT: f32 = 0.4;
-
-// behaviors
-pub const CHIMERA_FOLLOW_RANGE: f32 = 450.0;
-pub const CHIMERA_FOLLOW_DISTANCE: f32 = 100.0;
-pub const CHIMERA_PURSUE_RANGE: f32 = 250.0;
-pub const ANIMAL_RUNAWAY_RANGE: f32 = 250.0;
-pub const ANIMAL_IDLE_RANGE: f32 = 500.0;
-pub const CHIMERA_IDLE_DURATION: f32 = 2.0;
-pub const CHIMERA_IDLE_DURATION_SPREAD: f32 = 1.0;
-pub const ANIMAL_IDLE_DURATION: f32 = 2.0;
-pub const ANIMAL_IDLE_DURATION_SPREAD: f32 = 1.0;
-pub const ANIMAL_REGEN_RATE: f32 = 2.0;
-pub const VILLAGER_REGEN_RATE: f32 = 2.0;
-pub const VILLAGER_ATTACK_RATE: f32 = 1.0;
-pub const CHIMERA_ATTACK_RATE: f32 = 1.0;
-
-// spawning
-pub const ANIMAL_SPAWN_MAX_X: f32 = 900.0;
-pub const ANIMAL_SPAWN_MIN_X: f32 = -900.0;
-pub const ANIMAL_SPAWN_MAX_Y: f32 = 1400.0;
-pub const ANIMAL_SPAWN_MIN_Y: f32 = 200.0;
-pub const ANIMAL_BASE_SPAWN_DURATION: f32 = 6.0;
-pub const ANIMAL_SPAWN_FACTOR: f32 = 25.0;
-pub const ANIMAL_SPAWN_HOURS: (u8, u8) = (20, 6);
-pub const VILLAGER_BASE_SPAWN_DURATION: f32 = 2.0;
-pub const VILLAGER_SPAWN_FACTOR: f32 = 25.0;
-pub const VILLAGER_SPAWN_HOURS: (u8, u8) = (6, 20);
-pub const VILLAGER_SPAWN_LOCATIONS: [(f32, f32); 6] = [
-    (-600.0, 500.0),
-    (0.0, 500.0),
-    (600.0, 500.0),
-    (-600.0, 1000.0),
-    (0.0, 1000.0),
-    (600.0, 1000.0),
-];
-
 // bobbing anim
 pub const ANIMATION_SPEED_FACTOR: f32 = 0.2;
 pub const ANIMATION_OFFSET_FACTOR: f32 = 4.0;
 
-// stats
-pub const ANIMAL_STATS_DEVIATION: f32 = 0.5;
-pub const VILLAGER_STATS_DEVIATION: f32 = 0.2;
+// chimeras are made from 2 to 4 parts
+pub const CHIMERA_MIN_PARTS: usize = 2;
+pub const CHIMERA_MAX_PARTS: usize = 4;
+
+// chimera orders
+// an attack order picks a villager this close to the cursor
+pub const ORDER_TARGET_RANGE: f32 = 60.0;
+// chimeras holding a position or guarding a point stop this close to it
+pub const ORDER_HOLD_DISTANCE: f32 = 20.0;
 
 // misc
 pub const DAMAGE_RED_DURATION: f32 = 0.5;
+pub const CORPSE_DURATION: f32 = 1.0;
 
 // compute maximal values
 #[derive(Default)]
@@ -89,21 +36,44 @@ pub struct MaxStats {
     pub range: f32,
 }
 
+impl MaxStats {
+    // highest stats an animal can roll, chimeras are built from animal parts
+    pub fn from_animals(animal_attr: &AnimalAttributesResource, deviation: f32) -> Self {
+        let factor = 1.0 + deviation;
+        let mut maxi = MaxStats::default();
+
+        for (_, attr) in animal_attr.iter() {
+            maxi.accel = f32::max(maxi.accel, attr.accel * factor);
+            maxi.decel = f32::max(maxi.decel, attr.decel * factor);
+            maxi.attack = f32::max(maxi.attack, attr.attack * factor);
+            maxi.speed = f32::max(maxi.speed, attr.speed * factor);
+            maxi.health = f32::max(maxi.health, attr.health * factor);
+            maxi.regen = f32::max(maxi.regen, attr.regen * factor);
+            maxi.range = f32::max(maxi.range, attr.range * factor);
+        }
+
+        maxi
+    }
+}
+
 pub fn compute_max_stats(
-    animal_attr: Res<HashMap<AnimalKind, AnimalAttributes>>,
+    animal_attr: Res<AnimalAttributesResource>,
+    balance: Res<GameBalance>,
     mut commands: Commands,
 ) {
-    let mut maxi = MaxStats::default();
+    commands.insert_resource(MaxStats::from_animals(
+        &animal_attr,
+        balance.stats.animal_deviation,
+    ));
+}
 
-    for (_, attr) in animal_attr.iter() {
-        maxi.accel = f32::max(maxi.accel, attr.accel * 1.5);
-        maxi.decel = f32::max(maxi.decel, attr.decel * 1.5);
-        maxi.attack = f32::max(maxi.attack, attr.attack * 1.5);
-        maxi.speed = f32::max(maxi.speed, attr.speed * 1.5);
-        maxi.health = f32::max(maxi.health, attr.health * 1.5);
-        maxi.regen = f32::max(maxi.regen, attr.regen * 1.5);
-        maxi.range = f32::max(maxi.range, attr.range * 1.5);
+// keeps the maximal stats in line with a reloaded balance
+pub fn update_max_stats(
+    animal_attr: Res<AnimalAttributesResource>,
+    balance: Res<GameBalance>,
+    max_stats: Option<ResMut<MaxStats>>,
+) {
+    if let Some(mut max_stats) = max_stats.filter(|_| balance.is_changed()) {
+        *max_stats = MaxStats::from_animals(&animal_attr, balance.stats.animal_deviation);
     }
-
-    commands.insert_resource(maxi);
 }
@@ -0,0 +1,181 @@
+use bevy::{ecs::event::Events, prelude::*};
+use bevy_kira_audio::AudioChannel;
+use rand::Rng;
+
+use crate::{
+    assets_manager::AssetsManager,
+    chimeras::{trait_total, ChimeraComponent, TraitKind},
+    constants,
+    health::Health,
+    pause::GameClock,
+    player::Player,
+    rng::{GameRng, RngStream},
+    schedule::{self, FixedUpdateStage},
+    sound_manager::ChimeraHitAudioChannel,
+    states::GameStates,
+    villagers::VillagerComponent,
+};
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        // damage events are kept until a tick reads them, there can be zero or
+        // several ticks per frame
+        app.init_resource::<Events<DamageEvent>>()
+            .add_event::<UnitDiedEvent>();
+
+        // on tick, attack systems run before "apply_damage"
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            schedule::on_fixed_update()
+                .with_system(apply_damage_system.label("apply_damage"))
+                .with_system(Events::<DamageEvent>::update_system.after("apply_damage")),
+        );
+
+        // on update
+        app.add_system_set(SystemSet::on_update(GameStates::Game).with_system(damage_flash_system));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageKind {
+    Melee,
+    Projectile,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitKind {
+    Player,
+    Chimera,
+    Villager,
+    Animal,
+}
+
+// Sent by anything hurting a unit or the player, applied in one place
+#[derive(Debug, Clone)]
+pub struct DamageEvent {
+    // attacking unit, or the caster of a projectile
+    pub source: Entity,
+    pub target: Entity,
+    pub amount: f32,
+    pub kind: DamageKind,
+}
+
+// Sent once when a damage brings the health of a unit or the player to zero
+#[derive(Debug, Clone)]
+pub struct UnitDiedEvent {
+    pub entity: Entity,
+    pub kind: UnitKind,
+    pub position: Vec2,
+    // source of the killing blow
+    pub killer: Entity,
+    pub damage_kind: DamageKind,
+}
+
+// Tints the sprites of a damaged entity in red until the timer finishes
+#[derive(Component)]
+pub struct DamageFlash(Timer);
+
+impl Default for DamageFlash {
+    fn default() -> Self {
+        Self(Timer::from_seconds(constants::DAMAGE_RED_DURATION, false))
+    }
+}
+
+// components telling the kind of a damaged entity, animals have none of them
+type UnitKindComponents = (
+    Option<&'static Player>,
+    Option<&'static ChimeraComponent>,
+    Option<&'static VillagerComponent>,
+);
+
+#[allow(clippy::too_many_arguments)]
+fn apply_damage_system(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+    mut died_events: EventWriter<UnitDiedEvent>,
+    mut health_query: Query<(&mut Health, &Transform, UnitKindComponents)>,
+    assets: Option<Res<AssetsManager>>,
+    hit_audio: Option<Res<AudioChannel<ChimeraHitAudioChannel>>>,
+    mut rng: ResMut<GameRng>,
+) {
+    for event in damage_events.iter() {
+        // the target can be gone since the event was sent
+        let (mut health, transform, (player, chimera, villager)) =
+            match health_query.get_mut(event.target) {
+                Ok(target) => target,
+                Err(_) => continue,
+            };
+        let kind = match (player, chimera, villager) {
+            (Some(_), _, _) => UnitKind::Player,
+            (_, Some(_), _) => UnitKind::Chimera,
+            (_, _, Some(_)) => UnitKind::Villager,
+            _ => UnitKind::Animal,
+        };
+
+        // chimeras can dodge a hit, then ignore a share of its damage
+        let mut amount = event.amount;
+        if let Some(chimera) = chimera {
+            let dodge = trait_total(&chimera.traits, TraitKind::Flutter);
+            if dodge > 0.0 && rng.stream(RngStream::Behaviors).gen_bool(dodge as f64) {
+                continue;
+            }
+            amount *= 1.0 - trait_total(&chimera.traits, TraitKind::Sturdy);
+        }
+
+        let was_alive = health.health > 0.0;
+        health.health -= amount;
+
+        commands.entity(event.target).insert(DamageFlash::default());
+
+        // only chimeras have a hit sound, there is no audio when running headless
+        if kind == UnitKind::Chimera {
+            if let (Some(hit_audio), Some(assets)) = (&hit_audio, &assets) {
+                hit_audio.set_playback_rate(rng.stream(RngStream::Audio).gen_range(0.3..1.8));
+                hit_audio.play(assets.sound_hit.clone());
+            }
+        }
+
+        if was_alive && health.health <= 0.0 {
+            died_events.send(UnitDiedEvent {
+                entity: event.target,
+                kind,
+                position: transform.translation.truncate(),
+                killer: event.source,
+                damage_kind: event.kind,
+            });
+        }
+    }
+}
+
+// the player has its sprite on its own entity, units on their children
+fn damage_flash_system(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut flash_query: Query<(
+        Entity,
+        &mut DamageFlash,
+        Option<&Children>,
+        Option<&mut TextureAtlasSprite>,
+    )>,
+    mut sprite_query: Query<&mut Sprite>,
+) {
+    for (entity, mut flash, children, atlas_sprite) in flash_query.iter_mut() {
+        flash.0.tick(clock.delta());
+        let red = if flash.0.finished() { 1.0 } else { 255.0 };
+
+        if let Some(mut sprite) = atlas_sprite {
+            sprite.color.set_r(red);
+        }
+        for child in children.iter().flat_map(|children| children.iter()) {
+            if let Ok(mut sprite) = sprite_query.get_mut(*child) {
+                sprite.color.set_r(red);
+            }
+        }
+
+        if flash.0.finished() {
+            commands.entity(entity).remove::<DamageFlash>();
+        }
+    }
+}
@@ -2,20 +2,20 @@ use std::{f32::consts::PI, time::Duration};
 
 use bevy::{log, prelude::*};
 
-use crate::{assets_manager::AssetsManager, constants, states::GameStates};
+use crate::{
+    assets_manager::AssetsManager,
+    balance::{DayCycleBalance, GameBalance},
+    constants,
+    schedule::{self, tick_delta, FixedUpdateStage},
+    states::{GameEntity, GameStates},
+};
 
 pub struct DayCyclePlugin;
 
 impl Plugin for DayCyclePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(DayCycleResource {
-            day_timer: Timer::from_seconds(constants::DAY_LENGTH, true),
-            day_length: constants::DAY_LENGTH,
-            max_alpha: constants::MAX_ALPHA,
-            min_alpha: constants::MIN_ALPHA,
-            night_hours: constants::NIGHT_HOURS,
-            days_passed: 0,
-        });
+        // configured from the game balance when the game starts
+        app.init_resource::<DayCycleResource>();
 
         // on enter
         app.add_system_set(
@@ -23,10 +23,21 @@ impl Plugin for DayCyclePlugin {
         );
 
         // on update
-        app.add_system_set(SystemSet::on_update(GameStates::Game).with_system(day_cycle_system));
+        app.add_system_set(
+            SystemSet::on_update(GameStates::Game)
+                .with_system(lighting_system)
+                .with_system(day_cycle_balance_system),
+        );
+
+        // on tick
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            schedule::on_fixed_update().with_system(day_cycle_system),
+        );
     }
 }
 
+#[derive(Default)]
 pub struct DayCycleResource {
     pub day_timer: Timer,
     pub day_length: f32,
@@ -37,6 +48,35 @@ pub struct DayCycleResource {
 }
 
 impl DayCycleResource {
+    pub fn new(balance: &DayCycleBalance) -> Self {
+        let mut day_timer = Timer::from_seconds(balance.day_length, true);
+        day_timer.set_elapsed(Duration::from_secs_f32(
+            (balance.starting_hour / 24.0) * balance.day_length,
+        ));
+
+        DayCycleResource {
+            day_timer,
+            day_length: balance.day_length,
+            max_alpha: balance.max_alpha,
+            min_alpha: balance.min_alpha,
+            night_hours: balance.night_hours,
+            days_passed: 0,
+        }
+    }
+
+    // applies new balance values while keeping the current time of day
+    pub fn apply_balance(&mut self, balance: &DayCycleBalance) {
+        let progress = self.day_timer.percent();
+
+        self.day_timer = Timer::from_seconds(balance.day_length, true);
+        self.day_timer
+            .set_elapsed(Duration::from_secs_f32(progress * balance.day_length));
+        self.day_length = balance.day_length;
+        self.max_alpha = balance.max_alpha;
+        self.min_alpha = balance.min_alpha;
+        self.night_hours = balance.night_hours;
+    }
+
     pub fn get_alpha(&self) -> f32 {
         let x = self.get_hour() as f32 + self.get_minute() as f32 / 60.;
         let fx = f32::max(f32::min(f32::cos(x * 2. * PI / 24. - 0.5) * 1.8, 1.), 0.);
@@ -60,16 +100,17 @@ impl DayCycleResource {
     }
 }
 
-fn day_cycle_system(
-    mut lighting_query: Query<&mut UiColor, With<LightingComponent>>,
-    mut day_cycle_resource: ResMut<DayCycleResource>,
-    time: Res<Time>,
-) {
-    day_cycle_resource.day_timer.tick(time.delta());
+fn day_cycle_system(mut day_cycle_resource: ResMut<DayCycleResource>) {
+    day_cycle_resource.day_timer.tick(tick_delta());
     if day_cycle_resource.day_timer.just_finished() {
         day_cycle_resource.days_passed += 1;
     }
+}
 
+fn lighting_system(
+    mut lighting_query: Query<&mut UiColor, With<LightingComponent>>,
+    day_cycle_resource: Res<DayCycleResource>,
+) {
     for mut uicolor in lighting_query.iter_mut() {
         log::trace!(
             "day {} hour: {} minute: {} => alpha: {}",
@@ -82,22 +123,35 @@ fn day_cycle_system(
     }
 }
 
+fn day_cycle_balance_system(
+    mut day_cycle_resource: ResMut<DayCycleResource>,
+    balance: Res<GameBalance>,
+) {
+    if balance.is_changed() {
+        day_cycle_resource.apply_balance(&balance.day_cycle);
+    }
+}
+
 #[derive(Component)]
 pub struct LightingComponent;
 
 fn setup_lighting_system(
     mut commands: Commands,
     mut day_cycle_resource: ResMut<DayCycleResource>,
-    assets: Res<AssetsManager>,
+    assets: Option<Res<AssetsManager>>,
+    balance: Res<GameBalance>,
 ) {
-    day_cycle_resource
-        .day_timer
-        .set_elapsed(Duration::from_secs_f32(
-            (constants::STARTING_HOUR / 24.0) * constants::DAY_LENGTH,
-        ));
+    *day_cycle_resource = DayCycleResource::new(&balance.day_cycle);
+
+    // nothing to display when running headless
+    let assets = match assets {
+        Some(assets) => assets,
+        None => return,
+    };
 
     commands
         .spawn()
+        .insert(GameEntity)
         .insert(LightingComponent)
         .insert_bundle(ImageBundle {
             transform: Transform::from_xyz(0., 0., constants::Z_DAY_CYCLE),
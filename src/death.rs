@@ -0,0 +1,138 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use bevy_kira_audio::AudioChannel;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    animals::AnimalComponent,
+    assets_manager::AssetsManager,
+    chimeras::ChimeraComponent,
+    constants,
+    damage::{UnitDiedEvent, UnitKind},
+    health::Health,
+    pause::GameClock,
+    schedule::{self, FixedUpdateStage},
+    sound_manager::ChimeraDeathAudioChannel,
+    states::GameStates,
+    villagers::VillagerComponent,
+};
+
+pub struct DeathPlugin;
+
+impl Plugin for DeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KillStats>();
+
+        // on tick
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            schedule::on_fixed_update().with_system(unit_died_system.after("apply_damage")),
+        );
+
+        // on update
+        app.add_system_set(SystemSet::on_update(GameStates::Game).with_system(corpse_system));
+
+        // on exit
+        app.add_system_set(SystemSet::on_exit(GameStates::Game).with_system(reset_kill_stats));
+    }
+}
+
+// Kills of the current game
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct KillStats {
+    pub villagers_killed: u32,
+    pub chimeras_lost: u32,
+}
+
+// Remains of a dead unit, falling on its side and fading out before being despawned
+#[derive(Component)]
+pub struct Corpse {
+    pub timer: Timer,
+}
+
+impl Default for Corpse {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(constants::CORPSE_DURATION, false),
+        }
+    }
+}
+
+fn reset_kill_stats(mut kills: ResMut<KillStats>) {
+    *kills = KillStats::default();
+}
+
+fn unit_died_system(
+    mut commands: Commands,
+    mut died_events: EventReader<UnitDiedEvent>,
+    mut chimera_query: Query<&mut ChimeraComponent>,
+    mut kills: ResMut<KillStats>,
+    mut game_state: ResMut<State<GameStates>>,
+    assets: Option<Res<AssetsManager>>,
+    death_audio: Option<Res<AudioChannel<ChimeraDeathAudioChannel>>>,
+) {
+    for died in died_events.iter() {
+        info!(
+            "{:?} {:?} killed by {:?} ({:?}) at {}",
+            died.kind, died.entity, died.killer, died.damage_kind, died.position
+        );
+
+        // the killer can be dying in the same tick, its components are removed later
+        if let Ok(mut killer) = chimera_query.get_mut(died.killer) {
+            killer.kills += 1;
+        }
+
+        match died.kind {
+            UnitKind::Player => {
+                // overrides a pause requested during the same frame
+                game_state.overwrite_push(GameStates::GameOver).unwrap();
+                continue;
+            }
+            UnitKind::Villager => kills.villagers_killed += 1,
+            UnitKind::Chimera => {
+                kills.chimeras_lost += 1;
+
+                // there is no audio when running headless
+                if let (Some(death_audio), Some(assets)) = (&death_audio, &assets) {
+                    death_audio.play(assets.sound_dying_chimera.clone());
+                }
+            }
+            UnitKind::Animal => {}
+        }
+
+        // the corpse is no longer a unit, nor a physics body. The physics world
+        // only drops a body when its handles are removed
+        commands
+            .entity(died.entity)
+            .remove_bundle::<(ChimeraComponent, VillagerComponent, AnimalComponent, Health)>()
+            .remove_bundle::<(RigidBody, Collider, Velocity, ActiveEvents)>()
+            .remove_bundle::<(RapierRigidBodyHandle, RapierColliderHandle)>()
+            .insert(Corpse::default());
+    }
+}
+
+fn corpse_system(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut corpse_query: Query<(Entity, &mut Corpse, &mut Transform, &Children)>,
+    mut sprite_query: Query<&mut Sprite>,
+) {
+    for (entity, mut corpse, mut transform, children) in corpse_query.iter_mut() {
+        corpse.timer.tick(clock.delta());
+        let progress = corpse.timer.percent();
+
+        // falls during the first quarter, fades out the whole time
+        transform.rotation = Quat::from_rotation_z(-FRAC_PI_2 * (progress * 4.0).min(1.0));
+        for child in children.iter() {
+            if let Ok(mut sprite) = sprite_query.get_mut(*child) {
+                sprite.color.set_a(1.0 - progress);
+            }
+        }
+
+        if corpse.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
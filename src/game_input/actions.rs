@@ -0,0 +1,414 @@
+use std::collections::BTreeMap;
+
+use bevy::{prelude::*, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{assets_manager::AssetsManager, settings::Settings, states::GameEntity};
+
+// sticks tilted less than this are at rest
+const STICK_DEADZONE: f32 = 0.2;
+// logical pixels per second the right stick moves the cursor at, fully tilted
+const VIRTUAL_CURSOR_SPEED: f32 = 900.0;
+
+// What the player can do, bound to keys, mouse buttons and gamepad buttons.
+// Systems read the actions instead of the devices
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Capture,
+    CastSpell,
+    NextSpell,
+    // selects the unit under the cursor, or the chimeras in a box when dragging
+    Inspect,
+    Pause,
+    // presses the highlighted button of the menus
+    Confirm,
+    OrderStay,
+    OrderFollow,
+    OrderGuard,
+    OrderAttack,
+    OrderBreed,
+}
+
+impl Action {
+    pub const ALL: [Action; 15] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Capture,
+        Action::CastSpell,
+        Action::NextSpell,
+        Action::Inspect,
+        Action::Pause,
+        Action::Confirm,
+        Action::OrderStay,
+        Action::OrderFollow,
+        Action::OrderGuard,
+        Action::OrderAttack,
+        Action::OrderBreed,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Capture => "Capture",
+            Action::CastSpell => "Cast spell",
+            Action::NextSpell => "Next spell",
+            Action::Inspect => "Inspect",
+            Action::Pause => "Pause",
+            Action::Confirm => "Confirm",
+            Action::OrderStay => "Stay",
+            Action::OrderFollow => "Follow",
+            Action::OrderGuard => "Guard",
+            Action::OrderAttack => "Attack",
+            Action::OrderBreed => "Breed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    // on any of the connected gamepads
+    Gamepad(GamepadButtonType),
+}
+
+impl Binding {
+    pub fn is_gamepad(self) -> bool {
+        matches!(self, Binding::Gamepad(_))
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Mouse(MouseButton::Other(button)) => format!("Mouse {}", button),
+            Binding::Mouse(button) => format!("{:?} mouse", button),
+            Binding::Gamepad(button) => format!("{:?}", button),
+        }
+    }
+}
+
+// Inputs bound to each action, saved with the settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bindings(BTreeMap<Action, Vec<Binding>>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use self::Binding::{Gamepad, Key, Mouse};
+
+        // the left stick also moves the player, the right stick the cursor
+        Self(BTreeMap::from([
+            (Action::MoveUp, vec![Key(KeyCode::W), Key(KeyCode::Up)]),
+            (Action::MoveDown, vec![Key(KeyCode::S), Key(KeyCode::Down)]),
+            (Action::MoveLeft, vec![Key(KeyCode::A), Key(KeyCode::Left)]),
+            (
+                Action::MoveRight,
+                vec![Key(KeyCode::D), Key(KeyCode::Right)],
+            ),
+            (
+                Action::Capture,
+                vec![Key(KeyCode::E), Gamepad(GamepadButtonType::South)],
+            ),
+            (
+                Action::CastSpell,
+                vec![
+                    Mouse(MouseButton::Left),
+                    Gamepad(GamepadButtonType::RightTrigger2),
+                ],
+            ),
+            (
+                Action::NextSpell,
+                vec![
+                    Mouse(MouseButton::Middle),
+                    Gamepad(GamepadButtonType::RightTrigger),
+                ],
+            ),
+            (
+                Action::Inspect,
+                vec![Mouse(MouseButton::Right), Gamepad(GamepadButtonType::West)],
+            ),
+            (
+                Action::Pause,
+                vec![Key(KeyCode::Escape), Gamepad(GamepadButtonType::Start)],
+            ),
+            // the south button also captures, the menus are not open in game
+            (
+                Action::Confirm,
+                vec![
+                    Key(KeyCode::Return),
+                    Key(KeyCode::Space),
+                    Gamepad(GamepadButtonType::South),
+                ],
+            ),
+            (
+                Action::OrderStay,
+                vec![Key(KeyCode::Z), Gamepad(GamepadButtonType::DPadDown)],
+            ),
+            (
+                Action::OrderFollow,
+                vec![Key(KeyCode::X), Gamepad(GamepadButtonType::DPadUp)],
+            ),
+            (
+                Action::OrderGuard,
+                vec![Key(KeyCode::C), Gamepad(GamepadButtonType::DPadLeft)],
+            ),
+            (
+                Action::OrderAttack,
+                vec![Key(KeyCode::V), Gamepad(GamepadButtonType::DPadRight)],
+            ),
+            (
+                Action::OrderBreed,
+                vec![Key(KeyCode::B), Gamepad(GamepadButtonType::North)],
+            ),
+        ]))
+    }
+}
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    // The binding replaces the other bindings of the action on the same kind
+    // of device, and is taken from the action it was bound to
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        for bindings in self.0.values_mut() {
+            bindings.retain(|other| *other != binding);
+        }
+
+        let bindings = self.0.entry(action).or_default();
+        bindings.retain(|other| other.is_gamepad() != binding.is_gamepad());
+        bindings.push(binding);
+    }
+
+    // actions added since the settings were saved get their default bindings
+    pub fn add_missing(&mut self) {
+        for (action, bindings) in Bindings::default().0 {
+            self.0.entry(action).or_insert(bindings);
+        }
+    }
+}
+
+// State of the actions on this frame, read from the keyboard, the mouse and
+// the gamepads
+#[derive(Debug, Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    just_released: HashSet<Action>,
+    // left stick, zero at rest
+    pub move_axis: Vec2,
+    // position in the window of the mouse, or of the cursor moved by the right stick
+    pub cursor: Option<Vec2>,
+    // the right stick moved the cursor last, the game draws it
+    pub virtual_cursor: bool,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    // the action is not seen again this frame, like `Input::reset`
+    pub fn reset(&mut self, action: Action) {
+        self.pressed.remove(&action);
+        self.just_pressed.remove(&action);
+        self.just_released.remove(&action);
+    }
+}
+
+// the devices actions are read from
+struct Devices<'a> {
+    keyboard: &'a Input<KeyCode>,
+    mouse: &'a Input<MouseButton>,
+    gamepad_buttons: &'a Input<GamepadButton>,
+    gamepad_axes: &'a Axis<GamepadAxis>,
+    gamepads: &'a Gamepads,
+}
+
+impl Devices<'_> {
+    // pressed, just pressed and just released
+    fn read(&self, binding: Binding) -> [bool; 3] {
+        match binding {
+            Binding::Key(key) => [
+                self.keyboard.pressed(key),
+                self.keyboard.just_pressed(key),
+                self.keyboard.just_released(key),
+            ],
+            Binding::Mouse(button) => [
+                self.mouse.pressed(button),
+                self.mouse.just_pressed(button),
+                self.mouse.just_released(button),
+            ],
+            Binding::Gamepad(button_type) => {
+                self.gamepads.iter().fold([false; 3], |state, gamepad| {
+                    let button = GamepadButton(*gamepad, button_type);
+                    [
+                        state[0] || self.gamepad_buttons.pressed(button),
+                        state[1] || self.gamepad_buttons.just_pressed(button),
+                        state[2] || self.gamepad_buttons.just_released(button),
+                    ]
+                })
+            }
+        }
+    }
+
+    // sum of the sticks of the connected gamepads, zero at rest
+    fn stick(&self, x: GamepadAxisType, y: GamepadAxisType) -> Vec2 {
+        let stick: Vec2 = self
+            .gamepads
+            .iter()
+            .map(|gamepad| {
+                let axis = |axis_type| {
+                    self.gamepad_axes
+                        .get(GamepadAxis(*gamepad, axis_type))
+                        .unwrap_or(0.0)
+                };
+                Vec2::new(axis(x), axis(y))
+            })
+            .fold(Vec2::ZERO, |sum, stick| sum + stick);
+
+        if stick.length() < STICK_DEADZONE {
+            Vec2::ZERO
+        } else {
+            stick.clamp_length_max(1.0)
+        }
+    }
+}
+
+// every frame, after the devices are updated
+#[allow(clippy::too_many_arguments)]
+pub fn update_actions_system(
+    keyboard: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
+    mut cursor_moved: EventReader<CursorMoved>,
+    windows: Res<Windows>,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut actions: ResMut<ActionState>,
+) {
+    let devices = Devices {
+        keyboard: &keyboard,
+        mouse: &mouse,
+        gamepad_buttons: &gamepad_buttons,
+        gamepad_axes: &gamepad_axes,
+        gamepads: &gamepads,
+    };
+
+    let actions = &mut *actions;
+    actions.pressed.clear();
+    actions.just_pressed.clear();
+    actions.just_released.clear();
+    for action in Action::ALL {
+        let [pressed, just_pressed, just_released] = settings
+            .bindings
+            .get(action)
+            .iter()
+            .map(|binding| devices.read(*binding))
+            .fold([false; 3], |state, read| {
+                [
+                    state[0] || read[0],
+                    state[1] || read[1],
+                    state[2] || read[2],
+                ]
+            });
+
+        if pressed {
+            actions.pressed.insert(action);
+        }
+        if just_pressed {
+            actions.just_pressed.insert(action);
+        }
+        // released by every one of its inputs
+        if just_released && !pressed {
+            actions.just_released.insert(action);
+        }
+    }
+
+    actions.move_axis = devices.stick(GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY);
+
+    // the mouse takes the cursor back as soon as it moves
+    if cursor_moved.iter().count() > 0 {
+        actions.virtual_cursor = false;
+    }
+
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let aim = devices.stick(GamepadAxisType::RightStickX, GamepadAxisType::RightStickY);
+    if aim != Vec2::ZERO {
+        // the virtual cursor starts from the mouse, or the middle of the window
+        let size = Vec2::new(window.width(), window.height());
+        let start = actions.cursor.unwrap_or(size / 2.0);
+        let moved = start + aim * VIRTUAL_CURSOR_SPEED * time.delta_seconds();
+
+        actions.cursor = Some(moved.clamp(Vec2::ZERO, size));
+        actions.virtual_cursor = true;
+    } else if !actions.virtual_cursor {
+        actions.cursor = window.cursor_position();
+    }
+}
+
+// Marks the image drawn at the position of the virtual cursor
+#[derive(Component)]
+pub struct VirtualCursorUI;
+
+// size of the virtual cursor on screen
+const VIRTUAL_CURSOR_SIZE: f32 = 32.0;
+
+pub fn spawn_virtual_cursor(mut commands: Commands, assets: Res<AssetsManager>) {
+    commands
+        .spawn_bundle(ImageBundle {
+            image: assets.texture_target.clone().into(),
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Px(VIRTUAL_CURSOR_SIZE), Val::Px(VIRTUAL_CURSOR_SIZE)),
+                display: Display::None,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(VirtualCursorUI)
+        .insert(GameEntity);
+}
+
+// the virtual cursor is only drawn while the right stick moves it
+pub fn virtual_cursor_system(
+    actions: Res<ActionState>,
+    mut cursor_query: Query<&mut Style, With<VirtualCursorUI>>,
+) {
+    for mut style in cursor_query.iter_mut() {
+        match actions.cursor.filter(|_| actions.virtual_cursor) {
+            Some(cursor) => {
+                style.display = Display::Flex;
+                style.position = Rect {
+                    left: Val::Px(cursor.x - VIRTUAL_CURSOR_SIZE / 2.0),
+                    bottom: Val::Px(cursor.y - VIRTUAL_CURSOR_SIZE / 2.0),
+                    ..default()
+                };
+            }
+            None => style.display = Display::None,
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use super::ActionState;
+use crate::camera::MainCamera;
+
+// Marks UI panels the world is not clicked through, on top of the buttons
+#[derive(Component)]
+pub struct BlocksPointer;
+
+// The cursor is over a button or a panel of the UI, clicks go to the UI and
+// not to the world
+#[derive(Debug, Default)]
+pub struct PointerOverUi(pub bool);
+
+// Position of the cursor in the world, moved by the mouse or a gamepad. Keeps
+// the camera projection of the frame to convert other positions in the window
+#[derive(Debug, Default)]
+pub struct WorldCursor {
+    // None when the cursor is outside of the window
+    pub position: Option<Vec2>,
+    window_size: Vec2,
+    ndc_to_world: Mat4,
+}
+
+impl WorldCursor {
+    // converts a position in the window to world-space coordinates
+    pub fn to_world(&self, screen_pos: Vec2) -> Vec2 {
+        // convert screen position [0..resolution] to ndc [-1..1] (gpu coordinates)
+        let ndc = (screen_pos / self.window_size) * 2.0 - Vec2::ONE;
+
+        self.ndc_to_world
+            .project_point3(ndc.extend(-1.0))
+            .truncate()
+    }
+}
+
+// every frame, once the cursor has moved
+pub fn world_cursor_system(
+    actions: Res<ActionState>,
+    windows: Res<Windows>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut world_cursor: ResMut<WorldCursor>,
+) {
+    let (window, (camera, camera_transform)) =
+        match (windows.get_primary(), camera_query.get_single()) {
+            (Some(window), Ok(camera)) => (window, camera),
+            _ => {
+                world_cursor.position = None;
+                return;
+            }
+        };
+
+    world_cursor.window_size = Vec2::new(window.width(), window.height());
+    // matrix for undoing the projection and camera transform
+    world_cursor.ndc_to_world =
+        camera_transform.compute_matrix() * camera.projection_matrix.inverse();
+    world_cursor.position = actions
+        .cursor
+        .map(|screen_pos| world_cursor.to_world(screen_pos));
+}
+
+// nodes the cursor can be over, the buttons and the panels
+type PointerNode<'a> = (&'a Node, &'a GlobalTransform, Option<&'a Interaction>);
+type PointerNodes = Or<(With<Interaction>, With<BlocksPointer>)>;
+
+// The buttons are hovered by the mouse as computed by the UI, the virtual
+// cursor of the gamepads is checked against the rects of the nodes like the
+// panels are
+pub fn pointer_over_ui_system(
+    actions: Res<ActionState>,
+    node_query: Query<PointerNode, PointerNodes>,
+    mut pointer_over_ui: ResMut<PointerOverUi>,
+) {
+    let over_node = |node: &Node, transform: &GlobalTransform| match actions.cursor {
+        Some(cursor) => {
+            let offset = (cursor - transform.translation.truncate()).abs();
+            offset.cmple(node.size / 2.0).all()
+        }
+        None => false,
+    };
+
+    pointer_over_ui.0 = node_query.iter().any(|(node, transform, interaction)| {
+        matches!(
+            interaction,
+            Some(Interaction::Hovered | Interaction::Clicked)
+        ) || over_node(node, transform)
+    });
+}
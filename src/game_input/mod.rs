@@ -0,0 +1,214 @@
+use bevy::{input::InputSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    chimeras::{ChimeraComponent, ChimeraOrder, OrderKind},
+    inventory_parts::InventoryItem,
+    schedule::{self, FixedUpdateStage},
+    selection::Selection,
+    states::GameStates,
+};
+
+pub use self::actions::{Action, ActionState, Binding, Bindings};
+pub use self::cursor::{BlocksPointer, PointerOverUi, WorldCursor};
+pub use self::replay::Replay;
+
+mod actions;
+mod cursor;
+mod replay;
+
+pub struct GameInputPlugin {
+    // inputs are read from this replay instead of the keyboard and mouse
+    pub replay: Option<Replay>,
+}
+
+impl Plugin for GameInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameInput>()
+            .init_resource::<ActionState>()
+            .init_resource::<WorldCursor>()
+            .init_resource::<PointerOverUi>();
+
+        // every frame, the menus read the actions too
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            actions::update_actions_system
+                .label("actions")
+                .after(InputSystem),
+        );
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            cursor::world_cursor_system.after("actions"),
+        );
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            cursor::pointer_over_ui_system.after("actions"),
+        );
+        app.add_system_set(
+            SystemSet::on_enter(GameStates::Game).with_system(actions::spawn_virtual_cursor),
+        );
+        app.add_system_set(
+            SystemSet::on_update(GameStates::Game).with_system(actions::virtual_cursor_system),
+        );
+
+        // gameplay systems run after "game_input" to use the inputs of this tick
+        match &self.replay {
+            Some(replay) => {
+                app.insert_resource(replay::ReplayPlayer::new(replay.clone()));
+
+                app.add_system_set_to_stage(
+                    FixedUpdateStage,
+                    schedule::on_fixed_update()
+                        .with_system(replay::play_inputs_system.label("game_input")),
+                );
+                app.add_system_set(
+                    SystemSet::on_exit(GameStates::Game).with_system(replay::rewind_replay_system),
+                );
+            }
+            None => {
+                app.init_resource::<PendingInput>()
+                    .init_resource::<replay::InputRecorder>();
+
+                // devices are read every frame, there can be zero or several ticks per frame
+                app.add_system_set(
+                    SystemSet::on_update(GameStates::Game)
+                        .with_system(read_inputs_system)
+                        .with_system(read_part_clicks_system),
+                );
+                app.add_system_set_to_stage(
+                    FixedUpdateStage,
+                    schedule::on_fixed_update()
+                        .with_system(next_tick_input_system.label("game_input"))
+                        .with_system(replay::record_inputs_system.after("game_input")),
+                );
+                app.add_system_set(
+                    SystemSet::on_update(GameStates::PauseMenu)
+                        .with_system(replay::interrupt_recording_system),
+                );
+                app.add_system_set(
+                    SystemSet::on_enter(GameStates::GameOver)
+                        .with_system(replay::write_replay_system),
+                );
+                app.add_system_set(
+                    SystemSet::on_exit(GameStates::Game).with_system(reset_inputs_system),
+                );
+            }
+        }
+    }
+}
+
+// Player inputs of one tick, read from the devices or from a replay
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameInput {
+    // sum of the pressed direction keys and of the left stick, not normalized
+    pub move_direction: Vec2,
+    pub capture_animal: bool,
+    pub switch_spell: bool,
+    // cast with the cursor outside of the UI
+    pub cast_spell: bool,
+    // world-space position of the cursor, None when it is outside of the window
+    pub cursor_position: Option<Vec2>,
+    pub order: Option<ChimeraOrder>,
+    // inventory indices of the parts clicked in the hotbar, in order
+    pub part_clicks: Vec<usize>,
+}
+
+// Inputs read since the last tick
+#[derive(Default)]
+struct PendingInput(GameInput);
+
+fn read_inputs_system(
+    actions: Res<ActionState>,
+    keyboard_input: Res<Input<KeyCode>>,
+    world_cursor: Res<WorldCursor>,
+    pointer_over_ui: Res<PointerOverUi>,
+    selection: Res<Selection>,
+    chimera_query: Query<&ChimeraComponent>,
+    mut pending: ResMut<PendingInput>,
+) {
+    let mut move_direction = actions.move_axis;
+
+    if actions.pressed(Action::MoveLeft) {
+        move_direction.x -= 1.0;
+    }
+    if actions.pressed(Action::MoveRight) {
+        move_direction.x += 1.0;
+    }
+
+    if actions.pressed(Action::MoveUp) {
+        move_direction.y += 1.0;
+    } else if actions.pressed(Action::MoveDown) {
+        move_direction.y -= 1.0;
+    }
+
+    // presses are kept until a tick uses them
+    let pending = &mut pending.0;
+    pending.move_direction = move_direction;
+    pending.capture_animal |= actions.just_pressed(Action::Capture);
+    pending.switch_spell |= actions.just_pressed(Action::NextSpell);
+    pending.cast_spell |= actions.just_pressed(Action::CastSpell) && !pointer_over_ui.0;
+    pending.cursor_position = world_cursor.position;
+
+    // orders go to the selected chimeras, or to every chimera when none is
+    // selected or shift is held
+    let order_kind = [
+        (Action::OrderStay, OrderKind::Stay),
+        (Action::OrderFollow, OrderKind::Follow),
+        (Action::OrderGuard, OrderKind::Guard),
+        (Action::OrderAttack, OrderKind::Attack),
+        (Action::OrderBreed, OrderKind::Breed),
+    ]
+    .into_iter()
+    .find(|(action, _)| actions.just_pressed(*action))
+    .map(|(_, kind)| kind);
+
+    if let Some(kind) = order_kind {
+        let to_all =
+            keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift);
+        let chimeras = match to_all {
+            true => Vec::new(),
+            false => selection
+                .units
+                .iter()
+                .filter_map(|entity| chimera_query.get(*entity).ok())
+                .map(|chimera| chimera.lineage.id)
+                .collect(),
+        };
+
+        pending.order = Some(ChimeraOrder { kind, chimeras });
+    }
+}
+
+// the clicked parts are selected on the next tick
+fn read_part_clicks_system(
+    item_query: Query<(&Interaction, &InventoryItem), Changed<Interaction>>,
+    mut pending: ResMut<PendingInput>,
+) {
+    for (interaction, item) in item_query.iter() {
+        if *interaction == Interaction::Clicked {
+            pending.0.part_clicks.push(item.index);
+        }
+    }
+}
+
+fn next_tick_input_system(mut pending: ResMut<PendingInput>, mut game_input: ResMut<GameInput>) {
+    *game_input = pending.0.clone();
+
+    let pending = &mut pending.0;
+    pending.capture_animal = false;
+    pending.switch_spell = false;
+    pending.cast_spell = false;
+    pending.order = None;
+    pending.part_clicks.clear();
+}
+
+// the next game is recorded from its first tick
+fn reset_inputs_system(
+    mut pending: ResMut<PendingInput>,
+    mut game_input: ResMut<GameInput>,
+    mut recorder: ResMut<replay::InputRecorder>,
+) {
+    *pending = PendingInput::default();
+    *game_input = GameInput::default();
+    *recorder = replay::InputRecorder::default();
+}
@@ -0,0 +1,105 @@
+use std::fs;
+
+use anyhow::anyhow;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::GameInput;
+use crate::{rng::GameRng, save_game::LoadGameEvent};
+
+// the last session is saved next to the executable's working directory
+const REPLAY_PATH: &str = "last.replay.ron";
+
+// Inputs of a whole session, one per tick, along with the seed of its random rolls
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub ticks: Vec<GameInput>,
+}
+
+impl Replay {
+    pub fn read(path: &str) -> anyhow::Result<Self> {
+        let bytes = fs::read(path)?;
+        ron::de::from_bytes(&bytes).map_err(|err| anyhow!("malformed replay file: {}", err))
+    }
+}
+
+#[derive(Default)]
+pub struct InputRecorder {
+    pub ticks: Vec<GameInput>,
+    // set when a save is loaded, the inputs would not match a new ranch anymore
+    pub interrupted: bool,
+}
+
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    pub next_tick: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            next_tick: 0,
+        }
+    }
+}
+
+pub fn record_inputs_system(game_input: Res<GameInput>, mut recorder: ResMut<InputRecorder>) {
+    recorder.ticks.push(game_input.clone());
+}
+
+pub fn interrupt_recording_system(
+    mut load_events: EventReader<LoadGameEvent>,
+    mut recorder: ResMut<InputRecorder>,
+) {
+    if load_events.iter().count() > 0 && !recorder.interrupted {
+        warn!("a save was loaded, this session will not be written as a replay");
+        recorder.interrupted = true;
+    }
+}
+
+pub fn write_replay_system(recorder: Res<InputRecorder>, rng: Res<GameRng>) {
+    if recorder.interrupted {
+        return;
+    }
+
+    let replay = Replay {
+        seed: rng.seed(),
+        ticks: recorder.ticks.clone(),
+    };
+
+    let serialized = match ron::ser::to_string(&replay) {
+        Ok(serialized) => serialized,
+        Err(err) => {
+            error!("could not serialize the replay: {}", err);
+            return;
+        }
+    };
+
+    match fs::write(REPLAY_PATH, serialized) {
+        Ok(()) => info!(
+            "replay of {} ticks saved to {}",
+            replay.ticks.len(),
+            REPLAY_PATH
+        ),
+        Err(err) => error!("could not write {}: {}", REPLAY_PATH, err),
+    }
+}
+
+// a new game plays the replay again, from the same seed
+pub fn rewind_replay_system(mut player: ResMut<ReplayPlayer>, mut game_input: ResMut<GameInput>) {
+    player.next_tick = 0;
+    *game_input = GameInput::default();
+}
+
+// feeds the recorded inputs back, the player stands still once they run out
+pub fn play_inputs_system(mut player: ResMut<ReplayPlayer>, mut game_input: ResMut<GameInput>) {
+    let tick = player.next_tick;
+    *game_input = player.replay.ticks.get(tick).cloned().unwrap_or_default();
+
+    if tick == player.replay.ticks.len() {
+        info!("replay finished after {} ticks", tick);
+    }
+    player.next_tick += 1;
+}
@@ -14,6 +14,8 @@ impl Plugin for GameOverPlugin {
         app.add_system_set(SystemSet::on_enter(GameStates::GameOver).with_system(setup_ui));
 
         app.add_system_set(SystemSet::on_update(GameStates::GameOver).with_system(button_handler));
+
+        app.add_system_set(SystemSet::on_exit(GameStates::GameOver).with_system(destroy_ui));
     }
 }
 
@@ -77,6 +79,18 @@ fn setup_ui(mut commands: Commands, assets: Res<AssetsManager>, day_cycle: Res<D
         ..Default::default()
     };
 
+    let restart_btn = UIButton::new(
+        "Restart".to_string(),
+        assets.font_regular.clone(),
+        "restart".to_string(),
+    );
+
+    let main_menu_btn = UIButton::new(
+        "Main menu".to_string(),
+        assets.font_regular.clone(),
+        "main_menu".to_string(),
+    );
+
     let quit_btn = UIButton::new(
         "Quit game".to_string(),
         assets.font_regular.clone(),
@@ -89,15 +103,25 @@ fn setup_ui(mut commands: Commands, assets: Res<AssetsManager>, day_cycle: Res<D
             parent.spawn_bundle(game_over_text);
             parent.spawn_bundle(score_text);
             parent.spawn_bundle(btn_group).with_children(|parent| {
-                //restart_btn.spawn(parent);
+                restart_btn.spawn(parent);
+                main_menu_btn.spawn(parent);
                 quit_btn.spawn(parent);
             });
         })
         .insert(GameOverScreenUI);
 }
 
+fn destroy_ui(mut commands: Commands, q_ui: Query<Entity, With<GameOverScreenUI>>) {
+    for entity in q_ui.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// replacing the whole state stack exits the game under the game over screen,
+// which cleans it up
 pub fn button_handler(
     mut interaction_query: Query<(&Interaction, &mut UiColor, &UIButton), Changed<Interaction>>,
+    mut game_state: ResMut<State<GameStates>>,
     mut exit: EventWriter<AppExit>,
 ) {
     for (interaction, mut color, button) in interaction_query.iter_mut() {
@@ -105,8 +129,13 @@ pub fn button_handler(
             Interaction::Clicked => {
                 *color = PRESSED_BUTTON.into();
                 match button.name.as_str() {
+                    "restart" => {
+                        game_state.overwrite_replace(GameStates::Game).unwrap();
+                    }
+                    "main_menu" => {
+                        game_state.overwrite_replace(GameStates::MainMenu).unwrap();
+                    }
                     "quit" => {
-                        //game_state.set(GameStates::MainMenu).unwrap();
                         exit.send(AppExit);
                     }
                     _ => {}
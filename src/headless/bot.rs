@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use super::Simulation;
+use crate::{
+    animals::{AnimalAttributesResource, AnimalComponent},
+    balance::GameBalance,
+    chimeras::{spawn_chimera, ChimeraIds, ChimeraOrder, ChimeraSlot, OrderKind},
+    constants,
+    game_input::GameInput,
+    health::Health,
+    player::{chimera_parts, Player},
+    rng::GameRng,
+};
+
+// spawns a player without sprite nor audio
+pub fn spawn_bot_player(mut commands: Commands) {
+    commands
+        .spawn_bundle(TransformBundle::from(Transform::from_xyz(0.0, 50.0, 100.0)))
+        .insert(Velocity::default())
+        .insert(RigidBody::Dynamic)
+        .insert(Collider::cuboid(25.0, 10.0))
+        .insert(LockedAxes::ROTATION_LOCKED)
+        .insert(Player::default())
+        .insert(Health::new(100.0, 1.0, 2.0));
+}
+
+// Scripted player: it hunts the closest animal, captures it and turns every
+// head and tail pair into a chimera, with up to two middle parts. Its chimeras
+// are always ordered to breed
+#[allow(clippy::too_many_arguments)]
+pub fn bot_player_system(
+    mut commands: Commands,
+    mut game_input: ResMut<GameInput>,
+    mut player_query: Query<(&mut Player, &mut Velocity, &Transform)>,
+    animal_query: Query<(Entity, &AnimalComponent, &Transform)>,
+    animal_attr_res: Res<AnimalAttributesResource>,
+    balance: Res<GameBalance>,
+    mut simulation: ResMut<Simulation>,
+    mut ids: ResMut<ChimeraIds>,
+    mut rng: ResMut<GameRng>,
+) {
+    let (mut player, mut vel, transform) = match player_query.get_single_mut() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    let position = transform.translation.truncate();
+
+    // the chimeras following the bot breed as soon as they are ready
+    game_input.order = Some(ChimeraOrder {
+        kind: OrderKind::Breed,
+        chimeras: Vec::new(),
+    });
+
+    // spawn a chimera as soon as a head and a tail are available, along with
+    // the middle parts at hand
+    let parts = &player.inventory.chimera_parts;
+    let slot_idx = |slot: ChimeraSlot| parts.iter().position(|part| part.kind.slot() == slot);
+
+    if let (Some(head_idx), Some(tail_idx)) =
+        (slot_idx(ChimeraSlot::Head), slot_idx(ChimeraSlot::Tail))
+    {
+        let mut picked = vec![head_idx, tail_idx];
+        picked.extend(
+            parts
+                .iter()
+                .enumerate()
+                .filter(|(_, part)| {
+                    !matches!(part.kind.slot(), ChimeraSlot::Head | ChimeraSlot::Tail)
+                })
+                .map(|(idx, _)| idx)
+                .take(constants::CHIMERA_MAX_PARTS - 2),
+        );
+
+        // remove the highest indices first so that the other ones stay valid
+        picked.sort_unstable_by(|a, b| b.cmp(a));
+        let chimera_parts = picked
+            .into_iter()
+            .map(|idx| player.inventory.chimera_parts.remove(idx))
+            .collect();
+
+        spawn_chimera(
+            chimera_parts,
+            position + Vec2::new(0.0, 150.0),
+            &balance,
+            &mut ids,
+            &mut rng,
+            &mut commands,
+        );
+        simulation.chimeras_spawned += 1;
+    }
+
+    let closest_animal = animal_query.iter().min_by(|(_, _, a), (_, _, b)| {
+        let distance_a = a.translation.truncate().distance(position);
+        let distance_b = b.translation.truncate().distance(position);
+        distance_a.total_cmp(&distance_b)
+    });
+
+    let (animal_entity, animal, animal_transform) = match closest_animal {
+        Some(animal) => animal,
+        None => {
+            // nothing to hunt, wait for the next spawn
+            vel.linvel = Vec2::lerp(vel.linvel, Vec2::ZERO, player.friction);
+            return;
+        }
+    };
+
+    let animal_position = animal_transform.translation.truncate();
+    let can_capture = player.inventory.has_room_for(1);
+
+    if can_capture && position.distance(animal_position) < player.capture_distance {
+        if let Some(animal_attr) = animal_attr_res.get(&animal.stats.kind) {
+            let parts = chimera_parts(&animal.stats, animal_attr, &balance);
+            if player.inventory.has_room_for(parts.len()) {
+                player.inventory.chimera_parts.extend(parts);
+                commands.entity(animal_entity).despawn_recursive();
+            }
+        }
+    } else {
+        vel.linvel = (animal_position - position).normalize_or_zero() * player.speed;
+    }
+}
@@ -0,0 +1,168 @@
+use std::{fs, process, time::Duration};
+
+use bevy::{
+    app::{AppExit, ScheduleRunnerSettings},
+    asset::{AssetPlugin, FileAssetIo},
+    hierarchy::HierarchyPlugin,
+    log::{Level, LogPlugin, LogSettings},
+    prelude::*,
+    transform::TransformPlugin,
+};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    animals::{self, AnimalAttributesResource, AnimalDefinitions},
+    balance::GameBalance,
+    chimeras::{self, ChimeraIds},
+    cli::CliArgs,
+    damage,
+    day_cycle::{self, DayCycleResource},
+    death::{self, KillStats},
+    game_input::GameInput,
+    health, navigation, pause, projectile,
+    rng::{self, GameRng},
+    schedule::{self, FixedUpdateStage},
+    states::GameStates,
+    villagers::{self, VillagerDefinitions},
+};
+
+mod bot;
+
+// same files as the ones loaded by the assets manager
+const BALANCE_PATH: &str = "data/game.balance.ron";
+const ANIMALS_PATH: &str = "data/farm.animals.ron";
+const VILLAGERS_PATH: &str = "data/village.villagers.ron";
+
+// Runs the game loop without window nor audio until the player dies or the
+// requested number of days is reached, then prints a report
+pub fn run(args: &CliArgs) {
+    let balance = read_data(BALANCE_PATH, GameBalance::from_bytes);
+    // textures are never displayed, every part uses the default handle
+    let definitions = read_data(ANIMALS_PATH, |bytes| {
+        AnimalDefinitions::from_bytes(bytes, |_| Handle::default())
+    });
+    let villager_definitions = read_data(VILLAGERS_PATH, |bytes| {
+        VillagerDefinitions::from_bytes(bytes, |_| Handle::default())
+    });
+
+    App::new()
+        // frames are not waited for, each of them runs a tick
+        .insert_resource(ScheduleRunnerSettings::run_loop(Duration::ZERO))
+        .insert_resource(LogSettings {
+            level: Level::WARN,
+            ..default()
+        })
+        .add_plugins(MinimalPlugins)
+        .add_plugin(LogPlugin)
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(AssetPlugin)
+        .add_plugin(
+            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0)
+                .with_default_system_setup(false),
+        )
+        .add_plugin(schedule::FixedUpdatePlugin {
+            tick_every_frame: true,
+        })
+        .insert_resource(balance)
+        .insert_resource(AnimalAttributesResource::from_definitions(&definitions))
+        .insert_resource(villager_definitions)
+        .insert_resource(Simulation {
+            days: args.days,
+            chimeras_spawned: 0,
+        })
+        .add_plugin(navigation::NavigationPlugin)
+        .add_plugin(animals::AnimalsPlugin)
+        .add_plugin(chimeras::ChimerasPlugin)
+        .add_plugin(villagers::VillagersPlugin)
+        .add_plugin(day_cycle::DayCyclePlugin)
+        .add_plugin(health::HealthPlugin)
+        .add_plugin(damage::DamagePlugin)
+        .add_plugin(projectile::ProjectilePlugin)
+        .add_plugin(pause::PausePlugin)
+        .add_plugin(death::DeathPlugin)
+        .add_plugin(rng::RngPlugin { seed: args.seed })
+        .add_plugin(HeadlessPlugin)
+        .add_state(GameStates::Game)
+        .run();
+}
+
+fn read_data<T>(path: &str, parse: impl FnOnce(&[u8]) -> anyhow::Result<T>) -> T {
+    let full_path = FileAssetIo::get_root_path().join("assets").join(path);
+
+    let result = fs::read(&full_path)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| parse(&bytes));
+
+    match result {
+        Ok(data) => data,
+        Err(err) => {
+            eprintln!("error: could not load {}: {}", full_path.display(), err);
+            process::exit(1);
+        }
+    }
+}
+
+// Progress of a headless run
+pub struct Simulation {
+    pub days: u8,
+    pub chimeras_spawned: u32,
+}
+
+struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        // on enter
+        app.add_system_set(
+            SystemSet::on_enter(GameStates::Game)
+                .with_system(crate::setup_physics)
+                .with_system(crate::setup_boundaries)
+                .with_system(bot::spawn_bot_player),
+        );
+
+        // written by the bot, which only gives breeding orders
+        app.init_resource::<GameInput>();
+
+        // on tick, the bot stands in for the inputs of the player
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            schedule::on_fixed_update().with_system(bot::bot_player_system.label("game_input")),
+        );
+
+        app.add_system(end_simulation_system);
+    }
+}
+
+fn end_simulation_system(
+    simulation: Res<Simulation>,
+    day_cycle: Res<DayCycleResource>,
+    game_state: Res<State<GameStates>>,
+    rng: Res<GameRng>,
+    kills: Res<KillStats>,
+    ids: Res<ChimeraIds>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let player_died = *game_state.current() == GameStates::GameOver;
+    if !player_died && day_cycle.days_passed < simulation.days {
+        return;
+    }
+
+    println!(
+        "simulation ended: {}",
+        if player_died {
+            "the player died"
+        } else {
+            "day limit reached"
+        }
+    );
+    println!("seed: {}", rng.seed());
+    println!("days survived: {}", day_cycle.days_passed);
+    println!("chimeras spawned: {}", simulation.chimeras_spawned);
+    // every chimera gets an id, the ones not spawned by the player were bred
+    println!("chimeras bred: {}", ids.next - simulation.chimeras_spawned);
+    println!("villagers killed: {}", kills.villagers_killed);
+    println!("chimeras lost: {}", kills.chimeras_lost);
+
+    exit.send(AppExit);
+}
@@ -1,13 +1,16 @@
 use bevy::prelude::*;
 
-use crate::{player::Player, states::GameStates};
+use crate::schedule::{self, tick_delta, FixedUpdateStage};
 
 pub struct HealthPlugin;
 
 impl Plugin for HealthPlugin {
     fn build(&self, app: &mut App) {
-        // on update
-        app.add_system_set(SystemSet::on_update(GameStates::Game).with_system(health_system));
+        // on tick
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            schedule::on_fixed_update().with_system(health_system.after("apply_damage")),
+        );
     }
 }
 
@@ -30,26 +33,9 @@ impl Health {
     }
 }
 
-pub fn health_system(
-    mut commands: Commands,
-    mut health_query: Query<(Entity, &mut Health)>,
-    player_query: Query<Entity, With<Player>>,
-    mut game_state: ResMut<State<GameStates>>,
-    time: Res<Time>,
-) {
-    let player_entity = player_query.iter().next().unwrap();
-
-    for (entity, mut health) in health_query.iter_mut() {
-        if health.health <= 0.0 {
-            if entity == player_entity {
-                game_state.push(GameStates::GameOver);
-            } else {
-                commands.entity(entity).despawn_recursive();
-            }
-            break;
-        }
-
-        health.regen_timer.tick(time.delta());
+pub fn health_system(mut health_query: Query<&mut Health>) {
+    for mut health in health_query.iter_mut() {
+        health.regen_timer.tick(tick_delta());
 
         if health.regen_timer.just_finished() {
             health.health += health.regen;
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::states::GameStates;
+use crate::states::{GameEntity, GameStates};
 
 pub struct HousesPlugin;
 
@@ -24,6 +24,7 @@ fn spawn_houses(mut commands: Commands, asset_server: Res<AssetServer>) {
     //house 1
     commands
         .spawn()
+        .insert(GameEntity)
         .insert(Collider::cuboid(60.0, 60.0))
         .insert_bundle( SpriteBundle {
         transform: Transform {
@@ -36,6 +37,7 @@ fn spawn_houses(mut commands: Commands, asset_server: Res<AssetServer>) {
     //house 2
    commands
     .spawn()
+    .insert(GameEntity)
     .insert(Collider::cuboid(60.0, 60.0))
     .insert_bundle( SpriteBundle {
     transform: Transform {
@@ -48,6 +50,7 @@ fn spawn_houses(mut commands: Commands, asset_server: Res<AssetServer>) {
 
     commands
         .spawn()
+        .insert(GameEntity)
         .insert(Collider::cuboid(60.0, 60.0))
         .insert_bundle( SpriteBundle {
         transform: Transform {
@@ -60,6 +63,7 @@ fn spawn_houses(mut commands: Commands, asset_server: Res<AssetServer>) {
 
     commands
         .spawn()
+        .insert(GameEntity)
         .insert(Collider::cuboid(60.0, 60.0))
         .insert_bundle( SpriteBundle {
         transform: Transform {
@@ -72,6 +76,7 @@ fn spawn_houses(mut commands: Commands, asset_server: Res<AssetServer>) {
 
     commands
         .spawn()
+        .insert(GameEntity)
         .insert(Collider::cuboid(60.0, 60.0))
         .insert_bundle( SpriteBundle {
         transform: Transform {
@@ -84,6 +89,7 @@ fn spawn_houses(mut commands: Commands, asset_server: Res<AssetServer>) {
 
     commands
         .spawn()
+        .insert(GameEntity)
         .insert(Collider::cuboid(60.0, 60.0))
         .insert_bundle( SpriteBundle {
         transform: Transform {
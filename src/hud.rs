@@ -3,10 +3,12 @@ use bevy::prelude::*;
 use crate::{
     assets_manager::AssetsManager,
     day_cycle::DayCycleResource,
+    death::KillStats,
+    game_input::BlocksPointer,
     health::Health,
     player::Player,
     spells::SpellKind,
-    states::GameStates,
+    states::{GameEntity, GameStates},
     stats_window::ui_bars::{BarStatType, UIBar},
 };
 
@@ -15,7 +17,11 @@ pub struct HudPlugin;
 impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_set(SystemSet::on_enter(GameStates::Game).with_system(setup_ui));
-        app.add_system_set(SystemSet::on_update(GameStates::Game).with_system(update_ui));
+        app.add_system_set(
+            SystemSet::on_update(GameStates::Game)
+                .with_system(update_ui)
+                .with_system(update_kills_ui),
+        );
     }
 }
 
@@ -25,6 +31,9 @@ pub struct ActiveSpellHud;
 #[derive(Component)]
 pub struct DaysElapsedHud;
 
+#[derive(Component)]
+pub struct KillsHud;
+
 fn setup_ui(mut commands: Commands, assets: Res<AssetsManager>) {
     let root = NodeBundle {
         // transform: Transform::from_xyz(0., 0., 101.),
@@ -36,7 +45,7 @@ fn setup_ui(mut commands: Commands, assets: Res<AssetsManager>) {
                 right: Val::Auto,
                 bottom: Val::Auto,
             },
-            size: Size::new(Val::Px(400.), Val::Px(150.)),
+            size: Size::new(Val::Px(400.), Val::Px(180.)),
             align_items: AlignItems::FlexStart,
             justify_content: JustifyContent::SpaceBetween,
             flex_direction: FlexDirection::ColumnReverse,
@@ -80,6 +89,23 @@ fn setup_ui(mut commands: Commands, assets: Res<AssetsManager>) {
         ..default()
     };
 
+    let kills = TextBundle {
+        style: Style { ..default() },
+        text: Text::with_section(
+            "Kills: 0  Chimeras lost: 0",
+            TextStyle {
+                color: Color::WHITE,
+                font: assets.font_regular.clone(),
+                font_size: 24.,
+            },
+            TextAlignment {
+                vertical: VerticalAlign::Center,
+                horizontal: HorizontalAlign::Left,
+            },
+        ),
+        ..default()
+    };
+
     let health = TextBundle {
         style: Style { ..default() },
         text: Text::with_section(
@@ -97,12 +123,17 @@ fn setup_ui(mut commands: Commands, assets: Res<AssetsManager>) {
         ..default()
     };
 
-    commands.spawn_bundle(root).with_children(|parent| {
-        parent.spawn_bundle(days_elapsed).insert(DaysElapsedHud);
-        parent.spawn_bundle(active_spell).insert(ActiveSpellHud);
-        parent.spawn_bundle(health);
-        create_player_ui_bar(parent, UIBar::from_type(BarStatType::Health));
-    });
+    commands
+        .spawn_bundle(root)
+        .with_children(|parent| {
+            parent.spawn_bundle(days_elapsed).insert(DaysElapsedHud);
+            parent.spawn_bundle(kills).insert(KillsHud);
+            parent.spawn_bundle(active_spell).insert(ActiveSpellHud);
+            parent.spawn_bundle(health);
+            create_player_ui_bar(parent, UIBar::from_type(BarStatType::Health));
+        })
+        .insert(BlocksPointer)
+        .insert(GameEntity);
 }
 
 fn update_ui(
@@ -173,6 +204,16 @@ fn update_ui(
         }
     }
 }
+
+fn update_kills_ui(kills: Res<KillStats>, mut q_kills: Query<&mut Text, With<KillsHud>>) {
+    for mut text in q_kills.iter_mut() {
+        text.sections[0].value = format!(
+            "Kills: {}  Chimeras lost: {}",
+            kills.villagers_killed, kills.chimeras_lost
+        );
+    }
+}
+
 #[derive(Component)]
 pub struct PlayerHealthValueBarComponent;
 
@@ -1,152 +1,104 @@
 use bevy::prelude::*;
 
-use crate::{
-    assets_manager::AssetsManager,
-    chimeras::{ChimeraPartAttributes, ChimeraPartKind},
-};
+use crate::{chimeras::ChimeraPartAttributes, constants, game_input::GameInput, player::Player};
 
-use super::InventoryItem;
-
-#[derive(Debug)]
+// Parts of the inventory selected to build a chimera. Selected on ticks from the
+// recorded clicks, so that replays select the same parts
+#[derive(Debug, Default)]
 pub struct InventoryManagement {
+    // the selection is cleared when the size of the inventory changes
     pub last_inv_size: usize,
-    pub is_selecting_first: bool,
-    pub target_1: ItemTarget,
-    pub target_2: ItemTarget,
+    // target replaced by the next click once all of them are used
+    pub next_replaced: usize,
+    pub targets: [ItemTarget; constants::CHIMERA_MAX_PARTS],
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ItemTarget {
-    pub selection: Option<(Entity, ChimeraPartAttributes)>,
-    pub is_setup: bool,
-    pub entity: Option<Entity>,
+    // index of the part in the player's inventory
+    pub selection: Option<(usize, ChimeraPartAttributes)>,
 }
 
 impl ItemTarget {
     pub fn reset(&mut self) {
         self.selection = None;
-        self.is_setup = false;
-        self.entity = None;
-    }
-}
-
-impl Default for ItemTarget {
-    fn default() -> Self {
-        ItemTarget {
-            selection: None,
-            is_setup: false,
-            entity: None,
-        }
     }
 }
 
 impl InventoryManagement {
     pub fn reset(&mut self) {
-        self.is_selecting_first = true;
-        self.target_1.reset();
-        self.target_2.reset();
+        self.next_replaced = 0;
+        for target in self.targets.iter_mut() {
+            target.reset();
+        }
     }
-}
 
-impl Default for InventoryManagement {
-    fn default() -> Self {
-        InventoryManagement {
-            last_inv_size: 0,
-            is_selecting_first: true,
-            target_1: ItemTarget::default(),
-            target_2: ItemTarget::default(),
-        }
+    // parts to build a chimera from, in the order they were selected
+    pub fn selected_parts(&self) -> Vec<ChimeraPartAttributes> {
+        self.targets
+            .iter()
+            .filter_map(|target| target.selection.as_ref())
+            .map(|(_, part)| part.clone())
+            .collect()
     }
-}
 
-pub fn handle_click(
-    mut commands: Commands,
-    q_inter: Query<(&Interaction, Entity, &InventoryItem), Changed<Interaction>>,
-    mut inv_man: ResMut<InventoryManagement>,
-) {
-    for (interaction, entity, item) in q_inter.iter() {
-        match *interaction {
-            Interaction::Clicked => {
-                // set selection
-                if inv_man.is_selecting_first {
-                    if let Some(entity) = inv_man.target_1.entity {
-                        commands.entity(entity).despawn_recursive();
-                        inv_man.target_1.reset();
-                    }
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.targets
+            .iter()
+            .any(|target| matches!(target.selection, Some((selected, _)) if selected == index))
+    }
 
-                    inv_man.target_1.selection = Some((entity, item.part.clone()));
-                } else {
-                    if let Some(entity) = inv_man.target_2.entity {
-                        commands.entity(entity).despawn_recursive();
-                        inv_man.target_2.reset();
-                    }
+    // clicking a selected part unselects it, otherwise the part takes a free
+    // target or the oldest one
+    pub fn click(&mut self, index: usize, part: &ChimeraPartAttributes) {
+        let selected = self
+            .targets
+            .iter()
+            .position(|target| matches!(target.selection, Some((item, _)) if item == index));
+        let empty = self
+            .targets
+            .iter()
+            .position(|target| target.selection.is_none());
+
+        let idx = match (selected, empty) {
+            (Some(idx), _) | (None, Some(idx)) => idx,
+            (None, None) => {
+                let idx = self.next_replaced;
+                self.next_replaced = (idx + 1) % constants::CHIMERA_MAX_PARTS;
+                idx
+            }
+        };
 
-                    inv_man.target_2.selection = Some((entity, item.part.clone()));
-                }
-                inv_man.is_selecting_first = !inv_man.is_selecting_first;
+        let target = &mut self.targets[idx];
+        target.reset();
 
-                info!("Inv_man: {:?}", inv_man);
-            }
-            _ => {}
+        if selected.is_none() {
+            target.selection = Some((index, part.clone()));
         }
     }
 }
 
-#[derive(Component)]
-pub struct InventoryItemTarget;
-
-pub fn set_selected_items(
-    mut commands: Commands,
+// every tick, applies the clicks on the parts of the inventory
+pub fn select_parts_system(
+    game_input: Res<GameInput>,
+    player_query: Query<&Player>,
     mut inv_man: ResMut<InventoryManagement>,
-    assets: Res<AssetsManager>,
 ) {
-    let mut setup = |target: &mut ItemTarget| {
-        if target.is_setup {
-            return;
-        }
-
-        if let Some((target_entity, part)) = target.selection.clone() {
-            let is_head = matches!(part.kind, ChimeraPartKind::Head(_));
-
-            commands.entity(target_entity).with_children(|parent| {
-                let child_id = parent
-                    .spawn_bundle(ImageBundle {
-                        image: assets.texture_target.clone().into(),
-                        style: Style {
-                            position_type: PositionType::Absolute,
-                            position: Rect {
-                                top: Val::Px(0.),
-                                bottom: Val::Auto,
-                                left: {
-                                    if is_head {
-                                        Val::Auto
-                                    } else {
-                                        Val::Px(0.)
-                                    }
-                                },
-                                right: {
-                                    if !is_head {
-                                        Val::Auto
-                                    } else {
-                                        Val::Px(0.)
-                                    }
-                                },
-                            },
-                            size: Size::new(Val::Px(48.), Val::Px(48.)),
-                            ..default()
-                        },
-                        ..default()
-                    })
-                    .insert(InventoryItemTarget)
-                    .id();
+    let player = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    let parts = &player.inventory.chimera_parts;
 
-                target.entity = Some(child_id);
-            });
+    // the selected indices are only valid for the inventory they were picked in
+    if inv_man.last_inv_size != parts.len() {
+        inv_man.last_inv_size = parts.len();
+        inv_man.reset();
+    }
 
-            target.is_setup = true;
+    for index in game_input.part_clicks.iter() {
+        if let Some(part) = parts.get(*index) {
+            inv_man.click(*index, part);
         }
-    };
-
-    setup(&mut inv_man.target_1);
-    setup(&mut inv_man.target_2);
+    }
 }
@@ -2,10 +2,12 @@ use bevy::{prelude::*, ui::widget::ImageMode};
 
 use crate::{
     assets_manager::AssetsManager,
-    chimeras::{ChimeraPartAttributes, ChimeraPartKind},
+    chimeras::{ChimeraPartAttributes, ChimeraPartKind, ChimeraSlot},
     constants,
+    game_input::BlocksPointer,
     player::Player,
-    states::GameStates,
+    schedule::{self, FixedUpdateStage},
+    states::{GameEntity, GameStates},
 };
 
 use self::interaction::InventoryManagement;
@@ -22,15 +24,27 @@ impl Plugin for InventoryUIPlugin {
         app.add_system_set(SystemSet::on_enter(GameStates::Game).with_system(setup_ui));
 
         // on update
-        app.add_system_set(
-            SystemSet::on_update(GameStates::Game)
-                .with_system(update_ui)
-                .with_system(interaction::handle_click)
-                .with_system(interaction::set_selected_items),
+        app.add_system_set(SystemSet::on_update(GameStates::Game).with_system(update_ui));
+
+        // on tick, before the selected parts are used
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            schedule::on_fixed_update().with_system(
+                interaction::select_parts_system
+                    .label("select_parts")
+                    .after("game_input"),
+            ),
         );
+
+        // on exit
+        app.add_system_set(SystemSet::on_exit(GameStates::Game).with_system(reset_inventory));
     }
 }
 
+fn reset_inventory(mut inv_man: ResMut<InventoryManagement>) {
+    *inv_man = InventoryManagement::default();
+}
+
 #[derive(Component)]
 pub struct PartInventoryContainer;
 
@@ -63,9 +77,13 @@ fn setup_ui(mut commands: Commands, assets: Res<AssetsManager>) {
         ..Default::default()
     };
 
-    commands.spawn_bundle(hotbar);
+    commands
+        .spawn_bundle(hotbar)
+        .insert(BlocksPointer)
+        .insert(GameEntity);
     commands
         .spawn_bundle(root)
+        .insert(GameEntity)
         // .with_children(|parent| {
         //     create_item_icon(&asset_server, parent, "chickenhead.png", true);
         //     create_item_icon(&asset_server, parent, "chickentail.png", false);
@@ -80,46 +98,57 @@ fn setup_ui(mut commands: Commands, assets: Res<AssetsManager>) {
 fn update_ui(
     mut commands: Commands,
     q_container: Query<Entity, With<PartInventoryContainer>>,
+    q_new_container: Query<(), Added<PartInventoryContainer>>,
     q_player: Query<&Player>,
-    mut inv_man: ResMut<InventoryManagement>,
+    inv_man: Res<InventoryManagement>,
+    assets: Res<AssetsManager>,
 ) {
+    // the selection changes on ticks, and is cleared when the inventory changes
+    if !inv_man.is_changed() && q_new_container.is_empty() {
+        return;
+    }
+
     for player in q_player.iter() {
-        // if inventory changed
-        if inv_man.last_inv_size != player.inventory.chimera_parts.len() {
-            // set new inventory length
-            inv_man.last_inv_size = player.inventory.chimera_parts.len();
-
-            // reset the references
-            inv_man.reset();
-
-            for cont_ent in q_container.iter() {
-                // start by cleaning
-                let mut container = commands.entity(cont_ent);
-                container.despawn_descendants();
-
-                // add the parts
-                container.with_children(|parent| {
-                    for part in player.inventory.chimera_parts.iter() {
-                        let is_head = matches!(part.kind, ChimeraPartKind::Head(_));
-                        create_item_icon(parent, &part.texture, is_head, part.clone())
-                    }
-                });
-            }
+        for cont_ent in q_container.iter() {
+            // start by cleaning
+            let mut container = commands.entity(cont_ent);
+            container.despawn_descendants();
+
+            // add the parts, the selected ones with a target over them
+            container.with_children(|parent| {
+                for (index, part) in player.inventory.chimera_parts.iter().enumerate() {
+                    let target = inv_man.is_selected(index).then_some(&assets.texture_target);
+                    create_item_icon(parent, index, part.clone(), target);
+                }
+            });
         }
     }
 }
 
+// part textures are as large as a whole animal, the icon shows the half of the
+// head or tail, or the middle of the other parts
+pub fn icon_offset(kind: &ChimeraPartKind) -> f32 {
+    match kind.slot() {
+        ChimeraSlot::Head => -48.,
+        ChimeraSlot::Tail => 0.,
+        _ => -24.,
+    }
+}
+
+// A part of the inventory, clicking it selects the part at this index
 #[derive(Component)]
 pub struct InventoryItem {
-    pub part: ChimeraPartAttributes,
+    pub index: usize,
 }
 
 fn create_item_icon(
     parent: &mut ChildBuilder,
-    handle: &Handle<Image>,
-    is_head: bool,
+    index: usize,
     part: ChimeraPartAttributes,
+    target: Option<&Handle<Image>>,
 ) {
+    let offset = icon_offset(&part.kind);
+
     parent
         .spawn_bundle(NodeBundle {
             style: Style {
@@ -140,11 +169,11 @@ fn create_item_icon(
         .with_children(|parent| {
             parent
                 .spawn_bundle(ButtonBundle {
-                    image: handle.clone().into(),
+                    image: part.texture.clone().into(),
                     style: Style {
                         position_type: PositionType::Absolute,
                         position: Rect {
-                            left: Val::Px(if is_head { -48. } else { 0. }),
+                            left: Val::Px(offset),
                             right: Val::Auto,
                             top: Val::Px(0.),
                             bottom: Val::Auto,
@@ -154,6 +183,26 @@ fn create_item_icon(
                     },
                     ..Default::default()
                 })
-                .insert(InventoryItem { part });
+                .insert(InventoryItem { index })
+                .with_children(|parent| {
+                    // the target covers the visible part of the icon
+                    if let Some(target) = target {
+                        parent.spawn_bundle(ImageBundle {
+                            image: target.clone().into(),
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                position: Rect {
+                                    top: Val::Px(0.),
+                                    bottom: Val::Auto,
+                                    left: Val::Px(-offset),
+                                    right: Val::Auto,
+                                },
+                                size: Size::new(Val::Px(48.), Val::Px(48.)),
+                                ..default()
+                            },
+                            ..default()
+                        });
+                    }
+                });
         });
 }
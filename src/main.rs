@@ -1,27 +1,40 @@
-use bevy::prelude::*;
+use bevy::{asset::AssetServerSettings, prelude::*};
 use bevy_ecs_tilemap::prelude::*;
 use bevy_rapier2d::prelude::*;
 use bevy_tweening::TweeningPlugin;
-use states::GameStates;
+use states::{GameEntity, GameStates};
 
 mod animals;
 mod animations;
 mod assets_manager;
+mod balance;
 mod behaviors;
 mod camera;
 mod chimeras;
+mod cli;
 mod constants;
+mod damage;
+mod death;
 mod day_cycle;
+mod game_input;
 mod gameover;
+mod headless;
 mod health;
 mod helpers;
 mod houses;
 mod hud;
 mod inventory_parts;
 mod main_menu;
+mod navigation;
+mod pause;
 mod pause_menu;
 mod player;
 mod projectile;
+mod rng;
+mod save_game;
+mod schedule;
+mod selection;
+mod settings;
 mod sound_manager;
 mod spells;
 mod states;
@@ -29,20 +42,50 @@ mod stats_window;
 mod villagers;
 
 fn main() {
+    let args = cli::CliArgs::from_env();
+    if args.headless {
+        headless::run(&args);
+        return;
+    }
+
+    // a replay brings the seed of the recorded session
+    let replay = args.replay.as_deref().map(|path| match game_input::Replay::read(path) {
+        Ok(replay) => replay,
+        Err(err) => {
+            eprintln!("error: could not read {}: {}", path, err);
+            std::process::exit(2);
+        }
+    });
+    let seed = replay.as_ref().map(|replay| replay.seed).or(args.seed);
+
+    // the window is created from the settings
+    let settings = settings::Settings::load();
+
     App::new()
+        // balance data is reloaded when edited, the web build has no file watcher
+        .insert_resource(AssetServerSettings {
+            watch_for_changes: !cfg!(target_arch = "wasm32"),
+            ..Default::default()
+        })
         .insert_resource(WindowDescriptor {
             resizable: false,
-            height: 720.,
-            width: 1280.,
+            height: settings.resolution().y,
+            width: settings.resolution().x,
+            mode: settings.window_mode.window_mode(),
             title: "Chimera Rancher - Rusty Jam #2".to_string(),
             ..Default::default()
         })
         .insert_resource(ClearColor(Color::rgb_u8(168, 52, 235)))
+        .insert_resource(settings)
         .add_plugins(DefaultPlugins)
         .add_plugin(TilemapPlugin)
         .add_plugin(assets_manager::AssetsManagerPlugin)
-        .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
+        .add_plugin(balance::BalancePlugin)
+        .add_plugin(rng::RngPlugin { seed })
+        .add_plugin(game_input::GameInputPlugin { replay })
+        .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0).with_default_system_setup(false))
         .add_plugin(RapierDebugRenderPlugin::default())
+        .add_plugin(schedule::FixedUpdatePlugin { tick_every_frame: false })
         .add_plugin(animals::AnimalsPlugin)
         .add_plugin(chimeras::ChimerasPlugin)
         .add_plugin(villagers::VillagersPlugin)
@@ -50,18 +93,25 @@ fn main() {
         .add_plugin(camera::CameraPlugin)
         .add_plugin(animations::AnimationsPlugin)
         .add_plugin(stats_window::StatsWindowPlugin)
+        .add_plugin(selection::SelectionPlugin)
         .add_plugin(inventory_parts::InventoryUIPlugin)
         .add_plugin(main_menu::MainMenuPlugin)
         .add_plugin(pause_menu::PauseMenuPlugin)
+        .add_plugin(pause::PausePlugin)
+        .add_plugin(settings::SettingsPlugin)
         .add_plugin(day_cycle::DayCyclePlugin)
         .add_plugin(health::HealthPlugin)
+        .add_plugin(damage::DamagePlugin)
+        .add_plugin(death::DeathPlugin)
         .add_plugin(sound_manager::SoundChannelsPlugin)
         .add_plugin(spells::SpellsPlugin)
         .add_plugin(gameover::GameOverPlugin)
         .add_plugin(hud::HudPlugin)
         .add_plugin(TweeningPlugin)
         .add_plugin(houses::HousesPlugin)
+        .add_plugin(navigation::NavigationPlugin)
         .add_plugin(projectile::ProjectilePlugin)
+        .add_plugin(save_game::SaveGamePlugin)
         .add_state(GameStates::AssetsLoading)
         .add_system_set(
             SystemSet::on_enter(GameStates::Game)
@@ -74,7 +124,13 @@ fn main() {
         )
         .add_system_set(
             SystemSet::on_update(GameStates::Game)
-                .with_system(helpers::texture::set_texture_filters_to_nearest),
+                .with_system(helpers::texture::set_texture_filters_to_nearest)
+                .with_system(constants::update_max_stats),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameStates::Game)
+                .with_system(states::despawn_game_entities)
+                .with_system(despawn_tiles),
         )
         .run();
 }
@@ -142,6 +198,11 @@ fn setup_tiles(mut commands: Commands, asset_server: Res<AssetServer>, mut map_q
         .insert(GlobalTransform::default());
 }
 
+// the map layers and chunks are not children of the map entity
+fn despawn_tiles(mut commands: Commands, mut map_query: MapQuery) {
+    map_query.despawn(&mut commands, 0_u16);
+}
+
 fn setup_physics(mut rapier_config: ResMut<RapierConfiguration>) {
     rapier_config.gravity = [0.0, 0.0].into();
 }
@@ -151,6 +212,7 @@ fn setup_boundaries(mut commands: Commands) {
     /*Bottom Edge*/
     commands
         .spawn()
+        .insert(GameEntity)
         .insert(Collider::cuboid(1000.0, 20.0))
         .insert_bundle(TransformBundle::from(Transform::from_xyz(
             0.0, -1500.0, 0.0,
@@ -159,12 +221,14 @@ fn setup_boundaries(mut commands: Commands) {
     /*Top Edge*/
     commands
         .spawn()
+        .insert(GameEntity)
         .insert(Collider::cuboid(1000.0, 20.0))
         .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1500.0, 0.0)));
 
     /*Left Edge*/
     commands
         .spawn()
+        .insert(GameEntity)
         .insert(Collider::cuboid(20.0, 1500.0))
         .insert_bundle(TransformBundle::from(Transform::from_xyz(
             -1000.0, 0.0, 0.0,
@@ -173,6 +237,7 @@ fn setup_boundaries(mut commands: Commands) {
     /*Right Edge*/
     commands
         .spawn()
+        .insert(GameEntity)
         .insert(Collider::cuboid(20.0, 1500.0))
         .insert_bundle(TransformBundle::from(Transform::from_xyz(1000.0, 0.0, 0.0)));
 
@@ -1,6 +1,13 @@
-use bevy::prelude::*;
+use bevy::{app::AppExit, prelude::*};
+use bevy_kira_audio::Audio;
 
-use crate::states::GameStates;
+use crate::{
+    assets_manager::AssetsManager,
+    game_input::{Action, ActionState},
+    pause_menu::button::{UIButton, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON},
+    save_game::{self, ContinueGame},
+    states::GameStates,
+};
 
 pub struct MainMenuPlugin;
 
@@ -8,40 +15,250 @@ impl Plugin for MainMenuPlugin {
     fn build(&self, app: &mut App) {
         // on enter
         app.add_system_set(SystemSet::on_enter(GameStates::MainMenu).with_system(build_ui));
+        app.add_system_set(SystemSet::on_enter(GameStates::Credits).with_system(build_credits_ui));
 
         // on update
-        app.add_system_set(SystemSet::on_update(GameStates::MainMenu).with_system(start_game));
+        app.add_system_set(SystemSet::on_update(GameStates::MainMenu).with_system(button_handler));
+        app.add_system_set(
+            SystemSet::on_update(GameStates::Credits)
+                .with_system(credits_button_handler)
+                .with_system(close_credits),
+        );
+
+        // on pause, only the screen on top of the stack has its buttons
+        app.add_system_set(SystemSet::on_pause(GameStates::MainMenu).with_system(destroy_ui));
+        // on resume
+        app.add_system_set(SystemSet::on_resume(GameStates::MainMenu).with_system(build_ui));
 
         // on exit
         app.add_system_set(SystemSet::on_exit(GameStates::MainMenu).with_system(destroy_ui));
+        app.add_system_set(SystemSet::on_exit(GameStates::Credits).with_system(destroy_credits_ui));
     }
 }
 
 #[derive(Component)]
 pub struct MainMenuUI;
 
-fn build_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands
-        .spawn_bundle(ImageBundle {
-            image: asset_server.load("main_menu.png").into(), // not using assetsmanager as we don't load everything on the main menu
-            style: Style {
-                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
-                ..default()
-            },
+#[derive(Component)]
+pub struct CreditsUI;
+
+fn build_ui(mut commands: Commands, asset_server: Res<AssetServer>, assets: Res<AssetsManager>) {
+    let background = ImageBundle {
+        image: asset_server.load("main_menu.png").into(), // not using assetsmanager as we don't load everything on the main menu
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            // the buttons at the bottom of the screen
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::FlexStart,
+            ..default()
+        },
+        ..default()
+    };
+
+    let btn_group = NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Auto),
+            padding: Rect::all(Val::Px(10.)),
+            flex_direction: FlexDirection::Row,
+            justify_content: JustifyContent::Center,
             ..default()
+        },
+        color: Color::rgba_u8(0, 0, 0, 150).into(),
+        ..default()
+    };
+
+    let mut buttons = vec![("New game", "new_game")];
+    // there is nothing to continue before the first save
+    if save_game::save_exists() {
+        buttons.push(("Continue", "continue"));
+    }
+    buttons.extend([
+        ("Settings", "settings"),
+        ("Credits", "credits"),
+        ("Quit", "quit"),
+    ]);
+
+    commands
+        .spawn_bundle(background)
+        .with_children(|parent| {
+            parent.spawn_bundle(btn_group).with_children(|parent| {
+                for (text, name) in buttons {
+                    UIButton::new(text.to_string(), assets.font_bold.clone(), name.to_string())
+                        .spawn(parent);
+                }
+            });
         })
         .insert(MainMenuUI);
 }
 
-fn start_game(mut keyboard: ResMut<Input<KeyCode>>, mut game_state: ResMut<State<GameStates>>) {
-    if keyboard.just_pressed(KeyCode::Return) {
-        game_state.set(GameStates::Game).unwrap();
-        keyboard.reset(KeyCode::Return);
+fn destroy_ui(mut commands: Commands, q_ui: Query<Entity, With<MainMenuUI>>) {
+    for entity in q_ui.iter() {
+        commands.entity(entity).despawn_recursive();
     }
 }
 
-fn destroy_ui(mut commands: Commands, q_ui: Query<Entity, With<MainMenuUI>>) {
+fn button_handler(
+    mut commands: Commands,
+    mut interaction_query: Query<(&Interaction, &mut UiColor, &UIButton), Changed<Interaction>>,
+    mut game_state: ResMut<State<GameStates>>,
+    mut exit: EventWriter<AppExit>,
+    audio: Res<Audio>,
+    assets: Res<AssetsManager>,
+) {
+    for (interaction, mut color, button) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                audio.play(assets.sound_button.clone());
+                *color = PRESSED_BUTTON.into();
+                match button.name.as_str() {
+                    "new_game" => {
+                        game_state.overwrite_set(GameStates::Game).unwrap();
+                    }
+                    "continue" => {
+                        commands.insert_resource(ContinueGame);
+                        game_state.overwrite_set(GameStates::Game).unwrap();
+                    }
+                    "settings" => {
+                        game_state.overwrite_push(GameStates::Settings).unwrap();
+                    }
+                    "credits" => {
+                        game_state.overwrite_push(GameStates::Credits).unwrap();
+                    }
+                    "quit" => {
+                        exit.send(AppExit);
+                    }
+                    _ => {}
+                }
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+const CREDITS: &[&str] = &[
+    "Made by TheRealTeamFReSh for the Rusty Jam #2",
+    "Built with Bevy, Rapier and Kira",
+    "Fonts: Fira Sans, Animal Paw",
+    "Music: Ambient Piano & Strings, Inspiring Motivational Mood, Price of Freedom",
+];
+
+fn build_credits_ui(mut commands: Commands, assets: Res<AssetsManager>) {
+    let root = NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        color: Color::BLACK.into(),
+        ..default()
+    };
+
+    let title = TextBundle {
+        style: Style {
+            margin: Rect {
+                bottom: Val::Px(40.),
+                ..default()
+            },
+            ..default()
+        },
+        text: Text::with_section(
+            "Credits",
+            TextStyle {
+                font_size: 96.,
+                font: assets.font_bold.clone(),
+                color: Color::rgb_u8(220, 220, 220),
+            },
+            TextAlignment::default(),
+        ),
+        ..default()
+    };
+
+    let back_btn = UIButton::new(
+        "Back".to_string(),
+        assets.font_regular.clone(),
+        "back".to_string(),
+    );
+
+    commands
+        .spawn_bundle(root)
+        .with_children(|parent| {
+            parent.spawn_bundle(title);
+            for line in CREDITS {
+                parent.spawn_bundle(TextBundle {
+                    style: Style {
+                        margin: Rect::all(Val::Px(8.)),
+                        ..default()
+                    },
+                    text: Text::with_section(
+                        *line,
+                        TextStyle {
+                            font_size: 32.,
+                            font: assets.font_regular.clone(),
+                            color: Color::rgb_u8(220, 220, 220),
+                        },
+                        TextAlignment::default(),
+                    ),
+                    ..default()
+                });
+            }
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        margin: Rect {
+                            top: Val::Px(60.),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    ..default()
+                })
+                .with_children(|parent| back_btn.spawn(parent));
+        })
+        .insert(CreditsUI);
+}
+
+fn destroy_credits_ui(mut commands: Commands, q_ui: Query<Entity, With<CreditsUI>>) {
     for entity in q_ui.iter() {
         commands.entity(entity).despawn_recursive();
     }
 }
+
+fn credits_button_handler(
+    mut interaction_query: Query<(&Interaction, &mut UiColor, &UIButton), Changed<Interaction>>,
+    mut game_state: ResMut<State<GameStates>>,
+    audio: Res<Audio>,
+    assets: Res<AssetsManager>,
+) {
+    for (interaction, mut color, button) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                audio.play(assets.sound_button.clone());
+                *color = PRESSED_BUTTON.into();
+                if button.name == "back" {
+                    game_state.overwrite_pop().unwrap();
+                }
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+fn close_credits(mut actions: ResMut<ActionState>, mut game_state: ResMut<State<GameStates>>) {
+    if actions.just_pressed(Action::Pause) {
+        game_state.overwrite_pop().unwrap();
+        actions.reset(Action::Pause);
+    }
+}
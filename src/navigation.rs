@@ -0,0 +1,275 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    schedule::{self, FixedUpdateStage},
+    states::GameStates,
+};
+
+// side of a cell of the navigation grid
+const CELL_SIZE: f32 = 25.0;
+// cells this close to an obstacle are blocked, so that units do not graze it
+const CLEARANCE: f32 = 15.0;
+// a path is searched again when its goal moved this far
+const REPATH_DISTANCE: f32 = 50.0;
+
+// A* costs of a straight and a diagonal step
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavGrid>();
+
+        // on tick, before the behaviors move the units
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            schedule::on_fixed_update().with_system(build_nav_grid_system.label("nav_grid")),
+        );
+
+        // on exit
+        app.add_system_set(SystemSet::on_exit(GameStates::Game).with_system(reset_nav_grid));
+    }
+}
+
+// Cells of the map blocked by the houses and the map boundaries
+#[derive(Default)]
+pub struct NavGrid {
+    // world position of the corner of the first cell
+    origin: Vec2,
+    columns: usize,
+    rows: usize,
+    blocked: Vec<bool>,
+}
+
+impl NavGrid {
+    // obstacles are boxes given by their center and half extents
+    pub fn from_obstacles(obstacles: &[(Vec2, Vec2)]) -> Self {
+        if obstacles.is_empty() {
+            return Self::default();
+        }
+
+        let min = obstacles
+            .iter()
+            .map(|(center, half_extents)| *center - *half_extents)
+            .reduce(Vec2::min)
+            .unwrap();
+        let max = obstacles
+            .iter()
+            .map(|(center, half_extents)| *center + *half_extents)
+            .reduce(Vec2::max)
+            .unwrap();
+        let columns = ((max.x - min.x) / CELL_SIZE).ceil() as usize;
+        let rows = ((max.y - min.y) / CELL_SIZE).ceil() as usize;
+
+        let mut grid = Self {
+            origin: min,
+            columns,
+            rows,
+            blocked: Vec::with_capacity(columns * rows),
+        };
+        for idx in 0..columns * rows {
+            let center = grid.cell_center(idx);
+            let blocked = obstacles.iter().any(|(obstacle, half_extents)| {
+                let reach = *half_extents + Vec2::splat(CLEARANCE);
+                (center - *obstacle).abs().cmplt(reach).all()
+            });
+            grid.blocked.push(blocked);
+        }
+
+        grid
+    }
+
+    // index of the cell containing a position, None outside of the grid
+    fn cell(&self, position: Vec2) -> Option<usize> {
+        let coords = ((position - self.origin) / CELL_SIZE).floor();
+        if coords.x < 0.0 || coords.y < 0.0 {
+            return None;
+        }
+
+        let (column, row) = (coords.x as usize, coords.y as usize);
+        (column < self.columns && row < self.rows).then(|| row * self.columns + column)
+    }
+
+    fn cell_center(&self, idx: usize) -> Vec2 {
+        let (column, row) = (idx % self.columns, idx / self.columns);
+        self.origin + (Vec2::new(column as f32, row as f32) + 0.5) * CELL_SIZE
+    }
+
+    fn is_blocked(&self, position: Vec2) -> bool {
+        self.cell(position).is_some_and(|idx| self.blocked[idx])
+    }
+
+    // whether a unit can move in a straight line between two positions, the
+    // cells of both ends are not checked so that units can leave or reach the
+    // edge of an obstacle
+    pub fn line_of_sight(&self, from: Vec2, to: Vec2) -> bool {
+        let steps = (from.distance(to) / (CELL_SIZE / 2.0)).ceil() as usize;
+        let (from_cell, to_cell) = (self.cell(from), self.cell(to));
+
+        (1..steps).all(|step| {
+            let position = from.lerp(to, step as f32 / steps as f32);
+            let cell = self.cell(position);
+            cell == from_cell || cell == to_cell || !self.is_blocked(position)
+        })
+    }
+
+    // A* search from a position to another, the path is made of the centers of
+    // the cells to go through and ends on the goal
+    pub fn find_path(&self, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        let (start_cell, goal_cell) = (self.cell(start)?, self.cell(goal)?);
+
+        let heuristic = |idx: usize| {
+            let dx = (idx % self.columns).abs_diff(goal_cell % self.columns) as u32;
+            let dy = (idx / self.columns).abs_diff(goal_cell / self.columns) as u32;
+            STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+        };
+
+        let mut costs = vec![u32::MAX; self.blocked.len()];
+        let mut came_from = vec![usize::MAX; self.blocked.len()];
+        let mut open = BinaryHeap::new();
+        costs[start_cell] = 0;
+        open.push(Reverse((heuristic(start_cell), start_cell)));
+
+        while let Some(Reverse((_, idx))) = open.pop() {
+            if idx == goal_cell {
+                break;
+            }
+
+            for (neighbor, step_cost) in self.neighbors(idx, goal_cell) {
+                let cost = costs[idx] + step_cost;
+                if cost < costs[neighbor] {
+                    costs[neighbor] = cost;
+                    came_from[neighbor] = idx;
+                    open.push(Reverse((cost + heuristic(neighbor), neighbor)));
+                }
+            }
+        }
+
+        if costs[goal_cell] == u32::MAX {
+            return None;
+        }
+
+        let mut path = vec![goal];
+        let mut idx = came_from[goal_cell];
+        while idx != usize::MAX && idx != start_cell {
+            path.push(self.cell_center(idx));
+            idx = came_from[idx];
+        }
+        path.reverse();
+
+        Some(path)
+    }
+
+    // free cells around a cell, diagonals do not cut the corners of obstacles.
+    // The goal is reachable even when blocked
+    fn neighbors(&self, idx: usize, goal: usize) -> impl Iterator<Item = (usize, u32)> + '_ {
+        let (column, row) = ((idx % self.columns) as isize, (idx / self.columns) as isize);
+        let free = move |column: isize, row: isize| {
+            if column < 0 || row < 0 || column >= self.columns as isize || row >= self.rows as isize
+            {
+                return None;
+            }
+            let neighbor = row as usize * self.columns + column as usize;
+            (neighbor == goal || !self.blocked[neighbor]).then_some(neighbor)
+        };
+
+        [
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+            (-1, -1),
+            (-1, 1),
+            (1, -1),
+            (1, 1),
+        ]
+        .into_iter()
+        .filter_map(move |(dx, dy)| {
+            let neighbor = free(column + dx, row + dy)?;
+            if dx == 0 || dy == 0 {
+                return Some((neighbor, STRAIGHT_COST));
+            }
+
+            free(column + dx, row)?;
+            free(column, row + dy)?;
+            Some((neighbor, DIAGONAL_COST))
+        })
+    }
+}
+
+// Path followed by a unit around the obstacles, searched again when its goal moves
+#[derive(Component, Default)]
+pub struct NavPath {
+    goal: Option<Vec2>,
+    waypoints: Vec<Vec2>,
+}
+
+impl NavPath {
+    // direction to take from a position to reach a goal
+    pub fn direction(&mut self, grid: &NavGrid, position: Vec2, goal: Vec2) -> Vec2 {
+        // straight to the goal when nothing is in the way
+        if grid.line_of_sight(position, goal) {
+            self.goal = None;
+            self.waypoints.clear();
+            return (goal - position).normalize_or_zero();
+        }
+
+        let moved = self
+            .goal
+            .is_none_or(|old_goal| old_goal.distance(goal) > REPATH_DISTANCE);
+        // a goal out of reach is not searched again until it moves
+        if moved {
+            self.goal = Some(goal);
+            self.waypoints = grid.find_path(position, goal).unwrap_or_default();
+        }
+
+        // skip the waypoints reached or already seen past
+        while self.waypoints.len() > 1
+            && (position.distance(self.waypoints[0]) < CELL_SIZE / 2.0
+                || grid.line_of_sight(position, self.waypoints[1]))
+        {
+            self.waypoints.remove(0);
+        }
+
+        // without a path, the unit tries its luck in a straight line
+        let waypoint = self.waypoints.first().copied().unwrap_or(goal);
+        (waypoint - position).normalize_or_zero()
+    }
+}
+
+fn reset_nav_grid(mut grid: ResMut<NavGrid>) {
+    *grid = NavGrid::default();
+}
+
+// the grid is built again when static colliders are added, houses and map
+// boundaries are spawned when entering the game
+fn build_nav_grid_system(
+    added_query: Query<(), (Added<Collider>, Without<RigidBody>)>,
+    obstacle_query: Query<(&Collider, &Transform), Without<RigidBody>>,
+    mut grid: ResMut<NavGrid>,
+) {
+    if added_query.is_empty() {
+        return;
+    }
+
+    let obstacles: Vec<(Vec2, Vec2)> = obstacle_query
+        .iter()
+        .filter_map(|(collider, transform)| {
+            let cuboid = collider.as_cuboid()?;
+            Some((transform.translation.truncate(), cuboid.half_extents()))
+        })
+        .collect();
+
+    *grid = NavGrid::from_obstacles(&obstacles);
+    info!(
+        "navigation grid built from {} obstacles, {}x{} cells",
+        obstacles.len(),
+        grid.columns,
+        grid.rows
+    );
+}
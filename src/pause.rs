@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    schedule::{tick_delta, TickEveryFrame},
+    states::GameStates,
+};
+
+// Freezes the game while a menu is pushed over it: the pause menu or the game
+// over screen. Ticks already stop outside of `GameStates::Game`, this also
+// stops the physics and the per frame timers. Audio channels are paused by the
+// sound manager
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameClock>();
+
+        // every frame, before the gameplay systems read the clock
+        app.add_system_to_stage(CoreStage::PreUpdate, game_clock_system);
+
+        // on enter, a new game starts unpaused whatever the last one was left in
+        app.add_system_set(
+            SystemSet::on_enter(GameStates::Game)
+                .with_system(reset_game_clock)
+                .with_system(resume_physics),
+        );
+        // on pause
+        app.add_system_set(SystemSet::on_pause(GameStates::Game).with_system(pause_physics));
+        // on resume
+        app.add_system_set(SystemSet::on_resume(GameStates::Game).with_system(resume_physics));
+    }
+}
+
+// Time spent playing the current game, it does not advance while paused. Per
+// frame gameplay timers tick with it instead of `Time`, ticks use `tick_delta`
+#[derive(Debug, Default)]
+pub struct GameClock {
+    elapsed: Duration,
+    // time played during the current frame, zero while paused
+    delta: Duration,
+}
+
+impl GameClock {
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    #[allow(dead_code)]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+fn game_clock_system(
+    time: Res<Time>,
+    tick_every_frame: Option<Res<TickEveryFrame>>,
+    state: Res<State<GameStates>>,
+    mut clock: ResMut<GameClock>,
+) {
+    let delta = if *state.current() != GameStates::Game {
+        Duration::ZERO
+    } else if tick_every_frame.is_some() {
+        tick_delta()
+    } else {
+        time.delta()
+    };
+    clock.delta = delta;
+    clock.elapsed += delta;
+}
+
+fn reset_game_clock(mut clock: ResMut<GameClock>) {
+    *clock = GameClock::default();
+}
+
+// bodies keep their velocities, they move on from where they stopped
+fn pause_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    info!("pausing the game");
+    rapier_config.physics_pipeline_active = false;
+}
+
+fn resume_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = true;
+}
@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use crate::game_input::{Action, ActionState};
+
 pub const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
 pub const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 pub const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
@@ -10,6 +12,8 @@ pub struct UIButton {
     text: String,
     font_handle: Handle<Font>,
     pub name: String,
+    size: Size<Val>,
+    font_size: f32,
 }
 
 impl UIButton {
@@ -18,13 +22,22 @@ impl UIButton {
             text,
             font_handle,
             name,
+            size: Size::new(Val::Px(200.0), Val::Px(65.0)),
+            font_size: 40.0,
         }
     }
 
+    // smaller buttons, like the arrows of the settings screen
+    pub fn with_size(mut self, width: f32, height: f32, font_size: f32) -> Self {
+        self.size = Size::new(Val::Px(width), Val::Px(height));
+        self.font_size = font_size;
+        self
+    }
+
     pub fn spawn(&self, parent: &mut ChildBuilder) {
         let container_component = ButtonBundle {
             style: Style {
-                size: Size::new(Val::Px(200.0), Val::Px(65.0)),
+                size: self.size,
                 // center button
                 margin: Rect::all(Val::Px(10.)),
                 // horizontally center child text
@@ -42,7 +55,7 @@ impl UIButton {
                 self.text.clone(),
                 TextStyle {
                     font: self.font_handle.clone(),
-                    font_size: 40.0,
+                    font_size: self.font_size,
                     color: Color::rgb(0.9, 0.9, 0.9),
                 },
                 Default::default(),
@@ -77,3 +90,104 @@ pub fn button_system(
         }
     }
 }
+
+// Button chosen with the keyboard or a gamepad, highlighted like a hovered one
+#[derive(Component)]
+pub struct FocusedButton;
+
+type FocusableButton<'a> = (
+    Entity,
+    &'a mut Interaction,
+    &'a mut UiColor,
+    &'a GlobalTransform,
+    Option<&'a FocusedButton>,
+);
+
+// Moves the focus between the buttons on screen with the movement actions or
+// the left stick, from top to bottom then left to right. The confirm action
+// presses the focused button as if it was clicked
+pub fn button_focus_system(
+    mut commands: Commands,
+    // buttons pressed on the last frame, released like a mouse click would be
+    mut pressed: Local<Vec<Entity>>,
+    // the stick moves the focus once each time it is tilted
+    mut stick_tilted: Local<bool>,
+    actions: Res<ActionState>,
+    mut button_query: Query<FocusableButton, With<UIButton>>,
+) {
+    for entity in pressed.drain(..) {
+        if let Ok((_, mut interaction, ..)) = button_query.get_mut(entity) {
+            *interaction = Interaction::None;
+        }
+    }
+
+    // runs after the screens color their buttons from the mouse interactions
+    for (_, interaction, mut color, _, focused) in button_query.iter_mut() {
+        if focused.is_some() && *interaction == Interaction::None {
+            *color = HOVERED_BUTTON.into();
+        }
+    }
+
+    let stick = actions.move_axis;
+    let just_tilted = stick != Vec2::ZERO && !*stick_tilted;
+    *stick_tilted = stick != Vec2::ZERO;
+    // the stick goes up and right with positive values
+    let stick_previous = if stick.x.abs() > stick.y.abs() {
+        stick.x < 0.0
+    } else {
+        stick.y > 0.0
+    };
+
+    let previous = actions.just_pressed(Action::MoveUp)
+        || actions.just_pressed(Action::MoveLeft)
+        || (just_tilted && stick_previous);
+    let next = actions.just_pressed(Action::MoveDown)
+        || actions.just_pressed(Action::MoveRight)
+        || (just_tilted && !stick_previous);
+    let confirm = actions.just_pressed(Action::Confirm);
+
+    if !(previous || next || confirm) {
+        return;
+    }
+
+    // the UI is laid out with y going up
+    let mut buttons: Vec<(Entity, Vec3, bool)> = button_query
+        .iter()
+        .map(|(entity, _, _, transform, focused)| {
+            (entity, transform.translation, focused.is_some())
+        })
+        .collect();
+    if buttons.is_empty() {
+        return;
+    }
+    buttons.sort_by(|(_, a, _), (_, b, _)| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+
+    let count = buttons.len();
+    let current = buttons.iter().position(|(_, _, focused)| *focused);
+    let focus = match current {
+        // the first key press only shows where the focus is
+        None => 0,
+        Some(index) if previous => (index + count - 1) % count,
+        Some(index) if next => (index + 1) % count,
+        Some(index) => {
+            if let Ok((entity, mut interaction, ..)) = button_query.get_mut(buttons[index].0) {
+                *interaction = Interaction::Clicked;
+                pressed.push(entity);
+            }
+            return;
+        }
+    };
+
+    for (index, (entity, _, _)) in buttons.into_iter().enumerate() {
+        let (_, interaction, mut color, ..) = button_query.get_mut(entity).unwrap();
+        if index == focus {
+            commands.entity(entity).insert(FocusedButton);
+            *color = HOVERED_BUTTON.into();
+        } else {
+            commands.entity(entity).remove::<FocusedButton>();
+            if *interaction == Interaction::None {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
@@ -1,8 +1,13 @@
 use bevy::{app::AppExit, prelude::*};
-use bevy_kira_audio::{Audio, AudioChannel};
+use bevy_kira_audio::Audio;
 use bevy_ninepatch::NinePatchPlugin;
 
-use crate::{assets_manager::AssetsManager, states::GameStates};
+use crate::{
+    assets_manager::AssetsManager,
+    game_input::{Action, ActionState},
+    save_game::{LoadGameEvent, SaveGameEvent},
+    states::GameStates,
+};
 
 use self::button::{UIButton, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
 
@@ -26,8 +31,15 @@ impl Plugin for PauseMenuPlugin {
                 .with_system(close_pause_menu)
                 .with_system(button_handler),
         );
+        // on pause, the settings are pushed over the menu
+        app.add_system_set(SystemSet::on_pause(GameStates::PauseMenu).with_system(destroy_menu));
+        // on resume
+        app.add_system_set(SystemSet::on_resume(GameStates::PauseMenu).with_system(ui::build_ui));
         // on exit
         app.add_system_set(SystemSet::on_exit(GameStates::PauseMenu).with_system(destroy_menu));
+
+        // every frame, after the screens handled the mouse over their buttons
+        app.add_system_to_stage(CoreStage::PostUpdate, button::button_focus_system);
     }
 }
 
@@ -39,23 +51,18 @@ fn destroy_menu(mut commands: Commands, query: Query<Entity, With<PauseMenuEntit
     info!("[PauseMenuPlugin] Exiting state");
 }
 
-fn open_pause_menu(
-    mut keyboard: ResMut<Input<KeyCode>>,
-    mut game_state: ResMut<State<GameStates>>,
-) {
-    if keyboard.just_pressed(KeyCode::Escape) {
-        game_state.push(GameStates::PauseMenu).unwrap();
-        keyboard.reset(KeyCode::Escape);
+fn open_pause_menu(mut actions: ResMut<ActionState>, mut game_state: ResMut<State<GameStates>>) {
+    if actions.just_pressed(Action::Pause) {
+        // a game over queued in the same frame takes precedence over the pause
+        let _ = game_state.push(GameStates::PauseMenu);
+        actions.reset(Action::Pause);
     }
 }
 
-fn close_pause_menu(
-    mut keyboard: ResMut<Input<KeyCode>>,
-    mut game_state: ResMut<State<GameStates>>,
-) {
-    if keyboard.just_pressed(KeyCode::Escape) {
-        game_state.pop().unwrap();
-        keyboard.reset(KeyCode::Escape);
+fn close_pause_menu(mut actions: ResMut<ActionState>, mut game_state: ResMut<State<GameStates>>) {
+    if actions.just_pressed(Action::Pause) {
+        game_state.overwrite_pop().unwrap();
+        actions.reset(Action::Pause);
     }
 }
 
@@ -63,18 +70,32 @@ pub fn button_handler(
     mut interaction_query: Query<(&Interaction, &mut UiColor, &UIButton), Changed<Interaction>>,
     mut game_state: ResMut<State<GameStates>>,
     mut exit: EventWriter<AppExit>,
+    mut save_events: EventWriter<SaveGameEvent>,
+    mut load_events: EventWriter<LoadGameEvent>,
+    audio: Res<Audio>,
     assets: Res<AssetsManager>,
 ) {
     for (interaction, mut color, button) in interaction_query.iter_mut() {
         match *interaction {
             Interaction::Clicked => {
-                let audio_channel = AudioChannel::<Audio>::default();
-                audio_channel.set_volume(5.0);
-                audio_channel.play(assets.sound_button.clone());
+                audio.play(assets.sound_button.clone());
                 *color = PRESSED_BUTTON.into();
                 match button.name.as_str() {
                     "resume" => {
-                        game_state.pop().unwrap();
+                        game_state.overwrite_pop().unwrap();
+                    }
+                    "save" => {
+                        save_events.send(SaveGameEvent);
+                    }
+                    "load" => {
+                        load_events.send(LoadGameEvent);
+                    }
+                    "settings" => {
+                        game_state.overwrite_push(GameStates::Settings).unwrap();
+                    }
+                    // replacing the whole state stack exits the game under the menu
+                    "main_menu" => {
+                        game_state.overwrite_replace(GameStates::MainMenu).unwrap();
                     }
                     "quit" => {
                         exit.send(AppExit);
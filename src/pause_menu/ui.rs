@@ -43,7 +43,7 @@ pub fn build_ui(
             justify_content: JustifyContent::Center,
             align_items: AlignItems::Center,
             flex_direction: FlexDirection::ColumnReverse,
-            size: Size::new(Val::Px(500.), Val::Px(400.)),
+            size: Size::new(Val::Px(500.), Val::Px(640.)),
             ..Default::default()
         },
         color: Color::rgba(0., 0., 0., 0.).into(),
@@ -57,7 +57,7 @@ pub fn build_ui(
             margin: Rect::all(Val::Auto),
             justify_content: JustifyContent::Center,
             align_items: AlignItems::Center,
-            size: Size::new(Val::Px(500.), Val::Px(400.)),
+            size: Size::new(Val::Px(500.), Val::Px(640.)),
             ..Default::default()
         },
         nine_patch_data: NinePatchData {
@@ -94,6 +94,18 @@ pub fn build_ui(
         font_handle.clone(),
         "resume".to_string(),
     );
+    let save_btn = UIButton::new("Save".to_string(), font_handle.clone(), "save".to_string());
+    let load_btn = UIButton::new("Load".to_string(), font_handle.clone(), "load".to_string());
+    let settings_btn = UIButton::new(
+        "Settings".to_string(),
+        font_handle.clone(),
+        "settings".to_string(),
+    );
+    let main_menu_btn = UIButton::new(
+        "Main menu".to_string(),
+        font_handle.clone(),
+        "main_menu".to_string(),
+    );
     let quit_btn = UIButton::new("Quit".to_string(), font_handle.clone(), "quit".to_string());
 
     // ---------- UI TREE CONSTRUCTION ----------//
@@ -105,6 +117,10 @@ pub fn build_ui(
             parent.spawn_bundle(container).with_children(|parent| {
                 parent.spawn_bundle(pause_title);
                 resume_btn.spawn(parent);
+                save_btn.spawn(parent);
+                load_btn.spawn(parent);
+                settings_btn.spawn(parent);
+                main_menu_btn.spawn(parent);
                 quit_btn.spawn(parent);
             });
         })
@@ -3,15 +3,18 @@ use bevy_kira_audio::AudioChannel;
 use bevy_rapier2d::prelude::*;
 
 use crate::{
-    animals::{AnimalAttributesResource, AnimalComponent},
+    animals::{AnimalAttributes, AnimalAttributesResource, AnimalComponent, AnimalStats},
     assets_manager::AssetsManager,
+    balance::GameBalance,
     camera::CameraTarget,
-    chimeras::{ChimeraPartAttributes, ChimeraPartKind},
-    constants,
+    chimeras::{ChimeraPartAttributes, ChimeraPartKind, ChimeraSlot},
+    game_input::GameInput,
     health::Health,
+    pause::GameClock,
+    schedule::{self, FixedUpdateStage},
     sound_manager::FootstepAudioChannel,
     spells::SpellKind,
-    states::GameStates,
+    states::{GameEntity, GameStates},
 };
 
 #[derive(Debug, Component)]
@@ -24,7 +27,23 @@ pub struct Player {
     pub active_spell: SpellKind,
     pub fire_projetile_cooldown: Timer,
     pub can_fire_projetile: bool,
-    pub damage_timer: Timer,
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self {
+            speed: 150.,
+            acceleration: 0.1,
+            friction: 0.2,
+            capture_distance: 200.0,
+            inventory: PlayerInventory {
+                chimera_parts: Vec::new(),
+            },
+            active_spell: SpellKind::SpawnChimera,
+            fire_projetile_cooldown: Timer::from_seconds(0.5, true),
+            can_fire_projetile: true,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -32,6 +51,23 @@ pub struct PlayerInventory {
     pub chimera_parts: Vec<ChimeraPartAttributes>,
 }
 
+impl PlayerInventory {
+    pub const CAPACITY: usize = 10;
+
+    pub fn has_room_for(&self, count: usize) -> bool {
+        self.chimera_parts.len() + count <= Self::CAPACITY
+    }
+
+    // removes parts used to build a chimera
+    pub fn remove_parts(&mut self, parts: &[ChimeraPartAttributes]) {
+        for part in parts {
+            if let Some(idx) = self.chimera_parts.iter().position(|owned| owned == part) {
+                self.chimera_parts.remove(idx);
+            }
+        }
+    }
+}
+
 #[derive(Component)]
 struct AnimationTimer(Timer);
 
@@ -43,12 +79,15 @@ impl Plugin for PlayerPlugin {
         app.add_system_set(SystemSet::on_enter(GameStates::Game).with_system(spawn_player));
 
         // on update
-        app.add_system_set(
-            SystemSet::on_update(GameStates::Game)
-                .with_system(animate_player)
-                .with_system(move_player)
-                .with_system(switch_spell_system)
-                .with_system(capture_animal),
+        app.add_system_set(SystemSet::on_update(GameStates::Game).with_system(animate_player));
+
+        // on tick
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            schedule::on_fixed_update()
+                .with_system(move_player.after("game_input"))
+                .with_system(switch_spell_system.after("game_input"))
+                .with_system(capture_animal.after("game_input")),
         );
     }
 }
@@ -63,19 +102,7 @@ fn spawn_player(
     let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(77.0, 50.0), 8, 1);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
 
-    let player = Player {
-        speed: 150.,
-        acceleration: 0.1,
-        friction: 0.2,
-        capture_distance: 200.0,
-        inventory: PlayerInventory {
-            chimera_parts: Vec::new(),
-        },
-        active_spell: SpellKind::SpawnChimera,
-        fire_projetile_cooldown: Timer::from_seconds(0.5, true),
-        can_fire_projetile: true,
-        damage_timer: Timer::from_seconds(constants::DAMAGE_RED_DURATION, true),
-    };
+    let player = Player::default();
 
     // spawn audio stopped
     step_audio.play_looped(assets.sound_footstep.clone());
@@ -98,6 +125,7 @@ fn spawn_player(
         .insert(RigidBody::Dynamic)
         .insert(Collider::cuboid(25.0, 10.0))
         .insert(LockedAxes::ROTATION_LOCKED)
+        .insert(GameEntity)
         .insert(player)
         .insert(AnimationTimer(Timer::from_seconds(0.1, true)))
         .insert(CameraTarget)
@@ -105,7 +133,7 @@ fn spawn_player(
 }
 
 fn animate_player(
-    time: Res<Time>,
+    clock: Res<GameClock>,
     texture_atlases: Res<Assets<TextureAtlas>>,
     mut query: Query<(
         &mut AnimationTimer,
@@ -117,7 +145,7 @@ fn animate_player(
     for (mut timer, mut sprite, texture_atlas_handle, velocity) in query.iter_mut() {
         // if the player moves, update the animation
         if velocity.linvel.length() > 20. {
-            timer.0.tick(time.delta());
+            timer.0.tick(clock.delta());
             if timer.0.just_finished() {
                 let texture_atlas = texture_atlases.get(texture_atlas_handle).unwrap();
                 sprite.index = (sprite.index + 1) % texture_atlas.textures.len();
@@ -132,32 +160,12 @@ fn animate_player(
 }
 
 fn move_player(
-    keyboard_input: Res<Input<KeyCode>>,
+    game_input: Res<GameInput>,
     mut query: Query<(&mut Player, &mut Velocity, &mut TextureAtlasSprite)>,
     step_audio: Res<AudioChannel<FootstepAudioChannel>>,
-    time: Res<Time>,
 ) {
-    for (mut player, mut vel, mut sprite) in query.iter_mut() {
-        if sprite.color.r() > 1.0 {
-            player.damage_timer.tick(time.delta());
-            if player.damage_timer.just_finished() {
-                sprite.color.set_r(1.0);
-            }
-        }
-        let mut input_direction = Vec2::ZERO;
-
-        if keyboard_input.pressed(KeyCode::A) || keyboard_input.pressed(KeyCode::Left) {
-            input_direction.x -= 1.0;
-        }
-        if keyboard_input.pressed(KeyCode::D) || keyboard_input.pressed(KeyCode::Right) {
-            input_direction.x += 1.0;
-        }
-
-        if keyboard_input.pressed(KeyCode::W) || keyboard_input.pressed(KeyCode::Up) {
-            input_direction.y += 1.0;
-        } else if keyboard_input.pressed(KeyCode::S) || keyboard_input.pressed(KeyCode::Down) {
-            input_direction.y -= 1.0;
-        }
+    for (player, mut vel, mut sprite) in query.iter_mut() {
+        let input_direction = game_input.move_direction;
 
         // if the player didn't move, use friction
         if input_direction == Vec2::ZERO {
@@ -166,8 +174,9 @@ fn move_player(
             // stop audio
             step_audio.pause();
         } else {
-            // normalize in order to have a maximum speed of 1 (dir.length == 1)
-            let dir_vel = input_direction.normalize() * player.speed;
+            // clamp in order to have a maximum speed of 1 (dir.length == 1), a
+            // stick tilted halfway moves at half the speed
+            let dir_vel = input_direction.clamp_length_max(1.0) * player.speed;
             vel.linvel = Vec2::lerp(vel.linvel, dir_vel, player.acceleration);
 
             // flip sprite depending on the direction
@@ -182,18 +191,17 @@ fn move_player(
 
 // capture and animal by pressing e near it
 fn capture_animal(
-    keyboard_input: Res<Input<KeyCode>>,
+    game_input: Res<GameInput>,
     mut commands: Commands,
     mut player_query: Query<(&Transform, &mut Player)>,
     animal_query: Query<(&Transform, &AnimalComponent, Entity)>,
     animal_attr_res: Res<AnimalAttributesResource>,
+    balance: Res<GameBalance>,
 ) {
-    let capture_input = keyboard_input.just_pressed(KeyCode::E);
-
-    if capture_input {
+    if game_input.capture_animal {
         for (player_transform, mut player) in player_query.iter_mut() {
             // prevent the player from capturing more than 10 parts
-            if player.inventory.chimera_parts.len() >= 10 {
+            if !player.inventory.has_room_for(1) {
                 continue;
             }
 
@@ -205,36 +213,20 @@ fn capture_animal(
                 // if player is in range of the animal, collect the animal
                 if player_pos.distance(animal_pos) < player.capture_distance {
                     // get the chimera part attributes from the animal component
-                    let animal_stats = animal.stats;
-                    let animal_attr = &animal_attr_res[&animal_stats.kind];
-                    let chimera_attr_head = ChimeraPartAttributes {
-                        attack: animal_stats.attack * constants::HEAD_ATTACK_PERCENT,
-                        range: animal_stats.range * constants::HEAD_RANGE_PERCENT,
-                        health: animal_stats.health * constants::HEAD_HEALTH_PERCENT,
-                        regen: animal_stats.regen * constants::HEAD_REGEN_PERECENT,
-                        speed: animal_stats.speed * constants::HEAD_SPEED_PERCENT,
-                        accel: animal_stats.accel * constants::HEAD_ACCEL_PERCENT,
-                        decel: animal_stats.decel * constants::HEAD_DECEL_PERCENT,
-                        collider_size: animal_attr.collider_size,
-                        texture: animal_attr.head_texture.clone(),
-                        kind: ChimeraPartKind::Head(animal_stats.kind),
-                    };
-                    let chimera_attr_tail = ChimeraPartAttributes {
-                        attack: animal_stats.attack * constants::TAIL_ATTACK_PERCENT,
-                        range: animal_stats.range * constants::TAIL_RANGE_PERCENT,
-                        health: animal_stats.health * constants::TAIL_HEALTH_PERCENT,
-                        regen: animal_stats.regen * constants::TAIL_REGEN_PERECENT,
-                        speed: animal_stats.speed * constants::TAIL_SPEED_PERCENT,
-                        accel: animal_stats.accel * constants::TAIL_ACCEL_PERCENT,
-                        decel: animal_stats.decel * constants::TAIL_DECEL_PERCENT,
-                        collider_size: animal_attr.collider_size,
-                        texture: animal_attr.tail_texture.clone(),
-                        kind: ChimeraPartKind::Tail(animal_stats.kind),
+                    let animal_stats = &animal.stats;
+                    let animal_attr = match animal_attr_res.get(&animal_stats.kind) {
+                        Some(attr) => attr,
+                        None => continue,
                     };
+                    let parts = chimera_parts(animal_stats, animal_attr, &balance);
+
+                    // the parts of an animal do not fit in a nearly full inventory
+                    if !player.inventory.has_room_for(parts.len()) {
+                        break;
+                    }
 
                     // add chimera parts to inventory
-                    player.inventory.chimera_parts.push(chimera_attr_head);
-                    player.inventory.chimera_parts.push(chimera_attr_tail);
+                    player.inventory.chimera_parts.extend(parts);
 
                     // print the attributes from the parts
                     //println!("capturing {:?}", animal.stats);
@@ -249,13 +241,8 @@ fn capture_animal(
     }
 }
 
-pub fn switch_spell_system(
-    mut player_query: Query<&mut Player>,
-    mouse_input: Res<Input<MouseButton>>,
-) {
-    let capture_input = mouse_input.just_pressed(MouseButton::Middle);
-
-    if capture_input {
+pub fn switch_spell_system(mut player_query: Query<&mut Player>, game_input: Res<GameInput>) {
+    if game_input.switch_spell {
         let mut player = player_query.iter_mut().next().unwrap();
 
         player.active_spell = match player.active_spell {
@@ -264,3 +251,33 @@ pub fn switch_spell_system(
         }
     }
 }
+
+// splits a captured animal into a head, a tail and the middle parts its
+// species has
+pub fn chimera_parts(
+    animal_stats: &AnimalStats,
+    animal_attr: &AnimalAttributes,
+    balance: &GameBalance,
+) -> Vec<ChimeraPartAttributes> {
+    ChimeraSlot::ALL
+        .into_iter()
+        .filter_map(|slot| {
+            let texture = animal_attr.part_texture(slot)?;
+            let split = balance.part_split(slot);
+
+            Some(ChimeraPartAttributes {
+                attack: animal_stats.attack * split.attack,
+                range: animal_stats.range * split.range,
+                health: animal_stats.health * split.health,
+                regen: animal_stats.regen * split.regen,
+                speed: animal_stats.speed * split.speed,
+                accel: animal_stats.accel * split.accel,
+                decel: animal_stats.decel * split.decel,
+                collider_size: animal_attr.collider_size,
+                texture: texture.clone(),
+                kind: ChimeraPartKind::new(slot, animal_stats.kind.clone()),
+                traits: animal_attr.part_traits.get(slot).to_vec(),
+            })
+        })
+        .collect()
+}
@@ -2,15 +2,56 @@ use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::{
-    health::Health,
-    states::GameStates,
-    villagers::{VillagerComponent, VillagerSprite},
+    animals::AnimalComponent,
+    chimeras::ChimeraComponent,
+    damage::{DamageEvent, DamageKind},
+    pause::GameClock,
+    player::Player,
+    states::{GameEntity, GameStates},
+    villagers::VillagerComponent,
 };
 
 #[derive(Debug, Component)]
 pub struct Projectile {
     pub despawn_timer: Timer,
     pub damage: f32,
+    // the player who cast it, or the villager who shot it
+    pub owner: Entity,
+    pub hits: ProjectileHits,
+}
+
+// Units hurt by a projectile, it goes through the others
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectileHits {
+    Villagers,
+    // the player, the chimeras and the animals
+    Ranch,
+}
+
+// spawns a projectile flying from a position, its texture is the default
+// handle when running headless
+pub fn spawn_projectile(
+    commands: &mut Commands,
+    projectile: Projectile,
+    position: Vec2,
+    velocity: Vec2,
+    texture: Handle<Image>,
+) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture,
+            transform: Transform::from_translation(position.extend(100.0)),
+            ..default()
+        })
+        .insert(Velocity {
+            linvel: velocity,
+            angvel: 0.5,
+        })
+        .insert(RigidBody::Dynamic)
+        .insert(Collider::cuboid(7.0, 7.0))
+        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(GameEntity)
+        .insert(projectile);
 }
 
 pub struct ProjectilePlugin;
@@ -26,36 +67,47 @@ impl Plugin for ProjectilePlugin {
     }
 }
 
+// units hurt by projectiles shot by the villagers
+type RanchUnits = Or<(With<Player>, With<ChimeraComponent>, With<AnimalComponent>)>;
+
 fn projectile_collision_system(
     mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
-    mut query_projectile: Query<(Entity, &mut Projectile)>,
-    mut query_villagers: Query<(Entity, &mut VillagerComponent, &mut Health, &Children)>,
-    mut villager_sprite_query: Query<&mut Sprite, With<VillagerSprite>>,
+    query_projectile: Query<&Projectile>,
+    query_villagers: Query<(), With<VillagerComponent>>,
+    query_ranch: Query<(), RanchUnits>,
+    mut damage_events: EventWriter<DamageEvent>,
 ) {
+    // a projectile touching several targets at once only hits the first one
+    let mut spent = Vec::new();
+
     for collision_event in collision_events.iter() {
-        for (projectile_entity, projectile) in query_projectile.iter_mut() {
-            for (villager_entity, mut villager, mut villager_health, children) in
-                query_villagers.iter_mut()
-            {
-                if let CollisionEvent::Started(e_1, e_2, _) = collision_event {
-                    if *e_1 == projectile_entity && *e_2 == villager_entity
-                        || *e_2 == projectile_entity && *e_1 == villager_entity
-                    {
-                        info!("projetile collide with villager");
-                        villager_health.health -= projectile.damage;
-                        commands.entity(projectile_entity).despawn();
-                        for &child in children.iter() {
-                            if let Ok(mut villager_sprite) = villager_sprite_query.get_mut(child) {
-                                villager_sprite.color.set_r(255.0);
-                                villager.damage_timer.reset();
-                            }
-                        }
-                    }
-                }
+        let (e_1, e_2) = match collision_event {
+            CollisionEvent::Started(e_1, e_2, _) => (*e_1, *e_2),
+            CollisionEvent::Stopped(..) => continue,
+        };
+
+        for (projectile_entity, target) in [(e_1, e_2), (e_2, e_1)] {
+            let projectile = match query_projectile.get(projectile_entity) {
+                Ok(projectile) if !spent.contains(&projectile_entity) => projectile,
+                _ => continue,
+            };
+            let hit = match projectile.hits {
+                ProjectileHits::Villagers => query_villagers.get(target).is_ok(),
+                ProjectileHits::Ranch => query_ranch.get(target).is_ok(),
+            };
+
+            if hit {
+                info!("projectile hit {:?}", target);
+                damage_events.send(DamageEvent {
+                    source: projectile.owner,
+                    target,
+                    amount: projectile.damage,
+                    kind: DamageKind::Projectile,
+                });
+                commands.entity(projectile_entity).despawn();
+                spent.push(projectile_entity);
             }
-            // is it intersecting with a villager?
-            // is it intersecting with a boundary?
         }
     }
 }
@@ -63,10 +115,10 @@ fn projectile_collision_system(
 fn projectile_despawn_system(
     mut commands: Commands,
     mut query_projectile: Query<(Entity, &mut Projectile, &Transform)>,
-    time: Res<Time>,
+    clock: Res<GameClock>,
 ) {
     for (projectile_entity, mut projectile, _) in query_projectile.iter_mut() {
-        projectile.despawn_timer.tick(time.delta());
+        projectile.despawn_timer.tick(clock.delta());
         if projectile.despawn_timer.just_finished() {
             commands.entity(projectile_entity).despawn();
         }
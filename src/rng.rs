@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::states::GameStates;
+
+pub struct RngPlugin {
+    // a random seed is picked when none is given
+    pub seed: Option<u64>,
+}
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        app.insert_resource(GameRng::new(seed))
+            .insert_resource(SeedSetting(self.seed));
+
+        app.add_startup_system(log_seed);
+
+        // each game gets its own streams
+        app.add_system_set(SystemSet::on_exit(GameStates::Game).with_system(reseed_system));
+    }
+}
+
+// Seed given on the command line, used again by the following games
+struct SeedSetting(Option<u64>);
+
+fn log_seed(rng: Res<GameRng>) {
+    info!(
+        "game seed: {} (replay with `--seed {}`)",
+        rng.seed(),
+        rng.seed()
+    );
+}
+
+fn reseed_system(setting: Res<SeedSetting>, mut rng: ResMut<GameRng>) {
+    *rng = GameRng::new(setting.0.unwrap_or_else(rand::random));
+    info!(
+        "next game seed: {} (replay with `--seed {}`)",
+        rng.seed(),
+        rng.seed()
+    );
+}
+
+// Independent random streams, so that a subsystem drawing more or fewer
+// numbers (e.g. no audio when running headless) does not shift the others
+#[derive(Clone, Copy, Debug)]
+pub enum RngStream {
+    // kinds, positions and delays of the spawns
+    Spawning,
+    // stat rolls of new units
+    Stats,
+    // idle durations and directions, dodge rolls
+    Behaviors,
+    // textures and animation offsets
+    Visuals,
+    // sound pitches and music choice
+    Audio,
+}
+
+impl RngStream {
+    const ALL: [RngStream; 5] = [
+        RngStream::Spawning,
+        RngStream::Stats,
+        RngStream::Behaviors,
+        RngStream::Visuals,
+        RngStream::Audio,
+    ];
+}
+
+// Source of every gameplay random number, saved along with the ranch
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameRng {
+    seed: u64,
+    streams: [ChaCha8Rng; RngStream::ALL.len()],
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        let streams = RngStream::ALL.map(|stream| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            rng.set_stream(stream as u64);
+            rng
+        });
+
+        Self { seed, streams }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut ChaCha8Rng {
+        &mut self.streams[stream as usize]
+    }
+}
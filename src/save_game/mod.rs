@@ -0,0 +1,515 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    animals::{
+        spawn_animal_entity, AnimalAttributesResource, AnimalBehavior, AnimalComponent,
+        AnimalSpawner,
+    },
+    balance::GameBalance,
+    behaviors::Facing,
+    chimeras::{
+        chimera_traits, spawn_chimera_entity, ChimeraBehavior, ChimeraComponent, ChimeraIds,
+    },
+    day_cycle::DayCycleResource,
+    death::{Corpse, KillStats},
+    health::Health,
+    inventory_parts::interaction::InventoryManagement,
+    player::Player,
+    projectile::Projectile,
+    rng::GameRng,
+    selection::SelectionMarker,
+    settings,
+    states::GameStates,
+    villagers::{
+        spawn_villager_entity, VillagerBehavior, VillagerComponent, VillagerDefinitions,
+        VillagerSpawner,
+    },
+};
+
+use self::snapshot::{
+    SaveBehavior, SaveGame, SavedAnimal, SavedBody, SavedChimera, SavedDayCycle, SavedPlayer,
+    SavedSpawners, SavedVillager,
+};
+
+mod snapshot;
+
+// the ranch is saved next to the settings
+const SAVE_FILE: &str = "ranch.save.ron";
+
+pub struct SaveGameEvent;
+pub struct LoadGameEvent;
+
+// Inserted by the main menu to load the save once the new game is set up
+pub struct ContinueGame;
+
+pub struct SaveGamePlugin;
+
+impl Plugin for SaveGamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveGameEvent>()
+            .add_event::<LoadGameEvent>();
+
+        // saving is done from the pause menu, loading also from the main menu
+        app.add_system_set(
+            SystemSet::on_update(GameStates::PauseMenu).with_system(save_game_system),
+        );
+        app.add_system_set(
+            SystemSet::on_update(GameStates::Game)
+                .with_system(continue_game_system.before("load_game")),
+        );
+        // a single load system for both states, an event is only read once
+        app.add_system(
+            load_game_system
+                .with_run_criteria(in_game_or_pause_menu)
+                .label("load_game"),
+        );
+
+        // restored units get their sprites once their children are spawned
+        app.add_system(restore_sprite_flips_system);
+    }
+}
+
+fn save_path() -> Option<PathBuf> {
+    settings::config_dir().map(|dir| dir.join(SAVE_FILE))
+}
+
+pub fn save_exists() -> bool {
+    save_path().is_some_and(|path| path.exists())
+}
+
+fn in_game_or_pause_menu(state: Res<State<GameStates>>) -> ShouldRun {
+    match state.current() {
+        GameStates::Game | GameStates::PauseMenu => ShouldRun::Yes,
+        _ => ShouldRun::No,
+    }
+}
+
+// the player is spawned when entering the game, the save moves it
+fn continue_game_system(
+    mut commands: Commands,
+    continue_game: Option<Res<ContinueGame>>,
+    player_query: Query<(), With<Player>>,
+    mut load_events: EventWriter<LoadGameEvent>,
+) {
+    if continue_game.is_some() && !player_query.is_empty() {
+        load_events.send(LoadGameEvent);
+        commands.remove_resource::<ContinueGame>();
+    }
+}
+
+// entities replaced when a save is loaded
+type SessionEntities = Or<(
+    With<ChimeraComponent>,
+    With<AnimalComponent>,
+    With<VillagerComponent>,
+    With<Projectile>,
+    With<Corpse>,
+)>;
+
+// Sprite orientations of a loaded unit, applied to its sprite children
+#[derive(Component)]
+struct RestoredSpriteFlips(Vec<bool>);
+
+// flips of the sprites of a unit, its selection marker excluded
+fn sprite_flips(
+    children: &Children,
+    sprite_query: &Query<&Sprite, Without<SelectionMarker>>,
+) -> Vec<bool> {
+    children
+        .iter()
+        .filter_map(|child| sprite_query.get(*child).ok())
+        .map(|sprite| sprite.flip_x)
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_game_system(
+    mut save_events: EventReader<SaveGameEvent>,
+    player_query: Query<(&Player, &Transform, &Velocity, &Health, &TextureAtlasSprite)>,
+    chimera_query: Query<(
+        &ChimeraComponent,
+        &Transform,
+        &Velocity,
+        &Health,
+        &Facing,
+        &Children,
+    )>,
+    animal_query: Query<(
+        &AnimalComponent,
+        &Transform,
+        &Velocity,
+        &Health,
+        &Facing,
+        &Children,
+    )>,
+    villager_query: Query<(
+        Entity,
+        &VillagerComponent,
+        &Transform,
+        &Velocity,
+        &Health,
+        &Facing,
+        &Children,
+    )>,
+    sprite_query: Query<&Sprite, Without<SelectionMarker>>,
+    day_cycle: Res<DayCycleResource>,
+    animal_spawner: Res<AnimalSpawner>,
+    villager_spawner: Res<VillagerSpawner>,
+    rng: Res<GameRng>,
+    kills: Res<KillStats>,
+    ids: Res<ChimeraIds>,
+) {
+    if save_events.iter().count() == 0 {
+        return;
+    }
+    let path = match save_path() {
+        Some(path) => path,
+        None => {
+            error!("no directory to save the ranch in");
+            return;
+        }
+    };
+
+    let (player, transform, velocity, health, sprite) = match player_query.iter().next() {
+        Some(player) => player,
+        None => return,
+    };
+
+    // attacked villagers are saved as their index in `SaveGame::villagers`
+    let villagers: Vec<Entity> = villager_query.iter().map(|(entity, ..)| entity).collect();
+
+    let body = |transform: &Transform,
+                velocity: &Velocity,
+                facing: &Facing,
+                children: &Children| SavedBody {
+        position: transform.translation.truncate(),
+        linvel: velocity.linvel,
+        sprite_flips: sprite_flips(children, &sprite_query),
+        facing_left: facing.left,
+    };
+
+    let save = SaveGame {
+        day_cycle: SavedDayCycle {
+            elapsed: day_cycle.day_timer.elapsed_secs(),
+            days_passed: day_cycle.days_passed,
+        },
+        spawners: SavedSpawners {
+            animal: (&animal_spawner.spawn_timer).into(),
+            villager: (&villager_spawner.spawn_timer).into(),
+        },
+        player: SavedPlayer {
+            position: transform.translation.truncate(),
+            linvel: velocity.linvel,
+            flip_x: sprite.flip_x,
+            health: health.into(),
+            inventory: player
+                .inventory
+                .chimera_parts
+                .iter()
+                .map(Into::into)
+                .collect(),
+            active_spell: player.active_spell.clone(),
+            fire_projectile_cooldown: (&player.fire_projetile_cooldown).into(),
+            can_fire_projectile: player.can_fire_projetile,
+        },
+        chimeras: chimera_query
+            .iter()
+            .map(
+                |(chimera, transform, velocity, health, facing, children)| SavedChimera {
+                    body: body(transform, velocity, facing, children),
+                    health: health.into(),
+                    stats: chimera.stats,
+                    behavior: chimera.behavior.save(&villagers),
+                    attack_timer: (&chimera.attack_timer).into(),
+                    taunt_timer: (&chimera.taunt_timer).into(),
+                    parts: chimera.parts.iter().map(Into::into).collect(),
+                    kills: chimera.kills,
+                    lineage: chimera.lineage,
+                    breeding_timer: (&chimera.breeding_timer).into(),
+                },
+            )
+            .collect(),
+        animals: animal_query
+            .iter()
+            .map(
+                |(animal, transform, velocity, health, facing, children)| SavedAnimal {
+                    body: body(transform, velocity, facing, children),
+                    health: health.into(),
+                    stats: animal.stats.clone(),
+                    behavior: animal.behavior.save(&villagers),
+                },
+            )
+            .collect(),
+        villagers: villager_query
+            .iter()
+            .map(
+                |(_, villager, transform, velocity, health, facing, children)| SavedVillager {
+                    body: body(transform, velocity, facing, children),
+                    health: health.into(),
+                    stats: villager.stats,
+                    behavior: villager.behavior.save(&villagers),
+                    role: villager.role.clone(),
+                    attack_timer: (&villager.attack_timer).into(),
+                    texture_variant: villager.texture_variant,
+                },
+            )
+            .collect(),
+        rng: rng.clone(),
+        kills: kills.clone(),
+        next_chimera_id: ids.next,
+    };
+
+    let serialized = match ron::ser::to_string_pretty(&save, ron::ser::PrettyConfig::default()) {
+        Ok(serialized) => serialized,
+        Err(err) => {
+            error!("could not serialize the ranch: {}", err);
+            return;
+        }
+    };
+
+    let written = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, serialized));
+    match written {
+        Ok(()) => info!("ranch saved to {}", path.display()),
+        Err(err) => error!("could not write {}: {}", path.display(), err),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn load_game_system(
+    mut commands: Commands,
+    mut load_events: EventReader<LoadGameEvent>,
+    mut player_query: Query<(
+        &mut Player,
+        &mut Transform,
+        &mut Velocity,
+        &mut Health,
+        &mut TextureAtlasSprite,
+    )>,
+    unit_query: Query<Entity, SessionEntities>,
+    animal_attr_res: Res<AnimalAttributesResource>,
+    definitions: Res<VillagerDefinitions>,
+    mut day_cycle: ResMut<DayCycleResource>,
+    mut animal_spawner: ResMut<AnimalSpawner>,
+    mut villager_spawner: ResMut<VillagerSpawner>,
+    mut inv_man: ResMut<InventoryManagement>,
+    mut rng: ResMut<GameRng>,
+    mut kills: ResMut<KillStats>,
+    mut ids: ResMut<ChimeraIds>,
+    balance: Res<GameBalance>,
+) {
+    if load_events.iter().count() == 0 {
+        return;
+    }
+    let path = match save_path() {
+        Some(path) => path,
+        None => return,
+    };
+
+    // invalid timers are rejected when parsing, the current session is kept
+    let save: SaveGame = match fs::read(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| ron::de::from_bytes(&bytes).map_err(|err| err.to_string()))
+    {
+        Ok(save) => save,
+        Err(err) => {
+            error!("could not load {}: {}", path.display(), err);
+            return;
+        }
+    };
+
+    // clear the current session
+    for entity in unit_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // world
+    day_cycle
+        .day_timer
+        .set_elapsed(Duration::from_secs_f32(save.day_cycle.elapsed));
+    day_cycle.days_passed = save.day_cycle.days_passed;
+    animal_spawner.spawn_timer = (&save.spawners.animal).into();
+    villager_spawner.spawn_timer = (&save.spawners.villager).into();
+
+    // player
+    if let Some((mut player, mut transform, mut velocity, mut health, mut sprite)) =
+        player_query.iter_mut().next()
+    {
+        let saved = &save.player;
+        transform.translation = saved.position.extend(transform.translation.z);
+        velocity.linvel = saved.linvel;
+        sprite.flip_x = saved.flip_x;
+        *health = (&saved.health).into();
+        player.inventory.chimera_parts = saved
+            .inventory
+            .iter()
+            .filter_map(|part| part.restore(&animal_attr_res))
+            .collect();
+        player.active_spell = saved.active_spell.clone();
+        player.fire_projetile_cooldown = (&saved.fire_projectile_cooldown).into();
+        player.can_fire_projetile = saved.can_fire_projectile;
+    }
+
+    // clears the selection made in the previous inventory, rebuilding its UI
+    inv_man.reset();
+    inv_man.last_inv_size = usize::MAX;
+
+    // units
+    ids.next = save.next_chimera_id;
+
+    // villagers come first, chimeras attacking them refer to their entities
+    let mut villagers = Vec::new();
+    for saved in save.villagers.iter() {
+        // villagers of a role that is no longer defined take the first one
+        let (role, attributes) = match definitions.get(&saved.role) {
+            Some(attributes) => (saved.role.clone(), attributes),
+            None => {
+                let (role, attributes) = &definitions.roles[0];
+                warn!(
+                    "unknown villager role {} in save, loading it as {}",
+                    saved.role, role
+                );
+                (role.clone(), attributes)
+            }
+        };
+
+        if saved.texture_variant as usize >= attributes.textures.len() {
+            warn!(
+                "skipping a saved {} with the unknown texture {}",
+                role, saved.texture_variant
+            );
+            continue;
+        }
+
+        // villagers saved in a state they no longer have start over
+        let behavior = VillagerBehavior::restore(&saved.behavior, &[])
+            .unwrap_or_else(|| VillagerBehavior::initial(attributes.ability));
+
+        let villager = VillagerComponent {
+            behavior,
+            stats: saved.stats,
+            attack_timer: (&saved.attack_timer).into(),
+            role,
+            ability: attributes.ability,
+            preferences: attributes.preferences,
+            texture_variant: saved.texture_variant,
+        };
+        let entity = spawn_villager_entity(
+            villager,
+            (&saved.health).into(),
+            saved.body.position,
+            attributes,
+            &mut rng,
+            &mut commands,
+        );
+        restore_body(&mut commands, entity, &saved.body);
+        villagers.push(entity);
+    }
+
+    for saved in save.chimeras.iter() {
+        let parts: Vec<_> = match saved
+            .parts
+            .iter()
+            .map(|part| part.restore(&animal_attr_res))
+            .collect()
+        {
+            Some(parts) => parts,
+            None => {
+                warn!("skipping a saved chimera made of unknown animals");
+                continue;
+            }
+        };
+
+        // without its saved target, an attacking chimera follows the player
+        let behavior = ChimeraBehavior::restore(&saved.behavior, &villagers)
+            .unwrap_or_else(|| ChimeraBehavior::follow(&balance.behaviors));
+
+        let chimera = ChimeraComponent {
+            behavior,
+            stats: saved.stats,
+            attack_timer: (&saved.attack_timer).into(),
+            taunt_timer: (&saved.taunt_timer).into(),
+            // traits follow the current data files
+            traits: chimera_traits(&parts, &balance.synergies),
+            parts,
+            kills: saved.kills,
+            lineage: saved.lineage,
+            breeding_timer: (&saved.breeding_timer).into(),
+        };
+        let entity = spawn_chimera_entity(
+            chimera,
+            (&saved.health).into(),
+            saved.body.position,
+            &mut rng,
+            &mut commands,
+        );
+        restore_body(&mut commands, entity, &saved.body);
+    }
+
+    for saved in save.animals.iter() {
+        let attributes = match animal_attr_res.get(&saved.stats.kind) {
+            Some(attributes) => attributes,
+            None => {
+                warn!("skipping a saved {} as it is not defined", saved.stats.kind);
+                continue;
+            }
+        };
+
+        let behavior = AnimalBehavior::restore(&saved.behavior, &[])
+            .unwrap_or_else(|| AnimalBehavior::idle(&balance.behaviors));
+
+        let animal = AnimalComponent {
+            behavior,
+            stats: saved.stats.clone(),
+        };
+        let entity = spawn_animal_entity(
+            animal,
+            (&saved.health).into(),
+            saved.body.position,
+            attributes,
+            &mut rng,
+            &mut commands,
+        );
+        restore_body(&mut commands, entity, &saved.body);
+    }
+
+    // restored last, the respawns above draw animation offsets from it
+    *rng = save.rng;
+    *kills = save.kills;
+
+    info!("ranch loaded from {}", path.display());
+}
+
+fn restore_body(commands: &mut Commands, entity: Entity, body: &SavedBody) {
+    commands
+        .entity(entity)
+        .insert(Velocity::linear(body.linvel))
+        .insert(Facing {
+            left: body.facing_left,
+        })
+        .insert(RestoredSpriteFlips(body.sprite_flips.clone()));
+}
+
+fn restore_sprite_flips_system(
+    mut commands: Commands,
+    unit_query: Query<(Entity, &RestoredSpriteFlips, &Children)>,
+    mut sprite_query: Query<&mut Sprite>,
+) {
+    for (entity, flips, children) in unit_query.iter() {
+        let mut flips = flips.0.iter();
+        for child in children.iter() {
+            if let Ok(mut sprite) = sprite_query.get_mut(*child) {
+                if let Some(flip_x) = flips.next() {
+                    sprite.flip_x = *flip_x;
+                }
+            }
+        }
+
+        commands.entity(entity).remove::<RestoredSpriteFlips>();
+    }
+}
@@ -0,0 +1,448 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::{
+    animals::{AnimalAttributesResource, AnimalBehavior, AnimalStats},
+    behaviors::Wander,
+    chimeras::{ChimeraBehavior, ChimeraPartAttributes, ChimeraPartKind, ChimeraStats, Lineage},
+    constants,
+    death::KillStats,
+    health::Health,
+    rng::GameRng,
+    spells::SpellKind,
+    villagers::{VillagerBehavior, VillagerRole, VillagerStats},
+};
+
+// Everything needed to rebuild a ranch session
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    pub day_cycle: SavedDayCycle,
+    pub spawners: SavedSpawners,
+    pub player: SavedPlayer,
+    pub chimeras: Vec<SavedChimera>,
+    pub animals: Vec<SavedAnimal>,
+    pub villagers: Vec<SavedVillager>,
+    // random streams, so that a loaded ranch plays out the same way
+    pub rng: GameRng,
+    pub kills: KillStats,
+    pub next_chimera_id: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedDayCycle {
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub elapsed: f32,
+    pub days_passed: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedSpawners {
+    pub animal: SavedTimer,
+    pub villager: SavedTimer,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedPlayer {
+    pub position: Vec2,
+    pub linvel: Vec2,
+    pub flip_x: bool,
+    pub health: SavedHealth,
+    pub inventory: Vec<SavedPart>,
+    pub active_spell: SpellKind,
+    pub fire_projectile_cooldown: SavedTimer,
+    pub can_fire_projectile: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedChimera {
+    pub body: SavedBody,
+    pub health: SavedHealth,
+    pub stats: ChimeraStats,
+    pub behavior: SavedBehavior,
+    pub attack_timer: SavedTimer,
+    pub taunt_timer: SavedTimer,
+    #[serde(deserialize_with = "deserialize_chimera_parts")]
+    pub parts: Vec<SavedPart>,
+    pub kills: u32,
+    pub lineage: Lineage,
+    pub breeding_timer: SavedTimer,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedAnimal {
+    pub body: SavedBody,
+    pub health: SavedHealth,
+    pub stats: AnimalStats,
+    pub behavior: SavedBehavior,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedVillager {
+    pub body: SavedBody,
+    pub health: SavedHealth,
+    pub stats: VillagerStats,
+    pub behavior: SavedBehavior,
+    pub role: VillagerRole,
+    pub attack_timer: SavedTimer,
+    pub texture_variant: u8,
+}
+
+// Physical state shared by every unit, the sprite flips are stored in the
+// order of the sprite children
+#[derive(Serialize, Deserialize)]
+pub struct SavedBody {
+    pub position: Vec2,
+    pub linvel: Vec2,
+    pub sprite_flips: Vec<bool>,
+    pub facing_left: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedTimer {
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub duration: f32,
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub elapsed: f32,
+    pub repeating: bool,
+}
+
+impl From<&Timer> for SavedTimer {
+    fn from(timer: &Timer) -> Self {
+        Self {
+            duration: timer.duration().as_secs_f32(),
+            elapsed: timer.elapsed_secs(),
+            repeating: timer.repeating(),
+        }
+    }
+}
+
+impl From<&SavedTimer> for Timer {
+    fn from(saved: &SavedTimer) -> Self {
+        let mut timer = Timer::new(Duration::from_secs_f32(saved.duration), saved.repeating);
+        timer.set_elapsed(Duration::from_secs_f32(saved.elapsed));
+        timer
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedHealth {
+    pub max_health: f32,
+    pub health: f32,
+    pub regen: f32,
+    pub regen_timer: SavedTimer,
+}
+
+impl From<&Health> for SavedHealth {
+    fn from(health: &Health) -> Self {
+        Self {
+            max_health: health.max_health,
+            health: health.health,
+            regen: health.regen,
+            regen_timer: (&health.regen_timer).into(),
+        }
+    }
+}
+
+impl From<&SavedHealth> for Health {
+    fn from(saved: &SavedHealth) -> Self {
+        Self {
+            max_health: saved.max_health,
+            health: saved.health,
+            regen: saved.regen,
+            regen_timer: (&saved.regen_timer).into(),
+        }
+    }
+}
+
+// States of the units with serializable timers, each unit type restores the
+// ones it supports
+#[derive(Serialize, Deserialize)]
+pub enum SavedBehavior {
+    Idle {
+        timer: SavedTimer,
+        #[serde(deserialize_with = "deserialize_duration")]
+        base_duration: f32,
+        #[serde(deserialize_with = "deserialize_seconds")]
+        duration_spread: f32,
+        direction: Vec2,
+        is_moving: bool,
+    },
+    Pursue {
+        target: Option<Vec2>,
+    },
+    Follow {
+        target: Option<Vec2>,
+        distance: f32,
+    },
+    RunAway {
+        target: Option<Vec2>,
+    },
+    Skirmish {
+        target: Option<Vec2>,
+    },
+    Support {
+        target: Option<Vec2>,
+    },
+    Herd {
+        target: Option<Vec2>,
+    },
+    Retreat {
+        target: Option<Vec2>,
+        // order given back to the chimera once it recovered
+        resume: Option<Box<SavedBehavior>>,
+    },
+    Stay {
+        position: Vec2,
+    },
+    Guard {
+        point: Vec2,
+    },
+    // index of the attacked villager in `SaveGame::villagers`, None when it was
+    // not saved
+    Attack {
+        villager: Option<usize>,
+    },
+}
+
+impl From<&Wander> for SavedBehavior {
+    fn from(wander: &Wander) -> Self {
+        SavedBehavior::Idle {
+            timer: (&wander.timer).into(),
+            base_duration: wander.base_duration,
+            duration_spread: wander.duration_spread,
+            direction: wander.direction,
+            is_moving: wander.is_moving,
+        }
+    }
+}
+
+impl SavedBehavior {
+    fn wander(&self) -> Option<Wander> {
+        match self {
+            // a spread past the base duration would draw negative durations
+            SavedBehavior::Idle {
+                timer,
+                base_duration,
+                duration_spread,
+                direction,
+                is_moving,
+            } if duration_spread < base_duration => Some(Wander {
+                timer: timer.into(),
+                base_duration: *base_duration,
+                duration_spread: *duration_spread,
+                direction: *direction,
+                is_moving: *is_moving,
+            }),
+            _ => None,
+        }
+    }
+}
+
+// Behavior of a unit type, saved as a `SavedBehavior`
+pub trait SaveBehavior: Sized {
+    // `villagers` are the saved villagers, in the order of `SaveGame::villagers`
+    fn save(&self, villagers: &[Entity]) -> SavedBehavior;
+
+    // `villagers` are the loaded villagers, in the order of `SaveGame::villagers`.
+    // Fails for a state the unit type does not have, or an attack on a
+    // villager that was not saved
+    fn restore(saved: &SavedBehavior, villagers: &[Entity]) -> Option<Self>;
+}
+
+impl SaveBehavior for AnimalBehavior {
+    fn save(&self, _villagers: &[Entity]) -> SavedBehavior {
+        match self {
+            AnimalBehavior::Idle(wander) => wander.into(),
+            AnimalBehavior::Herd { target } => SavedBehavior::Herd { target: *target },
+            AnimalBehavior::RunAway { target } => SavedBehavior::RunAway { target: *target },
+        }
+    }
+
+    fn restore(saved: &SavedBehavior, _villagers: &[Entity]) -> Option<Self> {
+        let behavior = match saved {
+            SavedBehavior::Idle { .. } => AnimalBehavior::Idle(saved.wander()?),
+            SavedBehavior::Herd { target } => AnimalBehavior::Herd { target: *target },
+            SavedBehavior::RunAway { target } => AnimalBehavior::RunAway { target: *target },
+            _ => return None,
+        };
+
+        Some(behavior)
+    }
+}
+
+impl SaveBehavior for VillagerBehavior {
+    fn save(&self, _villagers: &[Entity]) -> SavedBehavior {
+        match self {
+            VillagerBehavior::Pursue { target } => SavedBehavior::Pursue { target: *target },
+            VillagerBehavior::Skirmish { target } => SavedBehavior::Skirmish { target: *target },
+            VillagerBehavior::Support { target } => SavedBehavior::Support { target: *target },
+            VillagerBehavior::RunAway { target } => SavedBehavior::RunAway { target: *target },
+        }
+    }
+
+    fn restore(saved: &SavedBehavior, _villagers: &[Entity]) -> Option<Self> {
+        let behavior = match saved {
+            SavedBehavior::Pursue { target } => VillagerBehavior::Pursue { target: *target },
+            SavedBehavior::Skirmish { target } => VillagerBehavior::Skirmish { target: *target },
+            SavedBehavior::Support { target } => VillagerBehavior::Support { target: *target },
+            SavedBehavior::RunAway { target } => VillagerBehavior::RunAway { target: *target },
+            _ => return None,
+        };
+
+        Some(behavior)
+    }
+}
+
+impl SaveBehavior for ChimeraBehavior {
+    fn save(&self, villagers: &[Entity]) -> SavedBehavior {
+        match self {
+            ChimeraBehavior::Idle(wander) => wander.into(),
+            ChimeraBehavior::Pursue { target } => SavedBehavior::Pursue { target: *target },
+            ChimeraBehavior::Follow { target, distance } => SavedBehavior::Follow {
+                target: *target,
+                distance: *distance,
+            },
+            ChimeraBehavior::Retreat { target, resume } => SavedBehavior::Retreat {
+                target: *target,
+                resume: resume.as_ref().map(|order| Box::new(order.save(villagers))),
+            },
+            ChimeraBehavior::Stay { position } => SavedBehavior::Stay {
+                position: *position,
+            },
+            ChimeraBehavior::Guard { point } => SavedBehavior::Guard { point: *point },
+            ChimeraBehavior::Attack { target } => SavedBehavior::Attack {
+                villager: villagers.iter().position(|villager| villager == target),
+            },
+        }
+    }
+
+    fn restore(saved: &SavedBehavior, villagers: &[Entity]) -> Option<Self> {
+        let behavior = match saved {
+            SavedBehavior::Idle { .. } => ChimeraBehavior::Idle(saved.wander()?),
+            SavedBehavior::Pursue { target } => ChimeraBehavior::Pursue { target: *target },
+            SavedBehavior::Follow { target, distance } => ChimeraBehavior::Follow {
+                target: *target,
+                distance: *distance,
+            },
+            // an attack on a villager that was not saved is dropped
+            SavedBehavior::Retreat { target, resume } => ChimeraBehavior::Retreat {
+                target: *target,
+                resume: resume
+                    .as_deref()
+                    .and_then(|order| Self::restore(order, villagers))
+                    .map(Box::new),
+            },
+            SavedBehavior::Stay { position } => ChimeraBehavior::Stay {
+                position: *position,
+            },
+            SavedBehavior::Guard { point } => ChimeraBehavior::Guard { point: *point },
+            SavedBehavior::Attack { villager } => ChimeraBehavior::Attack {
+                target: *villagers.get((*villager)?)?,
+            },
+            SavedBehavior::RunAway { .. }
+            | SavedBehavior::Herd { .. }
+            | SavedBehavior::Skirmish { .. }
+            | SavedBehavior::Support { .. } => return None,
+        };
+
+        Some(behavior)
+    }
+}
+
+// Chimera part without its texture, which is found back from the part kind
+#[derive(Serialize, Deserialize)]
+pub struct SavedPart {
+    pub kind: ChimeraPartKind,
+    pub speed: f32,
+    pub accel: f32,
+    pub decel: f32,
+    pub health: f32,
+    pub attack: f32,
+    pub regen: f32,
+    pub range: f32,
+    pub collider_size: Vec2,
+}
+
+impl From<&ChimeraPartAttributes> for SavedPart {
+    fn from(part: &ChimeraPartAttributes) -> Self {
+        Self {
+            kind: part.kind.clone(),
+            speed: part.speed,
+            accel: part.accel,
+            decel: part.decel,
+            health: part.health,
+            attack: part.attack,
+            regen: part.regen,
+            range: part.range,
+            collider_size: part.collider_size,
+        }
+    }
+}
+
+impl SavedPart {
+    // rebuilds the part, fails if its animal kind is not defined anymore or does
+    // not give this part
+    pub fn restore(
+        &self,
+        animal_attr_res: &AnimalAttributesResource,
+    ) -> Option<ChimeraPartAttributes> {
+        let animal_attr = animal_attr_res.get(self.kind.animal())?;
+        let texture = animal_attr.part_texture(self.kind.slot())?.clone();
+
+        Some(ChimeraPartAttributes {
+            speed: self.speed,
+            accel: self.accel,
+            decel: self.decel,
+            health: self.health,
+            attack: self.attack,
+            regen: self.regen,
+            range: self.range,
+            collider_size: self.collider_size,
+            texture,
+            kind: self.kind.clone(),
+            traits: animal_attr.part_traits.get(self.kind.slot()).to_vec(),
+        })
+    }
+}
+
+// elapsed times are turned into `Duration`s, which cannot be negative
+fn deserialize_seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let seconds = f32::deserialize(deserializer)?;
+    if !seconds.is_finite() || seconds < 0.0 {
+        return Err(de::Error::custom(format!(
+            "expected a positive number of seconds, got {}",
+            seconds
+        )));
+    }
+    Ok(seconds)
+}
+
+// repeating timers divide their elapsed time by their duration
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let seconds = f32::deserialize(deserializer)?;
+    if !seconds.is_finite() || seconds <= 0.0 {
+        return Err(de::Error::custom(format!(
+            "expected a strictly positive duration, got {}",
+            seconds
+        )));
+    }
+    Ok(seconds)
+}
+
+// chimeras are built from 2 to 4 parts
+fn deserialize_chimera_parts<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<SavedPart>, D::Error> {
+    let parts = Vec::<SavedPart>::deserialize(deserializer)?;
+    if !(constants::CHIMERA_MIN_PARTS..=constants::CHIMERA_MAX_PARTS).contains(&parts.len()) {
+        return Err(de::Error::custom(format!(
+            "expected {} to {} chimera parts, got {}",
+            constants::CHIMERA_MIN_PARTS,
+            constants::CHIMERA_MAX_PARTS,
+            parts.len()
+        )));
+    }
+    Ok(parts)
+}
@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use bevy::{core::FixedTimestep, ecs::schedule::ShouldRun, prelude::*};
+use bevy_rapier2d::prelude::*;
+
+use crate::states::GameStates;
+
+// Gameplay ticks per second. Unit accelerations, decelerations and the player
+// friction are applied once per tick, their values are tuned for this rate
+pub const TICK_RATE: f64 = 60.0;
+
+// Stage of the gameplay simulation, run TICK_RATE times per second whatever the
+// frame rate. Rendering and animations stay in the per frame stages
+#[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+pub struct FixedUpdateStage;
+
+pub struct FixedUpdatePlugin {
+    // runs one tick per frame instead of following the clock, so that headless
+    // runs go as fast as they can
+    pub tick_every_frame: bool,
+}
+
+// Present when a tick is run on every frame, the game clock then advances by a
+// tick per frame instead of following `Time`
+pub struct TickEveryFrame;
+
+// Rapier stages, run once at the end of each tick so that the bodies move by
+// exactly one tick with the velocities set during it
+struct PhysicsSchedule(Schedule);
+
+impl Plugin for FixedUpdatePlugin {
+    fn build(&self, app: &mut App) {
+        // must be added after the physics plugin, set up without its own stages
+        app.world
+            .get_resource_mut::<RapierConfiguration>()
+            .expect("the physics plugin should be added before the fixed update plugin")
+            .timestep_mode = TimestepMode::Fixed {
+            dt: (1.0 / TICK_RATE) as f32,
+            substeps: 1,
+        };
+
+        let mut physics = Schedule::default();
+        for stage in [
+            PhysicsStages::SyncBackend,
+            PhysicsStages::StepSimulation,
+            PhysicsStages::Writeback,
+        ] {
+            physics.add_stage(
+                stage.clone(),
+                SystemStage::parallel()
+                    .with_system_set(RapierPhysicsPlugin::<NoUserData>::get_systems(stage)),
+            );
+        }
+        app.insert_resource(PhysicsSchedule(physics));
+
+        let mut stage =
+            SystemStage::parallel().with_system(step_physics.exclusive_system().at_end());
+        if self.tick_every_frame {
+            app.insert_resource(TickEveryFrame);
+        } else {
+            stage = stage.with_run_criteria(FixedTimestep::steps_per_second(TICK_RATE));
+        }
+        app.add_stage_after(CoreStage::Update, FixedUpdateStage, stage);
+        // removed bodies are also detected at the end of the frame, like the
+        // physics plugin does
+        app.add_stage_before(
+            CoreStage::Last,
+            PhysicsStages::DetectDespawn,
+            SystemStage::parallel().with_system_set(
+                RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsStages::DetectDespawn),
+            ),
+        );
+    }
+}
+
+// after the systems of the tick and their commands, paused or not. A paused
+// game stops the physics pipeline instead
+fn step_physics(world: &mut World) {
+    world.resource_scope(|world, mut physics: Mut<PhysicsSchedule>| {
+        physics.0.run(world);
+    });
+}
+
+// duration of a gameplay tick, to be used instead of `Time` in the fixed stage
+pub fn tick_delta() -> Duration {
+    Duration::from_secs_f64(1.0 / TICK_RATE)
+}
+
+// Systems run on each tick while the game is not paused nor over, added with
+// `add_system_set_to_stage(FixedUpdateStage, ...)`
+pub fn on_fixed_update() -> SystemSet {
+    SystemSet::new().with_run_criteria(in_game)
+}
+
+// state transitions are applied by the Update stage, the fixed stage only
+// checks the current state
+fn in_game(state: Res<State<GameStates>>) -> ShouldRun {
+    if *state.current() == GameStates::Game {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
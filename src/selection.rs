@@ -0,0 +1,266 @@
+use bevy::prelude::*;
+use bevy_rapier2d::{plugin::RapierContext, prelude::InteractionGroups};
+
+use crate::{
+    animals::AnimalComponent,
+    assets_manager::AssetsManager,
+    chimeras::ChimeraComponent,
+    constants,
+    game_input::{Action, ActionState, PointerOverUi, WorldCursor},
+    states::{GameEntity, GameStates},
+};
+
+// a drag shorter than this is a click
+const DRAG_THRESHOLD: f32 = 5.0;
+
+// control groups are assigned with ctrl and recalled with these keys
+const GROUP_KEYS: [KeyCode; 9] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>();
+
+        // on enter
+        app.add_system_set(SystemSet::on_enter(GameStates::Game).with_system(setup_selection_box));
+
+        // on update
+        app.add_system_set(
+            SystemSet::on_update(GameStates::Game)
+                .with_system(prune_selection_system.label("prune_selection"))
+                .with_system(mouse_selection_system.after("prune_selection"))
+                .with_system(control_groups_system.after("prune_selection"))
+                .with_system(selection_markers_system.after("prune_selection")),
+        );
+
+        // on exit
+        app.add_system_set(SystemSet::on_exit(GameStates::Game).with_system(reset_selection));
+    }
+}
+
+// Units picked by the player, either a single animal or any number of chimeras
+#[derive(Default)]
+pub struct Selection {
+    pub units: Vec<Entity>,
+    // chimeras assigned to each number key
+    pub groups: [Vec<Entity>; 9],
+    // screen position where the current drag started
+    drag_start: Option<Vec2>,
+}
+
+// units that can be selected
+type SelectableUnits = Or<(With<ChimeraComponent>, With<AnimalComponent>)>;
+
+// Sprite shown on each selected unit
+#[derive(Component)]
+pub struct SelectionMarker;
+
+// Rectangle drawn while dragging the mouse
+#[derive(Component)]
+struct SelectionBox;
+
+// the next game starts without selection nor control groups
+fn reset_selection(mut selection: ResMut<Selection>) {
+    *selection = Selection::default();
+}
+
+fn setup_selection_box(mut commands: Commands) {
+    commands
+        .spawn_bundle(NodeBundle {
+            transform: Transform::from_xyz(0., 0., constants::Z_UI),
+            style: Style {
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                ..default()
+            },
+            color: Color::rgba(0.4, 0.9, 0.4, 0.25).into(),
+            ..default()
+        })
+        .insert(SelectionBox)
+        .insert(GameEntity);
+}
+
+// dead and captured units leave the selection and the control groups
+fn prune_selection_system(
+    mut selection: ResMut<Selection>,
+    unit_query: Query<(), SelectableUnits>,
+) {
+    let alive = |entity: &Entity| unit_query.get(*entity).is_ok();
+
+    selection.units.retain(alive);
+    for group in selection.groups.iter_mut() {
+        group.retain(alive);
+    }
+}
+
+// inspecting (a right click) selects the unit under the cursor, a drag the
+// chimeras in the box. Shift adds them to the current selection
+#[allow(clippy::too_many_arguments)]
+fn mouse_selection_system(
+    actions: Res<ActionState>,
+    keyboard_input: Res<Input<KeyCode>>,
+    world_cursor: Res<WorldCursor>,
+    pointer_over_ui: Res<PointerOverUi>,
+    rapier_context: Res<RapierContext>,
+    chimera_query: Query<(Entity, &Transform), With<ChimeraComponent>>,
+    unit_query: Query<Entity, SelectableUnits>,
+    mut box_query: Query<&mut Style, With<SelectionBox>>,
+    mut selection: ResMut<Selection>,
+) {
+    let cursor = match actions.cursor {
+        Some(cursor) => cursor,
+        None => return,
+    };
+
+    // clicks on the UI do not start a selection
+    if actions.just_pressed(Action::Inspect) && !pointer_over_ui.0 {
+        selection.drag_start = Some(cursor);
+    }
+    let start = match selection.drag_start {
+        Some(start) => start,
+        None => return,
+    };
+    let dragging = start.distance(cursor) > DRAG_THRESHOLD;
+
+    // the box follows the cursor while dragging
+    if let Ok(mut style) = box_query.get_single_mut() {
+        let (min, size) = (start.min(cursor), (start - cursor).abs());
+
+        style.display = if dragging && actions.pressed(Action::Inspect) {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        style.position = Rect {
+            left: Val::Px(min.x),
+            bottom: Val::Px(min.y),
+            ..default()
+        };
+        style.size = Size::new(Val::Px(size.x), Val::Px(size.y));
+    }
+
+    if !actions.just_released(Action::Inspect) {
+        return;
+    }
+    selection.drag_start = None;
+
+    let to_world = |screen_pos| world_cursor.to_world(screen_pos);
+    let picked: Vec<Entity> = if dragging {
+        let (corner_a, corner_b) = (to_world(start), to_world(cursor));
+        let (min, max) = (corner_a.min(corner_b), corner_a.max(corner_b));
+
+        chimera_query
+            .iter()
+            .filter(|(_, transform)| {
+                let position = transform.translation.truncate();
+                position.cmpge(min).all() && position.cmple(max).all()
+            })
+            .map(|(entity, _)| entity)
+            .collect()
+    } else {
+        let mut picked = Vec::new();
+        rapier_context.intersections_with_point(
+            to_world(cursor),
+            InteractionGroups::all(),
+            None,
+            |entity| {
+                if unit_query.get(entity).is_ok() {
+                    picked.push(entity);
+                    // stop searching
+                    return false;
+                }
+                true
+            },
+        );
+        picked
+    };
+
+    // animals are only selected alone
+    let only_chimeras = |units: &[Entity]| {
+        units
+            .iter()
+            .all(|entity| chimera_query.get(*entity).is_ok())
+    };
+    let adding = keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift);
+
+    if adding && only_chimeras(&picked) && only_chimeras(&selection.units) {
+        for entity in picked {
+            if !selection.units.contains(&entity) {
+                selection.units.push(entity);
+            }
+        }
+    } else {
+        selection.units = picked;
+    }
+}
+
+fn control_groups_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    chimera_query: Query<(), With<ChimeraComponent>>,
+    mut selection: ResMut<Selection>,
+) {
+    let assigning =
+        keyboard_input.pressed(KeyCode::LControl) || keyboard_input.pressed(KeyCode::RControl);
+
+    for (idx, key) in GROUP_KEYS.iter().enumerate() {
+        if !keyboard_input.just_pressed(*key) {
+            continue;
+        }
+
+        if assigning {
+            let chimeras: Vec<Entity> = selection
+                .units
+                .iter()
+                .copied()
+                .filter(|entity| chimera_query.get(*entity).is_ok())
+                .collect();
+            info!(
+                "control group {} set to {} chimera(s)",
+                idx + 1,
+                chimeras.len()
+            );
+            selection.groups[idx] = chimeras;
+        } else {
+            selection.units = selection.groups[idx].clone();
+        }
+    }
+}
+
+fn selection_markers_system(
+    mut commands: Commands,
+    selection: Res<Selection>,
+    marker_query: Query<(Entity, &Parent), With<SelectionMarker>>,
+    assets: Res<AssetsManager>,
+) {
+    for (marker, parent) in marker_query.iter() {
+        if !selection.units.contains(&parent.0) {
+            commands.entity(marker).despawn_recursive();
+        }
+    }
+
+    for unit in selection.units.iter() {
+        if marker_query.iter().any(|(_, parent)| parent.0 == *unit) {
+            continue;
+        }
+
+        commands.entity(*unit).with_children(|parent| {
+            parent
+                .spawn_bundle(SpriteBundle {
+                    texture: assets.texture_target.clone(),
+                    ..default()
+                })
+                .insert(SelectionMarker);
+        });
+    }
+}
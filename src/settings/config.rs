@@ -0,0 +1,335 @@
+use std::{env, fs, io, path::PathBuf};
+
+use bevy::{prelude::*, window::WindowMode};
+use serde::{Deserialize, Serialize};
+
+use crate::game_input::Bindings;
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+// resolutions of the window, the menus are laid out for 1280x720
+const RESOLUTIONS: [(u32, u32); 4] = [(1280, 720), (1366, 768), (1600, 900), (1920, 1080)];
+const UI_SCALES: [f32; 5] = [0.75, 1.0, 1.25, 1.5, 2.0];
+const MIN_UI_SIZE: (f32, f32) = (1280., 720.);
+
+// User settings, kept in the user's config directory and saved when leaving
+// the settings screen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // volumes from 0 to 1, music and sound effects are scaled by the master one
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub window_mode: WindowModeSetting,
+    pub resolution: (u32, u32),
+    // share of the distance to its target the camera moves every frame
+    pub camera_smooth_speed: f32,
+    // how far towards the cursor the camera looks, from the player
+    pub camera_target_to_mouse_percent: f32,
+    pub ui_scale: f32,
+    // keys, mouse and gamepad buttons of the actions, edited in the controls screen
+    pub bindings: Bindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 0.5,
+            sfx_volume: 1.0,
+            window_mode: WindowModeSetting::Windowed,
+            resolution: RESOLUTIONS[0],
+            camera_smooth_speed: 0.1,
+            camera_target_to_mouse_percent: 0.1,
+            ui_scale: 1.0,
+            bindings: Bindings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowModeSetting {
+    Windowed,
+    BorderlessFullscreen,
+    Fullscreen,
+}
+
+impl WindowModeSetting {
+    pub fn window_mode(self) -> WindowMode {
+        match self {
+            WindowModeSetting::Windowed => WindowMode::Windowed,
+            WindowModeSetting::BorderlessFullscreen => WindowMode::BorderlessFullscreen,
+            WindowModeSetting::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+// A line of the settings screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingKind {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    WindowMode,
+    Resolution,
+    CameraSmoothSpeed,
+    CameraTargetToMouse,
+    UiScale,
+}
+
+impl SettingKind {
+    pub const ALL: [SettingKind; 8] = [
+        SettingKind::MasterVolume,
+        SettingKind::MusicVolume,
+        SettingKind::SfxVolume,
+        SettingKind::WindowMode,
+        SettingKind::Resolution,
+        SettingKind::CameraSmoothSpeed,
+        SettingKind::CameraTargetToMouse,
+        SettingKind::UiScale,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingKind::MasterVolume => "Master volume",
+            SettingKind::MusicVolume => "Music volume",
+            SettingKind::SfxVolume => "Effects volume",
+            SettingKind::WindowMode => "Window mode",
+            SettingKind::Resolution => "Resolution",
+            SettingKind::CameraSmoothSpeed => "Camera speed",
+            SettingKind::CameraTargetToMouse => "Look ahead",
+            SettingKind::UiScale => "UI scale",
+        }
+    }
+
+    // used in the names of the buttons
+    pub fn name(self) -> &'static str {
+        match self {
+            SettingKind::MasterVolume => "master_volume",
+            SettingKind::MusicVolume => "music_volume",
+            SettingKind::SfxVolume => "sfx_volume",
+            SettingKind::WindowMode => "window_mode",
+            SettingKind::Resolution => "resolution",
+            SettingKind::CameraSmoothSpeed => "camera_smooth_speed",
+            SettingKind::CameraTargetToMouse => "camera_target_to_mouse_percent",
+            SettingKind::UiScale => "ui_scale",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SettingKind> {
+        SettingKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+    }
+}
+
+// moves a value by a step, within its bounds
+fn step_value(value: f32, step: f32, min: f32, max: f32, up: bool) -> f32 {
+    let value = if up { value + step } else { value - step };
+    // rounded to the step, repeated additions drift
+    ((value / step).round() * step).clamp(min, max)
+}
+
+// the next or the previous of the options, staying on the first or last one
+fn step_option<T: Copy + PartialEq>(options: &[T], value: T, up: bool) -> T {
+    let index = options.iter().position(|option| *option == value);
+    let index = match index {
+        Some(index) if up => (index + 1).min(options.len() - 1),
+        Some(index) => index.saturating_sub(1),
+        None => 0,
+    };
+    options[index]
+}
+
+// a loaded value within its bounds, the default when it is not a number
+fn clamp_value(value: f32, default: f32, min: f32, max: f32) -> f32 {
+    if value.is_nan() {
+        default
+    } else {
+        value.clamp(min, max)
+    }
+}
+
+// the option closest to a loaded value
+fn nearest_option<T: Copy>(options: &[T], distance: impl Fn(T) -> f32) -> T {
+    options
+        .iter()
+        .copied()
+        .min_by(|a, b| distance(*a).total_cmp(&distance(*b)))
+        .unwrap()
+}
+
+impl Settings {
+    pub fn step(&mut self, kind: SettingKind, up: bool) {
+        match kind {
+            SettingKind::MasterVolume => {
+                self.master_volume = step_value(self.master_volume, 0.1, 0.0, 1.0, up);
+            }
+            SettingKind::MusicVolume => {
+                self.music_volume = step_value(self.music_volume, 0.1, 0.0, 1.0, up);
+            }
+            SettingKind::SfxVolume => {
+                self.sfx_volume = step_value(self.sfx_volume, 0.1, 0.0, 1.0, up);
+            }
+            SettingKind::WindowMode => {
+                let modes = [
+                    WindowModeSetting::Windowed,
+                    WindowModeSetting::BorderlessFullscreen,
+                    WindowModeSetting::Fullscreen,
+                ];
+                self.window_mode = step_option(&modes, self.window_mode, up);
+            }
+            SettingKind::Resolution => {
+                self.resolution = step_option(&RESOLUTIONS, self.resolution, up);
+            }
+            SettingKind::CameraSmoothSpeed => {
+                self.camera_smooth_speed =
+                    step_value(self.camera_smooth_speed, 0.05, 0.05, 1.0, up);
+            }
+            SettingKind::CameraTargetToMouse => {
+                self.camera_target_to_mouse_percent =
+                    step_value(self.camera_target_to_mouse_percent, 0.05, 0.0, 0.5, up);
+            }
+            SettingKind::UiScale => {
+                self.ui_scale = step_option(&UI_SCALES, self.ui_scale, up);
+            }
+        }
+    }
+
+    pub fn display(&self, kind: SettingKind) -> String {
+        let percent = |value: f32| format!("{:.0}%", value * 100.);
+        match kind {
+            SettingKind::MasterVolume => percent(self.master_volume),
+            SettingKind::MusicVolume => percent(self.music_volume),
+            SettingKind::SfxVolume => percent(self.sfx_volume),
+            SettingKind::WindowMode => match self.window_mode {
+                WindowModeSetting::Windowed => "Windowed".to_string(),
+                WindowModeSetting::BorderlessFullscreen => "Borderless".to_string(),
+                WindowModeSetting::Fullscreen => "Fullscreen".to_string(),
+            },
+            SettingKind::Resolution => format!("{}x{}", self.resolution.0, self.resolution.1),
+            SettingKind::CameraSmoothSpeed => percent(self.camera_smooth_speed),
+            SettingKind::CameraTargetToMouse => percent(self.camera_target_to_mouse_percent),
+            SettingKind::UiScale => percent(self.ui_scale),
+        }
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        self.master_volume * self.sfx_volume
+    }
+
+    pub fn resolution(&self) -> Vec2 {
+        Vec2::new(self.resolution.0 as f32, self.resolution.1 as f32)
+    }
+
+    // the UI is scaled down when it would not fit in the window anymore
+    pub fn effective_ui_scale(&self) -> f32 {
+        let fit = self.resolution() / Vec2::from(MIN_UI_SIZE);
+        self.ui_scale.min(fit.x).min(fit.y)
+    }
+
+    // keeps hand edited values within what the settings screen allows
+    fn sanitize(&mut self) {
+        let default = Settings::default();
+        self.master_volume = clamp_value(self.master_volume, default.master_volume, 0.0, 1.0);
+        self.music_volume = clamp_value(self.music_volume, default.music_volume, 0.0, 1.0);
+        self.sfx_volume = clamp_value(self.sfx_volume, default.sfx_volume, 0.0, 1.0);
+        self.camera_smooth_speed = clamp_value(
+            self.camera_smooth_speed,
+            default.camera_smooth_speed,
+            0.05,
+            1.0,
+        );
+        self.camera_target_to_mouse_percent = clamp_value(
+            self.camera_target_to_mouse_percent,
+            default.camera_target_to_mouse_percent,
+            0.0,
+            0.5,
+        );
+
+        let (width, height) = self.resolution;
+        self.resolution = nearest_option(&RESOLUTIONS, |(w, h)| {
+            (w as f32 - width as f32).abs() + (h as f32 - height as f32).abs()
+        });
+        let ui_scale = clamp_value(self.ui_scale, default.ui_scale, 0.0, f32::MAX);
+        self.ui_scale = nearest_option(&UI_SCALES, |scale| (scale - ui_scale).abs());
+    }
+
+    // falls back to the defaults when the file is missing or invalid. Loaded
+    // before the log plugin is installed, the errors are printed to stderr
+    pub fn load() -> Settings {
+        let path = match settings_path() {
+            Some(path) => path,
+            None => return Settings::default(),
+        };
+
+        match fs::read(&path) {
+            Ok(bytes) => match ron::de::from_bytes::<Settings>(&bytes) {
+                Ok(mut settings) => {
+                    settings.sanitize();
+                    settings.bindings.add_missing();
+                    settings
+                }
+                Err(err) => {
+                    eprintln!("invalid settings in {}: {}", path.display(), err);
+                    Settings::default()
+                }
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Settings::default(),
+            Err(err) => {
+                eprintln!("could not read {}: {}", path.display(), err);
+                Settings::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let path = match settings_path() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let serialized = match ron::ser::to_string_pretty(self, Default::default()) {
+            Ok(serialized) => serialized,
+            Err(err) => {
+                error!("could not serialize the settings: {}", err);
+                return;
+            }
+        };
+
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, serialized));
+        match written {
+            Ok(()) => info!("settings saved to {}", path.display()),
+            Err(err) => error!("could not write {}: {}", path.display(), err),
+        }
+    }
+}
+
+// <config dir>/chimera-rancher, where each platform keeps the settings of its
+// applications. The settings and the saved ranch are kept there, the web build
+// has none
+pub fn config_dir() -> Option<PathBuf> {
+    let config_dir = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+
+    config_dir.map(|dir| dir.join("chimera-rancher"))
+}
+
+fn settings_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(SETTINGS_FILE))
+}
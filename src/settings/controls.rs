@@ -0,0 +1,306 @@
+use bevy::prelude::*;
+use bevy_kira_audio::Audio;
+
+use crate::{
+    assets_manager::AssetsManager,
+    constants,
+    game_input::{Action, ActionState, Binding},
+    pause_menu::button::{UIButton, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON},
+    states::GameStates,
+};
+
+use super::Settings;
+
+#[derive(Component)]
+pub struct ControlsUI;
+
+// Action waiting for its new binding, on a gamepad or not
+#[derive(Default)]
+pub struct Rebinding(Option<(Action, bool)>);
+
+// the actions are split in two columns
+const ACTIONS_PER_COLUMN: usize = 8;
+
+pub fn build_ui(mut commands: Commands, assets: Res<AssetsManager>) {
+    info!("[SettingsPlugin] Building controls UI");
+
+    let text_style = TextStyle {
+        font_size: 28.,
+        font: assets.font_regular.clone(),
+        color: Color::rgb_u8(205, 205, 205),
+    };
+
+    // root component, hiding the settings screen
+    let root = NodeBundle {
+        transform: Transform::from_xyz(0., 0., constants::Z_UI),
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: Rect::all(Val::Px(0.)),
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        color: Color::rgba_u8(0, 0, 0, 220).into(),
+        ..default()
+    };
+
+    let title = TextBundle {
+        text: Text::with_section(
+            "Controls",
+            TextStyle {
+                font_size: 64.,
+                font: assets.font_bold.clone(),
+                color: Color::rgb_u8(205, 205, 205),
+            },
+            TextAlignment::default(),
+        ),
+        ..default()
+    };
+
+    let hint = TextBundle {
+        style: Style {
+            margin: Rect {
+                bottom: Val::Px(20.),
+                ..default()
+            },
+            ..default()
+        },
+        text: Text::with_section(
+            "Pick a binding then press the new key or button, Esc cancels",
+            text_style.clone(),
+            TextAlignment::default(),
+        ),
+        ..default()
+    };
+
+    let columns = NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            ..default()
+        },
+        color: Color::NONE.into(),
+        ..default()
+    };
+    let column = NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::ColumnReverse,
+            margin: Rect::all(Val::Px(10.)),
+            ..default()
+        },
+        color: Color::NONE.into(),
+        ..default()
+    };
+    let row = NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        color: Color::NONE.into(),
+        ..default()
+    };
+    let label = |action: Action| TextBundle {
+        style: Style {
+            size: Size::new(Val::Px(150.), Val::Auto),
+            ..default()
+        },
+        text: Text::with_section(action.label(), text_style.clone(), TextAlignment::default()),
+        ..default()
+    };
+    // the texts are filled in by `update_bindings`
+    let binding_btn = |index: usize, device: &str| {
+        UIButton::new(
+            String::new(),
+            assets.font_regular.clone(),
+            format!("{}:{}", device, index),
+        )
+        .with_size(170., 36., 22.)
+    };
+
+    let back_btn = UIButton::new(
+        "Back".to_string(),
+        assets.font_bold.clone(),
+        "back".to_string(),
+    );
+
+    commands
+        .spawn_bundle(root)
+        .with_children(|parent| {
+            parent.spawn_bundle(title);
+            parent.spawn_bundle(hint);
+            parent.spawn_bundle(columns).with_children(|parent| {
+                for actions in Action::ALL.chunks(ACTIONS_PER_COLUMN) {
+                    parent.spawn_bundle(column.clone()).with_children(|parent| {
+                        for action in actions {
+                            let index = Action::ALL.iter().position(|a| a == action).unwrap();
+                            parent.spawn_bundle(row.clone()).with_children(|parent| {
+                                parent.spawn_bundle(label(*action));
+                                binding_btn(index, "keys").spawn(parent);
+                                binding_btn(index, "gamepad").spawn(parent);
+                            });
+                        }
+                    });
+                }
+            });
+            back_btn.spawn(parent);
+        })
+        .insert(ControlsUI);
+}
+
+pub fn destroy_ui(
+    mut commands: Commands,
+    query: Query<Entity, With<ControlsUI>>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    rebinding.0 = None;
+}
+
+// the action and the device of a binding button
+fn binding_button(name: &str) -> Option<(Action, bool)> {
+    let (device, index) = name.split_once(':')?;
+    let action = *Action::ALL.get(index.parse::<usize>().ok()?)?;
+    Some((action, device == "gamepad"))
+}
+
+pub fn button_handler(
+    mut interaction_query: Query<(&Interaction, &mut UiColor, &UIButton), Changed<Interaction>>,
+    mut game_state: ResMut<State<GameStates>>,
+    mut rebinding: ResMut<Rebinding>,
+    audio: Res<Audio>,
+    assets: Res<AssetsManager>,
+) {
+    for (interaction, mut color, button) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                audio.play(assets.sound_button.clone());
+                *color = PRESSED_BUTTON.into();
+                if button.name == "back" {
+                    game_state.overwrite_pop().unwrap();
+                } else if let Some(binding) = binding_button(&button.name) {
+                    rebinding.0 = Some(binding);
+                }
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+// The next key or mouse button, or gamepad button, is bound to the action. It
+// is consumed so that the menus do not react to it. Runs before the buttons
+// are handled, the click picking the binding is not taken for the new one
+pub fn capture_binding_system(
+    mut rebinding: ResMut<Rebinding>,
+    mut keyboard: ResMut<Input<KeyCode>>,
+    mut mouse: ResMut<Input<MouseButton>>,
+    mut gamepad_buttons: ResMut<Input<GamepadButton>>,
+    mut actions: ResMut<ActionState>,
+    mut settings: ResMut<Settings>,
+) {
+    let (action, gamepad) = match rebinding.0 {
+        Some(rebinding) => rebinding,
+        None => return,
+    };
+
+    if keyboard.just_pressed(KeyCode::Escape) {
+        keyboard.reset(KeyCode::Escape);
+        actions.reset(Action::Pause);
+        rebinding.0 = None;
+        return;
+    }
+
+    let binding = if gamepad {
+        let button = gamepad_buttons.get_just_pressed().next().copied();
+        button.map(|button| {
+            gamepad_buttons.reset(button);
+            Binding::Gamepad(button.1)
+        })
+    } else {
+        let key = keyboard.get_just_pressed().next().copied();
+        let mouse_button = mouse.get_just_pressed().next().copied();
+        match (key, mouse_button) {
+            (Some(key), _) => {
+                keyboard.reset(key);
+                Some(Binding::Key(key))
+            }
+            (None, Some(button)) => {
+                mouse.reset(button);
+                Some(Binding::Mouse(button))
+            }
+            (None, None) => None,
+        }
+    };
+
+    if let Some(binding) = binding {
+        // the actions of this frame were read before the binding was consumed
+        for bound in Action::ALL {
+            if settings.bindings.get(bound).contains(&binding) {
+                actions.reset(bound);
+            }
+        }
+
+        info!("{:?} bound to {}", action, binding.label());
+        settings.bindings.rebind(action, binding);
+        rebinding.0 = None;
+    }
+}
+
+// back to the settings, unless a binding is being picked
+pub fn close_controls(
+    rebinding: Res<Rebinding>,
+    mut actions: ResMut<ActionState>,
+    mut game_state: ResMut<State<GameStates>>,
+) {
+    if rebinding.0.is_none() && actions.just_pressed(Action::Pause) {
+        game_state.overwrite_pop().unwrap();
+        actions.reset(Action::Pause);
+    }
+}
+
+// the binding buttons show the current bindings, or wait for the new one
+pub fn update_bindings(
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+    button_query: Query<(&UIButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (button, children) in button_query.iter() {
+        let (action, gamepad) = match binding_button(&button.name) {
+            Some(binding) => binding,
+            None => continue,
+        };
+
+        let label = if rebinding.0 == Some((action, gamepad)) {
+            "...".to_string()
+        } else {
+            let labels: Vec<String> = settings
+                .bindings
+                .get(action)
+                .iter()
+                .filter(|binding| binding.is_gamepad() == gamepad)
+                .map(|binding| binding.label())
+                .collect();
+            match labels.is_empty() {
+                true => "-".to_string(),
+                false => labels.join(" / "),
+            }
+        };
+
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                if text.sections[0].value != label {
+                    text.sections[0].value = label.clone();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+use bevy_kira_audio::Audio;
+
+use crate::{
+    assets_manager::AssetsManager,
+    game_input::{Action, ActionState},
+    pause_menu::button::{UIButton, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON},
+    states::GameStates,
+};
+
+pub use self::config::{config_dir, SettingKind, Settings};
+
+mod config;
+mod controls;
+mod ui;
+
+#[derive(Component)]
+pub struct SettingsUI;
+
+// Settings screen, pushed over the main menu or the pause menu. The settings
+// are loaded before the window is created, in main
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        // every frame, the window follows the settings
+        app.add_system(apply_window_settings);
+
+        // on enter
+        app.add_system_set(SystemSet::on_enter(GameStates::Settings).with_system(ui::build_ui));
+        // on update
+        app.add_system_set(
+            SystemSet::on_update(GameStates::Settings)
+                .with_system(button_handler)
+                .with_system(close_settings)
+                .with_system(ui::update_values),
+        );
+        // on pause, the controls are pushed over the settings
+        app.add_system_set(SystemSet::on_pause(GameStates::Settings).with_system(destroy_ui));
+        // on resume
+        app.add_system_set(SystemSet::on_resume(GameStates::Settings).with_system(ui::build_ui));
+        // on exit
+        app.add_system_set(
+            SystemSet::on_exit(GameStates::Settings)
+                .with_system(destroy_ui)
+                .with_system(save_settings),
+        );
+
+        // controls screen
+        app.init_resource::<controls::Rebinding>();
+        app.add_system_set(
+            SystemSet::on_enter(GameStates::Controls).with_system(controls::build_ui),
+        );
+        app.add_system_set(
+            SystemSet::on_update(GameStates::Controls)
+                .with_system(controls::capture_binding_system.before("controls_buttons"))
+                .with_system(controls::button_handler.label("controls_buttons"))
+                .with_system(controls::close_controls)
+                .with_system(controls::update_bindings),
+        );
+        app.add_system_set(
+            SystemSet::on_exit(GameStates::Controls).with_system(controls::destroy_ui),
+        );
+    }
+}
+
+fn destroy_ui(mut commands: Commands, query: Query<Entity, With<SettingsUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    settings.save();
+}
+
+// back to the screen the settings were opened from
+fn close_settings(mut actions: ResMut<ActionState>, mut game_state: ResMut<State<GameStates>>) {
+    if actions.just_pressed(Action::Pause) {
+        game_state.overwrite_pop().unwrap();
+        actions.reset(Action::Pause);
+    }
+}
+
+// The UI keeps its size on screen when scaled: the window gets a larger scale
+// factor and a smaller logical size, the game camera zooms out to make up for it
+fn apply_window_settings(settings: Res<Settings>, mut windows: ResMut<Windows>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let window = if let Some(window) = windows.get_primary_mut() {
+        window
+    } else {
+        return;
+    };
+
+    let mode = settings.window_mode.window_mode();
+    if window.mode() != mode {
+        window.set_mode(mode);
+    }
+
+    let ui_scale = settings.effective_ui_scale();
+    window.set_scale_factor_override(Some(window.backend_scale_factor() * ui_scale as f64));
+    let resolution = settings.resolution() / ui_scale;
+    window.set_resolution(resolution.x, resolution.y);
+}
+
+fn button_handler(
+    mut interaction_query: Query<(&Interaction, &mut UiColor, &UIButton), Changed<Interaction>>,
+    mut game_state: ResMut<State<GameStates>>,
+    mut settings: ResMut<Settings>,
+    audio: Res<Audio>,
+    assets: Res<AssetsManager>,
+) {
+    for (interaction, mut color, button) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                audio.play(assets.sound_button.clone());
+                *color = PRESSED_BUTTON.into();
+                // the arrows are named after their setting
+                match button.name.split_once(':') {
+                    Some((name, direction)) => {
+                        if let Some(kind) = SettingKind::from_name(name) {
+                            settings.step(kind, direction == "up");
+                        }
+                    }
+                    None if button.name == "controls" => {
+                        game_state.overwrite_push(GameStates::Controls).unwrap();
+                    }
+                    None if button.name == "back" => {
+                        game_state.overwrite_pop().unwrap();
+                    }
+                    None => {}
+                }
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
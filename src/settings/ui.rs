@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+
+use crate::{assets_manager::AssetsManager, constants, pause_menu::button::UIButton};
+
+use super::{SettingKind, Settings};
+
+// Text showing the current value of a setting
+#[derive(Component)]
+pub struct SettingValue(SettingKind);
+
+pub fn build_ui(mut commands: Commands, assets: Res<AssetsManager>, settings: Res<Settings>) {
+    info!("[SettingsPlugin] Building settings UI");
+
+    let text_style = TextStyle {
+        font_size: 32.,
+        font: assets.font_regular.clone(),
+        color: Color::rgb_u8(205, 205, 205),
+    };
+
+    // root component, hiding the screen the settings were opened from
+    let root = NodeBundle {
+        transform: Transform::from_xyz(0., 0., constants::Z_UI),
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: Rect::all(Val::Px(0.)),
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        color: Color::rgba_u8(0, 0, 0, 220).into(),
+        ..default()
+    };
+
+    let title = TextBundle {
+        style: Style {
+            margin: Rect {
+                bottom: Val::Px(20.),
+                ..default()
+            },
+            ..default()
+        },
+        text: Text::with_section(
+            "Settings",
+            TextStyle {
+                font_size: 64.,
+                font: assets.font_bold.clone(),
+                color: Color::rgb_u8(205, 205, 205),
+            },
+            TextAlignment::default(),
+        ),
+        ..default()
+    };
+
+    // a label, then the value between the arrows changing it
+    let row = NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        color: Color::NONE.into(),
+        ..default()
+    };
+    let label = |kind: SettingKind| TextBundle {
+        style: Style {
+            size: Size::new(Val::Px(260.), Val::Auto),
+            ..default()
+        },
+        text: Text::with_section(kind.label(), text_style.clone(), TextAlignment::default()),
+        ..default()
+    };
+    let value_box = NodeBundle {
+        style: Style {
+            size: Size::new(Val::Px(180.), Val::Px(40.)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        color: Color::NONE.into(),
+        ..default()
+    };
+    let value = |kind: SettingKind| TextBundle {
+        text: Text::with_section(
+            settings.display(kind),
+            text_style.clone(),
+            TextAlignment::default(),
+        ),
+        ..default()
+    };
+    let arrow = |kind: SettingKind, text: &str, direction: &str| {
+        UIButton::new(
+            text.to_string(),
+            assets.font_bold.clone(),
+            format!("{}:{}", kind.name(), direction),
+        )
+        .with_size(40., 36., 32.)
+    };
+
+    let btn_group = NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            ..default()
+        },
+        color: Color::NONE.into(),
+        ..default()
+    };
+    let controls_btn = UIButton::new(
+        "Controls".to_string(),
+        assets.font_bold.clone(),
+        "controls".to_string(),
+    );
+    let back_btn = UIButton::new(
+        "Back".to_string(),
+        assets.font_bold.clone(),
+        "back".to_string(),
+    );
+
+    commands
+        .spawn_bundle(root)
+        .with_children(|parent| {
+            parent.spawn_bundle(title);
+            for kind in SettingKind::ALL {
+                parent.spawn_bundle(row.clone()).with_children(|parent| {
+                    parent.spawn_bundle(label(kind));
+                    arrow(kind, "<", "down").spawn(parent);
+                    parent
+                        .spawn_bundle(value_box.clone())
+                        .with_children(|parent| {
+                            parent.spawn_bundle(value(kind)).insert(SettingValue(kind));
+                        });
+                    arrow(kind, ">", "up").spawn(parent);
+                });
+            }
+            parent.spawn_bundle(btn_group).with_children(|parent| {
+                controls_btn.spawn(parent);
+                back_btn.spawn(parent);
+            });
+        })
+        .insert(super::SettingsUI);
+}
+
+pub fn update_values(settings: Res<Settings>, mut query: Query<(&mut Text, &SettingValue)>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (mut text, value) in query.iter_mut() {
+        text.sections[0].value = settings.display(value.0);
+    }
+}
@@ -1,7 +1,9 @@
-use bevy::prelude::*;
-use bevy_kira_audio::{AudioApp, AudioChannel, AudioPlugin};
+use bevy::{ecs::system::Resource, prelude::*};
+use bevy_kira_audio::{AudioApp, AudioChannel, AudioPlugin, MainTrack};
 use rand::Rng;
 
+use crate::rng::{GameRng, RngStream};
+use crate::settings::Settings;
 use crate::states::GameStates;
 
 pub struct SoundChannelsPlugin;
@@ -9,16 +11,18 @@ pub struct SoundChannelsPlugin;
 impl Plugin for SoundChannelsPlugin {
     fn build(&self, app: &mut App) {
         // setup
-        app.add_plugin(AudioPlugin)
-            .add_audio_channel::<SpawnChimeraAudioChannel>()
-            .add_audio_channel::<ChimeraHitAudioChannel>()
-            .add_audio_channel::<FootstepAudioChannel>()
-            .add_audio_channel::<BackgroundAudioChannel>()
-            .add_audio_channel::<ChimeraCaptureAudioChannel>()
-            .add_audio_channel::<VillagerHitAudioChannel>()
-            .add_audio_channel::<ChimeraDeathAudioChannel>()
-            .add_audio_channel::<ChimeraAttackAudioChanel>()
-            .add_audio_channel::<VillagerAttackAudioChannnel>();
+        app.add_plugin(AudioPlugin);
+        add_game_channel::<SpawnChimeraAudioChannel>(app, Volume::Sfx);
+        add_game_channel::<ChimeraHitAudioChannel>(app, Volume::Sfx);
+        add_game_channel::<FootstepAudioChannel>(app, Volume::Sfx);
+        add_game_channel::<BackgroundAudioChannel>(app, Volume::Music);
+        add_game_channel::<ChimeraCaptureAudioChannel>(app, Volume::Sfx);
+        add_game_channel::<VillagerHitAudioChannel>(app, Volume::Sfx);
+        add_game_channel::<ChimeraDeathAudioChannel>(app, Volume::Sfx);
+        add_game_channel::<ChimeraAttackAudioChanel>(app, Volume::Sfx);
+        add_game_channel::<VillagerAttackAudioChannnel>(app, Volume::Sfx);
+        // the menu sounds
+        app.add_system(sfx_volume::<MainTrack>);
 
         // on game start
         app.add_system_set(
@@ -26,6 +30,52 @@ impl Plugin for SoundChannelsPlugin {
         );
         // on game loop
         app.add_system_set(SystemSet::on_update(GameStates::Game).with_system(background_music));
+        // on game end
+        app.add_system_set(SystemSet::on_exit(GameStates::Game).with_system(stop_game_sounds));
+    }
+}
+
+// Which of the volume settings a channel follows
+enum Volume {
+    Music,
+    Sfx,
+}
+
+// Registers a channel of the game sounds, paused along with the game. Menu
+// sounds play on the default channel and keep playing
+fn add_game_channel<T: Resource>(app: &mut App, volume: Volume) {
+    app.add_audio_channel::<T>();
+
+    // every frame, the volume follows the settings
+    match volume {
+        Volume::Music => app.add_system(music_volume::<T>),
+        Volume::Sfx => app.add_system(sfx_volume::<T>),
+    };
+
+    // on pause
+    app.add_system_set(SystemSet::on_pause(GameStates::Game).with_system(pause_channel::<T>));
+    // on resume, and when a new game starts after one left paused
+    app.add_system_set(SystemSet::on_resume(GameStates::Game).with_system(resume_channel::<T>));
+    app.add_system_set(SystemSet::on_enter(GameStates::Game).with_system(resume_channel::<T>));
+}
+
+fn pause_channel<T: Resource>(channel: Res<AudioChannel<T>>) {
+    channel.pause();
+}
+
+fn resume_channel<T: Resource>(channel: Res<AudioChannel<T>>) {
+    channel.resume();
+}
+
+fn music_volume<T: Resource>(channel: Res<AudioChannel<T>>, settings: Res<Settings>) {
+    if settings.is_changed() {
+        channel.set_volume(settings.music_volume());
+    }
+}
+
+fn sfx_volume<T: Resource>(channel: Res<AudioChannel<T>>, settings: Res<Settings>) {
+    if settings.is_changed() {
+        channel.set_volume(settings.sfx_volume());
     }
 }
 
@@ -48,14 +98,25 @@ const BACKGROUND_MUSICS: &'static [&str] = &[
 fn setup_background_music(
     asset_server: Res<AssetServer>,
     background_audio: Res<AudioChannel<BackgroundAudioChannel>>,
+    mut rng: ResMut<GameRng>,
 ) {
-    let random_music = BACKGROUND_MUSICS[rand::thread_rng().gen_range(0..BACKGROUND_MUSICS.len())];
+    let random_music = BACKGROUND_MUSICS[rng
+        .stream(RngStream::Audio)
+        .gen_range(0..BACKGROUND_MUSICS.len())];
     let mut music_path = String::from("sounds/background/");
     music_path.push_str(random_music);
 
-    background_audio.set_volume(0.5);
     background_audio.play_looped(asset_server.load(&music_path));
 }
 
+// looped sounds are started again by the next game
+fn stop_game_sounds(
+    background_audio: Res<AudioChannel<BackgroundAudioChannel>>,
+    step_audio: Res<AudioChannel<FootstepAudioChannel>>,
+) {
+    background_audio.stop();
+    step_audio.stop();
+}
+
 // TODO: play other musics once the first is done
 fn background_music() {}
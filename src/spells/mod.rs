@@ -1,6 +1,7 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::GameStates;
+use crate::schedule::{self, FixedUpdateStage};
 
 mod fire_projectile;
 mod spawn_chimera;
@@ -8,15 +9,16 @@ pub struct SpellsPlugin;
 
 impl Plugin for SpellsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(
-            SystemSet::on_update(GameStates::Game)
-                .with_system(spawn_chimera::spawn_chimera_system)
-                .with_system(fire_projectile::fire_projectile_system),
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            schedule::on_fixed_update()
+                .with_system(spawn_chimera::spawn_chimera_system.after("select_parts"))
+                .with_system(fire_projectile::fire_projectile_system.after("game_input")),
         );
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SpellKind {
     SpawnChimera,
     FireProjectile,
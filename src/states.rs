@@ -8,4 +8,21 @@ pub enum GameStates {
     PauseMenu,
     Game,
     GameOver,
+    // pushed over the main menu or the pause menu
+    Settings,
+    Credits,
+    // pushed over the settings
+    Controls,
+}
+
+// Root entities spawned for a game, despawned with their children when leaving
+// `GameStates::Game` so that a new game starts from scratch
+#[derive(Component)]
+pub struct GameEntity;
+
+pub fn despawn_game_entities(mut commands: Commands, query: Query<Entity, With<GameEntity>>) {
+    info!("[GameStates] Destroying game entities before exiting...");
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
 }
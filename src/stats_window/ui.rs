@@ -4,12 +4,15 @@ use bevy_tweening::{lens::UiPositionLens, Animator, EaseFunction, Tween, Tweenin
 use crate::{
     animals::AnimalComponent,
     assets_manager::AssetsManager,
-    chimeras::ChimeraComponent,
+    chimeras::{current_order, ChimeraComponent},
     constants::{self, MaxStats},
+    game_input::BlocksPointer,
     health::Health,
+    selection::Selection,
+    states::GameEntity,
 };
 
-use super::{ui_bars::*, EntityType, StatsWindow};
+use super::{ui_bars::*, StatsWindow};
 
 #[derive(Component)]
 pub struct StatsWindowUI;
@@ -17,6 +20,20 @@ pub struct StatsWindowUI;
 #[derive(Component)]
 pub struct StatWindowTitle;
 
+// Lines of text about the shown chimera, empty for animals
+#[derive(Component)]
+pub enum StatWindowInfo {
+    Lineage,
+    Kills,
+    Order,
+}
+
+// Row of trait icons, rebuilt when the window shows another chimera
+#[derive(Component, Default)]
+pub struct StatWindowTraits {
+    shown: Option<Entity>,
+}
+
 const CLOSED_POS: Rect<Val> = Rect {
     right: Val::Px(-350.),
     top: Val::Px(0.),
@@ -30,9 +47,21 @@ const OPENED_POS: Rect<Val> = Rect {
     left: Val::Auto,
 };
 
+// Stats shown by the bars
+#[derive(Default)]
+struct ShownStats {
+    health: f32,
+    max_health: f32,
+    accel: f32,
+    decel: f32,
+    attack: f32,
+    speed: f32,
+    regen: f32,
+    range: f32,
+}
+
 pub fn update_window_stats(
-    // mut commands: Commands,
-    mut stats_window: ResMut<StatsWindow>,
+    selection: Res<Selection>,
     maxi_stats: Res<MaxStats>,
     q_chimera: Query<(&Health, &ChimeraComponent)>,
     q_animal: Query<(&Health, &AnimalComponent)>,
@@ -43,87 +72,216 @@ pub fn update_window_stats(
     >,
     mut q_ui_bar_value: Query<&mut Style, With<ValueBarComponent>>,
 ) {
-    if let Some(target_entity) = stats_window.target {
-        // get the player
-        let zero_health = Health {
-            health: 0.,
-            max_health: 0.,
-            regen: 0.,
-            regen_timer: Timer::from_seconds(0.0, false),
+    // get the stats of every selected unit
+    let selected: Vec<ShownStats> = selection
+        .units
+        .iter()
+        .filter_map(|entity| {
+            if let Ok((health, chimera)) = q_chimera.get(*entity) {
+                let stats = chimera.stats;
+                Some(ShownStats {
+                    health: health.health,
+                    max_health: health.max_health,
+                    accel: stats.accel,
+                    decel: stats.decel,
+                    attack: stats.attack,
+                    speed: stats.speed,
+                    regen: stats.regen,
+                    range: stats.range,
+                })
+            } else if let Ok((health, animal)) = q_animal.get(*entity) {
+                let stats = &animal.stats;
+                Some(ShownStats {
+                    health: health.health,
+                    max_health: health.max_health,
+                    accel: stats.accel,
+                    decel: stats.decel,
+                    attack: stats.attack,
+                    speed: stats.speed,
+                    regen: stats.regen,
+                    range: stats.range,
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    if selected.is_empty() {
+        return;
+    }
+
+    // several chimeras show the mean of their stats
+    let count = selected.len() as f32;
+    let mean = selected
+        .iter()
+        .fold(ShownStats::default(), |sum, stats| ShownStats {
+            health: sum.health + stats.health / count,
+            max_health: sum.max_health + stats.max_health / count,
+            accel: sum.accel + stats.accel / count,
+            decel: sum.decel + stats.decel / count,
+            attack: sum.attack + stats.attack / count,
+            speed: sum.speed + stats.speed / count,
+            regen: sum.regen + stats.regen / count,
+            range: sum.range + stats.range / count,
+        });
+
+    // for each bar
+    for (children, bar) in q_ui_bar.iter() {
+        // set value according to bartype
+        let (max_value_possible, max_value, value) = match bar.bartype {
+            BarStatType::Acceleration => (maxi_stats.accel, mean.accel, mean.accel),
+            BarStatType::Deceleration => (maxi_stats.decel, mean.decel, mean.decel),
+            BarStatType::Speed => (maxi_stats.speed, mean.speed, mean.speed),
+            BarStatType::Attack => (maxi_stats.attack, mean.attack, mean.attack),
+            BarStatType::Health => (maxi_stats.health, mean.max_health, mean.health),
+            BarStatType::Regen => (maxi_stats.regen, mean.regen, mean.regen),
+            BarStatType::Range => (maxi_stats.range, mean.range, mean.range),
         };
-        // get the stats
-        let (health, accel, decel, attack, speed, regen, range) =
-            if stats_window.target_type == EntityType::Chimera {
-                if let Ok((health, chimera)) = q_chimera.get(target_entity) {
-                    let stats = chimera.stats;
-                    (
-                        health,
-                        stats.accel,
-                        stats.decel,
-                        stats.attack,
-                        stats.speed,
-                        stats.regen,
-                        stats.range,
-                    )
-                } else {
-                    (&zero_health, 0., 0., 0., 0., 0., 0.)
+
+        // getting max_value
+        for child in children.iter() {
+            if let Ok((children2, mut style)) = q_ui_bar_max.get_mut(*child) {
+                style.size = Size::new(
+                    Val::Percent(100. * max_value / max_value_possible),
+                    Val::Percent(100.),
+                );
+
+                // getting value
+                for child2 in children2.iter() {
+                    if let Ok(mut style) = q_ui_bar_value.get_mut(*child2) {
+                        style.size =
+                            Size::new(Val::Percent(100. * value / max_value), Val::Percent(100.));
+                    }
                 }
-            } else if let Ok((health, animal)) = q_animal.get(target_entity) {
-                let stats = animal.stats;
-                (
-                    health,
-                    stats.accel,
-                    stats.decel,
-                    stats.attack,
-                    stats.speed,
-                    stats.regen,
-                    stats.range,
-                )
+            }
+        }
+    }
+}
+
+pub fn update_window_chimera_info(
+    selection: Res<Selection>,
+    q_chimera: Query<&ChimeraComponent>,
+    mut q_info: Query<(&mut Text, &StatWindowInfo)>,
+) {
+    let chimeras: Vec<&ChimeraComponent> = selection
+        .units
+        .iter()
+        .filter_map(|entity| q_chimera.get(*entity).ok())
+        .collect();
+
+    // the lineage is only shown for a single chimera
+    let lineage = match chimeras[..] {
+        [chimera] => {
+            let lineage = &chimera.lineage;
+            match lineage.parents {
+                Some((parent_a, parent_b)) => format!(
+                    "#{}, generation {} (#{} x #{})",
+                    lineage.id, lineage.generation, parent_a, parent_b
+                ),
+                None => format!("#{}, generation {}", lineage.id, lineage.generation),
+            }
+        }
+        _ => String::new(),
+    };
+    let (kills, order) = match chimeras.first() {
+        Some(first) => {
+            let kills: u32 = chimeras.iter().map(|chimera| chimera.kills).sum();
+            let order = current_order(&first.behavior);
+            let order = if chimeras
+                .iter()
+                .all(|chimera| current_order(&chimera.behavior) == order)
+            {
+                order.name()
             } else {
-                (&zero_health, 0., 0., 0., 0., 0., 0.)
+                "mixed"
             };
+            (format!("Kills: {}", kills), format!("Order: {}", order))
+        }
+        None => Default::default(),
+    };
 
-        // if the values are null, the entity does not exist anymore
-        if health.max_health == 0. && health.health == 0. {
-            stats_window.target = None;
-            stats_window.cursor = None;
-            stats_window.target_type = EntityType::None;
-            return;
+    for (mut text, info) in q_info.iter_mut() {
+        text.sections[0].value = match info {
+            StatWindowInfo::Lineage => lineage.clone(),
+            StatWindowInfo::Kills => kills.clone(),
+            StatWindowInfo::Order => order.clone(),
+        };
+    }
+}
+
+pub fn update_window_traits(
+    mut commands: Commands,
+    selection: Res<Selection>,
+    assets: Res<AssetsManager>,
+    q_chimera: Query<&ChimeraComponent>,
+    mut q_traits: Query<(Entity, &mut StatWindowTraits)>,
+) {
+    // traits are only shown for a single unit
+    let shown = match selection.units[..] {
+        [entity] => Some(entity),
+        _ => None,
+    };
+
+    for (row_entity, mut row) in q_traits.iter_mut() {
+        if row.shown == shown {
+            continue;
         }
+        row.shown = shown;
 
-        // for each bar
-        for (children, bar) in q_ui_bar.iter() {
-            // set value according to bartype
-            let (max_value_possible, max_value, value) = match bar.bartype {
-                BarStatType::Acceleration => (maxi_stats.accel, accel, accel),
-                BarStatType::Deceleration => (maxi_stats.decel, decel, decel),
-                BarStatType::Speed => (maxi_stats.speed, speed, speed),
-                BarStatType::Attack => (maxi_stats.attack, attack, attack),
-                BarStatType::Health => (maxi_stats.health, health.max_health, health.health),
-                BarStatType::Regen => (maxi_stats.regen, regen, regen),
-                BarStatType::Range => (maxi_stats.range, range, range),
-            };
+        let traits = shown
+            .and_then(|target_entity| q_chimera.get(target_entity).ok())
+            .map(|chimera| chimera.traits.clone())
+            .unwrap_or_default();
 
-            // getting max_value
-            for child in children.iter() {
-                if let Ok((children2, mut style)) = q_ui_bar_max.get_mut(*child) {
-                    style.size = Size::new(
-                        Val::Percent(100. * max_value / max_value_possible),
-                        Val::Percent(100.),
-                    );
-
-                    // getting value
-                    for child2 in children2.iter() {
-                        if let Ok(mut style) = q_ui_bar_value.get_mut(*child2) {
-                            style.size = Size::new(
-                                Val::Percent(100. * value / max_value),
-                                Val::Percent(100.),
-                            );
-                        }
-                    }
-                }
+        let mut row = commands.entity(row_entity);
+        row.despawn_descendants();
+        row.with_children(|parent| {
+            for chimera_trait in traits {
+                let label = format!(
+                    "{} +{:.0}%",
+                    chimera_trait.kind.name(),
+                    chimera_trait.value * 100.
+                );
+
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            margin: Rect::all(Val::Px(4.0)),
+                            ..default()
+                        },
+                        color: Color::NONE.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn_bundle(ImageBundle {
+                            image: chimera_trait.kind.icon(&assets).into(),
+                            style: Style {
+                                size: Size::new(Val::Px(24.), Val::Px(24.)),
+                                margin: Rect {
+                                    right: Val::Px(4.),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            ..default()
+                        });
+                        parent.spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                label,
+                                TextStyle {
+                                    font: assets.font_regular.clone(),
+                                    font_size: 18.0,
+                                    color: Color::WHITE,
+                                },
+                                Default::default(),
+                            ),
+                            ..default()
+                        });
+                    });
             }
-        }
+        });
     }
 }
 
@@ -150,7 +308,7 @@ pub fn setup_ui(mut commands: Commands, assets: Res<AssetsManager>) {
     let border = NodeBundle {
         style: Style {
             position: CLOSED_POS,
-            size: Size::new(Val::Px(300.0), Val::Px(550.0)),
+            size: Size::new(Val::Px(300.0), Val::Px(750.0)),
             border: Rect::all(Val::Px(2.0)),
             ..default()
         },
@@ -194,44 +352,73 @@ pub fn setup_ui(mut commands: Commands, assets: Res<AssetsManager>) {
     };
 
     // spawn the ui
-    commands.spawn_bundle(container).with_children(|parent| {
-        parent
-            .spawn_bundle(border)
-            .with_children(|parent| {
-                parent
-                    .spawn_bundle(content_container)
-                    .with_children(|parent| {
-                        parent.spawn_bundle(content_text).insert(StatWindowTitle);
-                        // health
-                        parent.spawn_bundle(create_stat_text(&assets, "Health"));
-                        create_ui_bar(parent, UIBar::from_type(BarStatType::Health));
-                        // speed
-                        parent.spawn_bundle(create_stat_text(&assets, "Speed"));
-                        create_ui_bar(parent, UIBar::from_type(BarStatType::Speed));
-                        // accel
-                        parent.spawn_bundle(create_stat_text(&assets, "Acceleration"));
-                        create_ui_bar(parent, UIBar::from_type(BarStatType::Acceleration));
-                        // decel
-                        parent.spawn_bundle(create_stat_text(&assets, "Deceleration"));
-                        create_ui_bar(parent, UIBar::from_type(BarStatType::Deceleration));
-                        // attack
-                        parent.spawn_bundle(create_stat_text(&assets, "Attack"));
-                        create_ui_bar(parent, UIBar::from_type(BarStatType::Attack));
-                        // regen
-                        parent.spawn_bundle(create_stat_text(&assets, "Regeneration"));
-                        create_ui_bar(parent, UIBar::from_type(BarStatType::Regen));
-                        // range
-                        parent.spawn_bundle(create_stat_text(&assets, "Range"));
-                        create_ui_bar(parent, UIBar::from_type(BarStatType::Range));
-                    });
-            })
-            .insert(Animator::<Style>::default())
-            .insert(StatsWindowUI);
-    });
+    commands
+        .spawn_bundle(container)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(border)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(content_container)
+                        .with_children(|parent| {
+                            parent.spawn_bundle(content_text).insert(StatWindowTitle);
+                            // lineage, kills and order, chimeras only
+                            for info in [
+                                StatWindowInfo::Lineage,
+                                StatWindowInfo::Kills,
+                                StatWindowInfo::Order,
+                            ] {
+                                parent
+                                    .spawn_bundle(create_stat_text(&assets, ""))
+                                    .insert(info);
+                            }
+                            // traits, chimeras only
+                            parent
+                                .spawn_bundle(NodeBundle {
+                                    style: Style {
+                                        flex_wrap: FlexWrap::Wrap,
+                                        justify_content: JustifyContent::Center,
+                                        size: Size::new(Val::Percent(100.0), Val::Auto),
+                                        ..default()
+                                    },
+                                    color: Color::NONE.into(),
+                                    ..default()
+                                })
+                                .insert(StatWindowTraits::default());
+                            // health
+                            parent.spawn_bundle(create_stat_text(&assets, "Health"));
+                            create_ui_bar(parent, UIBar::from_type(BarStatType::Health));
+                            // speed
+                            parent.spawn_bundle(create_stat_text(&assets, "Speed"));
+                            create_ui_bar(parent, UIBar::from_type(BarStatType::Speed));
+                            // accel
+                            parent.spawn_bundle(create_stat_text(&assets, "Acceleration"));
+                            create_ui_bar(parent, UIBar::from_type(BarStatType::Acceleration));
+                            // decel
+                            parent.spawn_bundle(create_stat_text(&assets, "Deceleration"));
+                            create_ui_bar(parent, UIBar::from_type(BarStatType::Deceleration));
+                            // attack
+                            parent.spawn_bundle(create_stat_text(&assets, "Attack"));
+                            create_ui_bar(parent, UIBar::from_type(BarStatType::Attack));
+                            // regen
+                            parent.spawn_bundle(create_stat_text(&assets, "Regeneration"));
+                            create_ui_bar(parent, UIBar::from_type(BarStatType::Regen));
+                            // range
+                            parent.spawn_bundle(create_stat_text(&assets, "Range"));
+                            create_ui_bar(parent, UIBar::from_type(BarStatType::Range));
+                        });
+                })
+                .insert(Animator::<Style>::default())
+                .insert(BlocksPointer)
+                .insert(StatsWindowUI);
+        })
+        .insert(GameEntity);
 }
 
 pub fn display_stats_window(
     mut stats_window: ResMut<StatsWindow>,
+    selection: Res<Selection>,
+    q_chimera: Query<(), With<ChimeraComponent>>,
     mut q_title: Query<&mut Text, With<StatWindowTitle>>,
     mut q_anim: Query<&mut Animator<Style>, With<StatsWindowUI>>,
 ) {
@@ -239,7 +426,7 @@ pub fn display_stats_window(
     let mut animator = q_anim.get_single_mut().unwrap();
 
     // if finished
-    if stats_window.target != None && !stats_window.opened {
+    if !selection.units.is_empty() && !stats_window.opened {
         log::debug!("opening stat window");
 
         stats_window.opened = true;
@@ -252,7 +439,7 @@ pub fn display_stats_window(
                 end: OPENED_POS,
             },
         ));
-    } else if stats_window.target == None && stats_window.opened {
+    } else if selection.units.is_empty() && stats_window.opened {
         log::debug!("closing stat window");
 
         stats_window.opened = false;
@@ -268,13 +455,15 @@ pub fn display_stats_window(
     }
 
     // change the title
-    for mut title in q_title.iter_mut() {
-        title.sections[0].value = (match stats_window.target_type {
-            EntityType::Animal => "Animal stats",
-            EntityType::Chimera => "Chimera stats",
-            _ => &title.sections[0].value,
-        })
-        .to_string();
+    // the title is kept while the window closes
+    let title = match selection.units[..] {
+        [] => return,
+        [entity] if q_chimera.get(entity).is_ok() => "Chimera stats".to_string(),
+        [_] => "Animal stats".to_string(),
+        ref units => format!("{} chimeras", units.len()),
+    };
+    for mut text in q_title.iter_mut() {
+        text.sections[0].value = title.clone();
     }
 }
 
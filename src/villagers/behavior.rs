@@ -1,70 +1,205 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use super::{VillagerComponent, VillagerSprite};
-use crate::behaviors::{self, UnitBehavior};
+use super::{ThreatTable, VillagerAbility, VillagerComponent};
+use crate::balance::GameBalance;
+use crate::behaviors::{self, steering};
+use crate::chimeras::ChimeraComponent;
+use crate::health::Health;
+use crate::navigation::{NavGrid, NavPath};
 use crate::player::Player;
 
-// Handles animals behaving according to their current behavior
+// archers walk toward their target until it is within this share of their range
+const SKIRMISH_RANGE_SHARE: f32 = 0.8;
+// priests stay within this share of their range from the villager they tend to
+const SUPPORT_RANGE_SHARE: f32 = 0.5;
+
+// States of a villager
+#[derive(Clone)]
+pub enum VillagerBehavior {
+    // going after the most threatening unit around
+    Pursue { target: Option<Vec2> },
+    // keeping its distance from its target while shooting at it
+    Skirmish { target: Option<Vec2> },
+    // staying close to the most hurt villager around to heal it
+    Support { target: Option<Vec2> },
+    // running away from the closest threat while badly hurt
+    RunAway { target: Option<Vec2> },
+}
+
+impl VillagerBehavior {
+    // how the villagers of a role behave when they spawn or get healed
+    pub fn initial(ability: VillagerAbility) -> Self {
+        match ability {
+            VillagerAbility::Melee => VillagerBehavior::Pursue { target: None },
+            VillagerAbility::Ranged { .. } => VillagerBehavior::Skirmish { target: None },
+            VillagerAbility::Heal => VillagerBehavior::Support { target: None },
+        }
+    }
+}
+
+// Moves the villagers towards the most threatening unit, each role its own
+// way, and makes the badly hurt ones run away until healed
 pub fn villager_behavior_system(
-    time: Res<Time>,
-    mut villager_query: Query<(&mut VillagerComponent, &mut Velocity, &Transform, &Children)>,
-    mut sprite_query: Query<&mut Sprite, With<VillagerSprite>>,
+    mut villager_query: Query<(
+        Entity,
+        &mut VillagerComponent,
+        &mut Velocity,
+        &mut NavPath,
+        &Transform,
+        &Health,
+        &ThreatTable,
+    )>,
     player_query: Query<&Transform, With<Player>>,
+    chimera_query: Query<&Transform, With<ChimeraComponent>>,
+    target_query: Query<&Transform, Without<VillagerComponent>>,
+    grid: Res<NavGrid>,
+    balance: Res<GameBalance>,
 ) {
-    for (mut villager, mut vel, transform, children) in villager_query.iter_mut() {
-        let sprite_entity = children.iter().next().unwrap();
-        let mut sprite = sprite_query.get_mut(*sprite_entity).unwrap();
-
-        if sprite.color.r() > 1.0 {
-            villager.damage_timer.tick(time.delta());
-            if villager.damage_timer.just_finished() {
-                sprite.color.set_r(1.0);
-            }
-        }
+    let balance = &balance.behaviors;
 
+    // position and share of health left of every villager
+    let villagers: Vec<(Entity, Vec2, f32)> = villager_query
+        .iter()
+        .map(|(entity, _, _, _, transform, health, _)| {
+            (
+                entity,
+                transform.translation.truncate(),
+                health.health / health.max_health,
+            )
+        })
+        .collect();
+
+    for (entity, mut villager, mut vel, mut path, transform, health, threats) in
+        villager_query.iter_mut()
+    {
         let player_transform = player_query.iter().next().unwrap();
+        let player_position = player_transform.translation.truncate();
+        let position = transform.translation.truncate();
         let stats = villager.stats;
+        let ability = villager.ability;
+
+        let health_share = health.health / health.max_health;
+
+        // the closest of the player and the chimeras
+        let enemy = chimera_query
+            .iter()
+            .map(|transform| transform.translation.truncate())
+            .chain(std::iter::once(player_position))
+            .min_by(|a, b| position.distance(*a).total_cmp(&position.distance(*b)))
+            .unwrap();
+
+        // the most threatening unit, picked from the threat table
+        let threat_target = threats
+            .target
+            .and_then(|target| target_query.get(target).ok())
+            .map(|transform| transform.translation.truncate());
+
         match &mut villager.behavior {
-            behaviors::UnitBehavior::Idle {
-                timer,
-                base_duration,
-                duration_spread,
-                direction,
-                is_moving,
-            } => behaviors::idle_behavior(
-                &mut vel,
-                vec![&mut sprite],
-                &time,
-                timer,
-                base_duration,
-                duration_spread,
-                direction,
-                is_moving,
-                stats.into(),
-            ),
-            behaviors::UnitBehavior::Pursue { target } => {
+            VillagerBehavior::Pursue { target } => {
                 behaviors::pursue_behavior(
                     &mut vel,
-                    vec![&mut sprite],
                     stats.into(),
-                    Vec2::new(transform.translation.x, transform.translation.y),
+                    position,
                     *target,
+                    &grid,
+                    &mut path,
                 );
 
-                // target the player
-                villager.behavior = UnitBehavior::Pursue {
-                    target: Some(Vec2::new(
-                        player_transform.translation.x,
-                        player_transform.translation.y,
-                    )),
+                villager.behavior = VillagerBehavior::Pursue {
+                    target: threat_target,
+                };
+            }
+            VillagerBehavior::Skirmish { target } => {
+                let keep_distance = match ability {
+                    VillagerAbility::Ranged { keep_distance, .. } => keep_distance,
+                    VillagerAbility::Melee | VillagerAbility::Heal => 0.0,
+                };
+
+                if let Some(target) = *target {
+                    let distance = position.distance(target);
+                    if distance < keep_distance {
+                        behaviors::run_away_behavior(
+                            &mut vel,
+                            stats.into(),
+                            position,
+                            Some(target),
+                        );
+                    } else if distance > stats.range * SKIRMISH_RANGE_SHARE {
+                        behaviors::pursue_behavior(
+                            &mut vel,
+                            stats.into(),
+                            position,
+                            Some(target),
+                            &grid,
+                            &mut path,
+                        );
+                    } else {
+                        behaviors::stop_behavior(&mut vel, stats.into());
+                    }
+                }
+
+                villager.behavior = VillagerBehavior::Skirmish {
+                    target: threat_target,
+                };
+            }
+            VillagerBehavior::Support { target } => {
+                if target.is_some() {
+                    behaviors::follow_behavior(
+                        &mut vel,
+                        stats.into(),
+                        position,
+                        *target,
+                        stats.range * SUPPORT_RANGE_SHARE,
+                        &grid,
+                        &mut path,
+                    );
+                } else {
+                    behaviors::stop_behavior(&mut vel, stats.into());
+                }
+
+                // the most hurt of the other villagers, the closest one when
+                // none is hurt
+                let tended = villagers
+                    .iter()
+                    .filter(|(other, _, _)| *other != entity)
+                    .min_by(|(_, a, a_share), (_, b, b_share)| {
+                        a_share
+                            .total_cmp(b_share)
+                            .then(position.distance(*a).total_cmp(&position.distance(*b)))
+                    })
+                    .map(|(_, other_position, _)| *other_position);
+
+                villager.behavior = VillagerBehavior::Support { target: tended };
+            }
+            VillagerBehavior::RunAway { target } => {
+                if target.is_some() {
+                    behaviors::run_away_behavior(&mut vel, stats.into(), position, *target);
+                } else {
+                    // catching its breath when nothing is close
+                    behaviors::stop_behavior(&mut vel, stats.into());
                 }
+
+                let threat =
+                    (position.distance(enemy) < balance.villager_flee_range).then_some(enemy);
+
+                villager.behavior = if health_share >= balance.villager_recover_health {
+                    VillagerBehavior::initial(villager.ability)
+                } else {
+                    VillagerBehavior::RunAway { target: threat }
+                };
             }
-            UnitBehavior::Follow {
-                target: _,
-                distance: _,
-            } => todo!(),
-            UnitBehavior::RunAway { target: _ } => todo!(),
         }
+
+        // badly hurt villagers of every role run away
+        let running_away = matches!(villager.behavior, VillagerBehavior::RunAway { .. });
+        if !running_away && health_share < balance.villager_flee_health {
+            villager.behavior = VillagerBehavior::RunAway { target: None };
+        }
+
+        // villagers spread out instead of piling up
+        let push =
+            steering::separation(position, villagers.iter().map(|(_, position, _)| *position));
+        vel.linvel = steering::separate(vel.linvel, push, &stats.into());
     }
 }
@@ -0,0 +1,310 @@
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::{anyhow, bail};
+use bevy::{
+    asset::{AssetLoader, AssetPath, BoxedFuture, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use serde::{Deserialize, Serialize};
+
+// Name of a villager role, as defined in the villagers file
+#[derive(Hash, PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct VillagerRole(String);
+
+impl VillagerRole {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+}
+
+impl fmt::Display for VillagerRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// What a villager does when its attack timer finishes
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum VillagerAbility {
+    // hits the player or a chimera in range
+    Melee,
+    // stays away from its target and shoots projectiles at it
+    Ranged {
+        keep_distance: f32,
+        projectile_speed: f32,
+    },
+    // heals the most hurt villager in range by its attack
+    Heal,
+}
+
+// How much the villagers of a role care about each kind of target, multiplies
+// the threat they feel from it
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TargetPreferences {
+    pub player: f32,
+    pub chimera: f32,
+    pub animal: f32,
+}
+
+impl Default for TargetPreferences {
+    // villagers leave the animals alone unless told otherwise
+    fn default() -> Self {
+        Self {
+            player: 1.0,
+            chimera: 1.0,
+            animal: 0.0,
+        }
+    }
+}
+
+// Stats and looks shared by the villagers of a role
+#[derive(Debug, Clone)]
+pub struct VillagerRoleAttributes {
+    pub health: f32,
+    pub attack: f32,
+    pub speed: f32,
+    pub accel: f32,
+    pub decel: f32,
+    pub range: f32,
+    // seconds between two attacks
+    pub attack_rate: f32,
+    pub ability: VillagerAbility,
+    // extra share of the melee damage dealt to chimeras
+    pub chimera_bonus: f32,
+    pub preferences: TargetPreferences,
+    // size of the sprite and collider, 1 for a regular villager
+    pub scale: f32,
+    // a villager picks one of the textures of its role
+    pub textures: Vec<Handle<Image>>,
+    // chance of this role among the ones that can spawn
+    pub spawn_weight: u32,
+    // first day the role appears on, the first day being 0
+    pub first_day: u32,
+    // most villagers of this role alive at once
+    pub max_alive: Option<u32>,
+}
+
+// Roles described by a villagers file, in a stable (alphabetical) order
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "6f3a8f0e-52c1-4b0e-9a57-2c4f2f1d8b6e"]
+pub struct VillagerDefinitions {
+    pub roles: Vec<(VillagerRole, VillagerRoleAttributes)>,
+}
+
+impl VillagerDefinitions {
+    pub fn get(&self, role: &VillagerRole) -> Option<&VillagerRoleAttributes> {
+        self.roles
+            .iter()
+            .find(|(name, _)| name == role)
+            .map(|(_, attributes)| attributes)
+    }
+}
+
+// One entry of the villagers file, as written by the designers
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VillagerDefinition {
+    health: f32,
+    attack: f32,
+    speed: f32,
+    accel: f32,
+    decel: f32,
+    range: f32,
+    attack_rate: f32,
+    ability: VillagerAbility,
+    #[serde(default)]
+    chimera_bonus: f32,
+    #[serde(default)]
+    preferences: TargetPreferences,
+    #[serde(default = "default_scale")]
+    scale: f32,
+    textures: Vec<String>,
+    spawn_weight: u32,
+    #[serde(default)]
+    first_day: u32,
+    #[serde(default)]
+    max_alive: Option<u32>,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+impl VillagerDefinition {
+    // checks the values that would otherwise break the game at runtime
+    fn validate(&self) -> anyhow::Result<()> {
+        let positive_stats = [
+            ("health", self.health),
+            ("speed", self.speed),
+            ("accel", self.accel),
+            ("decel", self.decel),
+            ("range", self.range),
+            ("attack_rate", self.attack_rate),
+            ("scale", self.scale),
+        ];
+        for (name, value) in positive_stats {
+            if !value.is_finite() || value <= 0.0 {
+                bail!("`{}` must be strictly positive, got {}", name, value);
+            }
+        }
+
+        for (name, value) in [
+            ("attack", self.attack),
+            ("chimera_bonus", self.chimera_bonus),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                bail!("`{}` must be positive, got {}", name, value);
+            }
+        }
+
+        if let VillagerAbility::Ranged {
+            keep_distance,
+            projectile_speed,
+        } = self.ability
+        {
+            if !(0.0..self.range).contains(&keep_distance) {
+                bail!(
+                    "`keep_distance` must be in [0, range), got {}",
+                    keep_distance
+                );
+            }
+            if !projectile_speed.is_finite() || projectile_speed <= 0.0 {
+                bail!(
+                    "`projectile_speed` must be strictly positive, got {}",
+                    projectile_speed
+                );
+            }
+        }
+
+        let TargetPreferences {
+            player,
+            chimera,
+            animal,
+        } = self.preferences;
+        for (name, value) in [
+            ("preferences.player", player),
+            ("preferences.chimera", chimera),
+            ("preferences.animal", animal),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                bail!("`{}` must be positive, got {}", name, value);
+            }
+        }
+
+        if self.textures.is_empty() || self.textures.iter().any(String::is_empty) {
+            bail!("`textures` must point to at least one image");
+        }
+
+        // the sprite variant of a villager is saved as a byte
+        if self.textures.len() > u8::MAX as usize {
+            bail!("`textures` can not list more than {} images", u8::MAX);
+        }
+
+        if self.max_alive == Some(0) {
+            bail!("`max_alive` must be strictly positive");
+        }
+
+        Ok(())
+    }
+}
+
+// Parses the content of a villagers file, the error names the faulty entry
+fn parse_definitions(bytes: &[u8]) -> anyhow::Result<BTreeMap<String, VillagerDefinition>> {
+    // the abilities are enums, which do not survive a `ron::Value`, so entries
+    // are parsed at once and malformed ones are found by their position
+    let definitions: BTreeMap<String, VillagerDefinition> =
+        ron::de::from_bytes(bytes).map_err(|err| anyhow!("malformed villagers file: {}", err))?;
+
+    if definitions.is_empty() {
+        bail!("the villagers file does not define any role");
+    }
+
+    for (name, definition) in definitions.iter() {
+        definition
+            .validate()
+            .map_err(|err| anyhow!("invalid villager `{}`: {}", name, err))?;
+    }
+
+    if definitions
+        .values()
+        .all(|definition| definition.first_day > 0 || definition.spawn_weight == 0)
+    {
+        bail!("at least one villager role must spawn from the first day");
+    }
+
+    Ok(definitions)
+}
+
+impl VillagerDefinitions {
+    // builds the roles of a villagers file, textures are resolved by the caller
+    pub fn from_bytes(
+        bytes: &[u8],
+        mut load_texture: impl FnMut(String) -> Handle<Image>,
+    ) -> anyhow::Result<Self> {
+        let definitions = parse_definitions(bytes)?;
+
+        let roles = definitions
+            .into_iter()
+            .map(|(name, definition)| {
+                let attributes = VillagerRoleAttributes {
+                    health: definition.health,
+                    attack: definition.attack,
+                    speed: definition.speed,
+                    accel: definition.accel,
+                    decel: definition.decel,
+                    range: definition.range,
+                    attack_rate: definition.attack_rate,
+                    ability: definition.ability,
+                    chimera_bonus: definition.chimera_bonus,
+                    preferences: definition.preferences,
+                    scale: definition.scale,
+                    textures: definition
+                        .textures
+                        .into_iter()
+                        .map(&mut load_texture)
+                        .collect(),
+                    spawn_weight: definition.spawn_weight,
+                    first_day: definition.first_day,
+                    max_alive: definition.max_alive,
+                };
+
+                (VillagerRole::new(name), attributes)
+            })
+            .collect();
+
+        Ok(VillagerDefinitions { roles })
+    }
+}
+
+#[derive(Default)]
+pub struct VillagerDefinitionsLoader;
+
+impl AssetLoader for VillagerDefinitionsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let mut dependencies = Vec::new();
+            let definitions = VillagerDefinitions::from_bytes(bytes, |path| {
+                let asset_path = AssetPath::new(path.into(), None);
+                let handle = load_context.get_handle(asset_path.clone());
+                dependencies.push(asset_path);
+                handle
+            })?;
+
+            load_context
+                .set_default_asset(LoadedAsset::new(definitions).with_dependencies(dependencies));
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["villagers.ron"]
+    }
+}
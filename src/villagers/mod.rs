@@ -1,40 +1,89 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 mod behavior;
+mod definitions;
 mod spawn;
+mod threat;
+pub use self::behavior::VillagerBehavior;
+pub use self::definitions::{
+    TargetPreferences, VillagerAbility, VillagerDefinitions, VillagerRole, VillagerRoleAttributes,
+};
+pub use self::spawn::VillagerSpawner;
+pub use self::threat::ThreatTable;
 
 use crate::animations::BobbingAnim;
-use crate::behaviors::{self, UnitBehavior};
-use crate::constants::{self, VILLAGER_STATS_DEVIATION as STATS_DEVIATION};
+use crate::assets_manager::AssetsManager;
+use crate::balance::GameBalance;
+use crate::behaviors::{self, Facing};
 use crate::health::Health;
-use crate::states::GameStates;
+use crate::navigation::NavPath;
+use crate::rng::{GameRng, RngStream};
+use crate::schedule::{self, FixedUpdateStage};
+use crate::states::{GameEntity, GameStates};
 
 pub struct VillagersPlugin;
 
 impl Plugin for VillagersPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(spawn::VillagerSpawner {
-            spawn_timer: Timer::from_seconds(constants::VILLAGER_BASE_SPAWN_DURATION, false),
-        });
+        app.add_asset::<VillagerDefinitions>()
+            .init_asset_loader::<definitions::VillagerDefinitionsLoader>();
 
-        // on enter
         app.add_system_set(
-            SystemSet::on_enter(GameStates::Game).with_system(spawn_test_villager_system),
+            SystemSet::on_enter(GameStates::MainMenu).with_system(setup_villager_definitions),
         );
 
-        // on update
+        // configured from the game balance when the game starts
+        app.init_resource::<VillagerSpawner>();
+
+        // on enter
         app.add_system_set(
-            SystemSet::on_update(GameStates::Game)
-                .with_system(behavior::villager_behavior_system)
-                .with_system(behaviors::villager_attack_system)
+            SystemSet::on_enter(GameStates::Game)
+                .with_system(spawn::setup_villager_spawner)
+                .with_system(spawn_test_villager_system),
+        );
+
+        // on tick
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            schedule::on_fixed_update()
+                .with_system(
+                    threat::threat_from_damage_system
+                        .after("chimera_taunt")
+                        .before("villager_target"),
+                )
+                .with_system(threat::villager_target_system.label("villager_target"))
+                .with_system(
+                    behavior::villager_behavior_system
+                        .after("nav_grid")
+                        .after("villager_target"),
+                )
+                .with_system(
+                    behaviors::villager_attack_system
+                        .after("villager_target")
+                        .before("apply_damage"),
+                )
                 .with_system(spawn::spawn_villagers_system),
         );
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+// the roles are available once the assets are loaded
+fn setup_villager_definitions(
+    mut commands: Commands,
+    assets: Res<AssetsManager>,
+    definitions: Res<Assets<VillagerDefinitions>>,
+) {
+    let definitions = definitions
+        .get(&assets.villager_definitions)
+        .expect("villager definitions should be loaded");
+
+    commands.insert_resource(definitions.clone());
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct VillagerStats {
     pub attack: f32,
     pub health: f32,
@@ -42,14 +91,21 @@ pub struct VillagerStats {
     pub accel: f32,
     pub decel: f32,
     pub range: f32,
+    // extra share of the melee damage dealt to chimeras
+    #[serde(default)]
+    pub chimera_bonus: f32,
 }
 
 #[derive(Component)]
 pub struct VillagerComponent {
-    pub behavior: UnitBehavior,
+    pub behavior: VillagerBehavior,
     pub stats: VillagerStats,
     pub attack_timer: Timer,
-    pub damage_timer: Timer,
+    pub role: VillagerRole,
+    pub ability: VillagerAbility,
+    pub preferences: TargetPreferences,
+    // index of the texture of the role
+    pub texture_variant: u8,
 }
 
 #[derive(Component)]
@@ -77,51 +133,84 @@ fn spawn_test_villager_system(mut _commands: Commands, _asset_server: Res<AssetS
     */
 }
 
-pub fn spawn_villager(position: Vec2, commands: &mut Commands, asset_server: &AssetServer) {
-    let villager_health = rand::thread_rng()
-        .gen_range(120.0 * (1.0 - STATS_DEVIATION)..120.0 * (1.0 + STATS_DEVIATION));
+pub fn spawn_villager(
+    position: Vec2,
+    role: &VillagerRole,
+    attributes: &VillagerRoleAttributes,
+    balance: &GameBalance,
+    rng: &mut GameRng,
+    commands: &mut Commands,
+) {
+    let stats_deviation = balance.stats.villager_deviation;
+    let stats_rng = rng.stream(RngStream::Stats);
+    let mut deviate =
+        |value: f32| value * stats_rng.gen_range(1.0 - stats_deviation..=1.0 + stats_deviation);
+
+    let villager_health = deviate(attributes.health);
+
+    let villager = VillagerComponent {
+        behavior: VillagerBehavior::initial(attributes.ability),
+        attack_timer: Timer::from_seconds(attributes.attack_rate, true),
+        stats: VillagerStats {
+            health: villager_health,
+            attack: deviate(attributes.attack),
+            speed: deviate(attributes.speed),
+            accel: deviate(attributes.accel),
+            decel: deviate(attributes.decel),
+            range: attributes.range,
+            chimera_bonus: attributes.chimera_bonus,
+        },
+        role: role.clone(),
+        ability: attributes.ability,
+        preferences: attributes.preferences,
+        texture_variant: rng
+            .stream(RngStream::Visuals)
+            .gen_range(0..attributes.textures.len()) as u8,
+    };
+    let health = Health::new(villager_health, 1.0, balance.behaviors.villager_regen_rate);
+
+    spawn_villager_entity(villager, health, position, attributes, rng, commands);
+}
+
+// spawns the entity of an already rolled villager, also used when loading a save
+pub fn spawn_villager_entity(
+    villager: VillagerComponent,
+    health: Health,
+    position: Vec2,
+    attributes: &VillagerRoleAttributes,
+    rng: &mut GameRng,
+    commands: &mut Commands,
+) -> Entity {
+    // textures are default handles when running headless
+    let texture = attributes.textures[villager.texture_variant as usize].clone();
+    let bobbing_anim_val = rng.stream(RngStream::Visuals).gen::<f32>() * 32.0;
+    let scale = attributes.scale;
 
     commands
         .spawn_bundle(TransformBundle::from(Transform::from_translation(
             position.extend(1.0),
         )))
+        .insert(GameEntity)
         .insert(Velocity::default())
-        .insert(VillagerComponent {
-            behavior: UnitBehavior::Pursue { target: None },
-            damage_timer: Timer::from_seconds(constants::DAMAGE_RED_DURATION, true),
-            attack_timer: Timer::from_seconds(constants::VILLAGER_ATTACK_RATE, true),
-            stats: VillagerStats {
-                health: villager_health,
-                attack: rand::thread_rng()
-                    .gen_range(10.0 * (1.0 - STATS_DEVIATION)..10.0 * (1.0 + STATS_DEVIATION)),
-                speed: rand::thread_rng()
-                    .gen_range(100.0 * (1.0 - STATS_DEVIATION)..100.0 * (1.0 + STATS_DEVIATION)),
-                accel: rand::thread_rng()
-                    .gen_range(2.0 * (1.0 - STATS_DEVIATION)..2.0 * (1.0 + STATS_DEVIATION)),
-                decel: rand::thread_rng()
-                    .gen_range(6.0 * (1.0 - STATS_DEVIATION)..6.0 * (1.0 + STATS_DEVIATION)),
-                range: 100.0,
-            },
-        })
-        .insert(Health::new(
-            villager_health,
-            1.0,
-            constants::VILLAGER_REGEN_RATE,
-        ))
+        .insert(NavPath::default())
+        .insert(Facing::default())
+        .insert(villager)
+        .insert(ThreatTable::default())
+        .insert(health)
         .insert(RigidBody::Dynamic)
-        .insert(Collider::cuboid(10.0, 15.0))
+        .insert(Collider::cuboid(10.0 * scale, 15.0 * scale))
         .insert(LockedAxes::ROTATION_LOCKED)
         .with_children(|parent| {
             parent
                 .spawn_bundle(SpriteBundle {
-                    texture: asset_server.load(
-                        format!("villager_{}.png", rand::thread_rng().gen_range(1..=3)).as_str(),
-                    ),
+                    texture,
+                    transform: Transform::from_scale(Vec3::splat(scale)),
                     ..default()
                 })
                 .insert(VillagerSprite)
                 .insert(BobbingAnim {
-                    anim: rand::thread_rng().gen::<f32>() * 32.0,
+                    anim: bobbing_anim_val,
                 });
-        });
+        })
+        .id()
 }
@@ -1,44 +1,97 @@
 use bevy::prelude::*;
 
-use super::spawn_villager;
-use crate::constants::{
-    self, VILLAGER_BASE_SPAWN_DURATION, VILLAGER_SPAWN_HOURS, VILLAGER_SPAWN_LOCATIONS,
-};
+use super::{spawn_villager, VillagerComponent, VillagerDefinitions, VillagerRole};
+use crate::balance::GameBalance;
 use crate::day_cycle::DayCycleResource;
+use crate::rng::{GameRng, RngStream};
+use crate::schedule::tick_delta;
+use rand::seq::SliceRandom;
 use rand::Rng;
 use std::time::Duration;
 
+#[derive(Default)]
 pub struct VillagerSpawner {
     pub spawn_timer: Timer,
 }
 
+pub fn setup_villager_spawner(
+    mut villager_spawner: ResMut<VillagerSpawner>,
+    balance: Res<GameBalance>,
+) {
+    villager_spawner.spawn_timer =
+        Timer::from_seconds(balance.spawning.villager_base_spawn_duration, false);
+}
+
 pub fn spawn_villagers_system(
     mut commands: Commands,
-    time: Res<Time>,
     day_cycle: Res<DayCycleResource>,
     mut villager_spawner: ResMut<VillagerSpawner>,
-    asset_server: Res<AssetServer>,
+    definitions: Res<VillagerDefinitions>,
+    villager_query: Query<&VillagerComponent>,
+    balance: Res<GameBalance>,
+    mut rng: ResMut<GameRng>,
 ) {
-    if day_cycle.get_hour() > VILLAGER_SPAWN_HOURS.0
-        && day_cycle.get_hour() < VILLAGER_SPAWN_HOURS.1
-    {
-        villager_spawner.spawn_timer.tick(time.delta());
+    let spawning = &balance.spawning;
+    let spawn_hours = spawning.villager_spawn_hours;
+
+    if day_cycle.get_hour() > spawn_hours.0 && day_cycle.get_hour() < spawn_hours.1 {
+        villager_spawner.spawn_timer.tick(tick_delta());
 
         if villager_spawner.spawn_timer.just_finished() {
             // choose random position in village
-            let position: Vec2 =
-                VILLAGER_SPAWN_LOCATIONS[rand::thread_rng().gen_range(0..6)].into();
+            let location_idx = rng
+                .stream(RngStream::Spawning)
+                .gen_range(0..spawning.villager_spawn_locations.len());
+            let position = spawning.villager_spawn_locations[location_idx];
+
+            // roles of the current day that are not at their limit
+            let alive = |role: &VillagerRole| {
+                villager_query
+                    .iter()
+                    .filter(|villager| villager.role == *role)
+                    .count() as u32
+            };
+            let available: Vec<_> = definitions
+                .roles
+                .iter()
+                .filter(|(role, attributes)| {
+                    attributes.first_day <= u32::from(day_cycle.days_passed)
+                        && attributes
+                            .max_alive
+                            .is_none_or(|max_alive| alive(role) < max_alive)
+                })
+                .collect();
+
+            if let Ok((role, attributes)) = available
+                .choose_weighted(rng.stream(RngStream::Spawning), |(_, attributes)| {
+                    attributes.spawn_weight
+                })
+                .copied()
+            {
+                info!(
+                    "spawning {} at position {} at time {}",
+                    role,
+                    position,
+                    day_cycle.get_hour()
+                );
 
-            info!(
-                "spawning villager at position {} at time {}",
-                position,
-                day_cycle.get_hour()
-            );
+                // a role alone of its kind is a boss, like the mayor
+                if attributes.max_alive == Some(1) {
+                    info!("the {} has come to the ranch", role);
+                }
 
-            spawn_villager(position, &mut commands, &asset_server);
+                spawn_villager(
+                    position,
+                    role,
+                    attributes,
+                    &balance,
+                    &mut rng,
+                    &mut commands,
+                );
+            }
 
-            let new_spawn_duration = VILLAGER_BASE_SPAWN_DURATION
-                * (-(day_cycle.days_passed as f32 + 1.0) / constants::VILLAGER_SPAWN_FACTOR).exp();
+            let new_spawn_duration = spawning.villager_base_spawn_duration
+                * (-(day_cycle.days_passed as f32 + 1.0) / spawning.villager_spawn_factor).exp();
 
             villager_spawner
                 .spawn_timer
@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+
+use super::{TargetPreferences, VillagerComponent};
+use crate::{
+    animals::AnimalComponent, balance::GameBalance, chimeras::ChimeraComponent,
+    damage::DamageEvent, player::Player, schedule::tick_delta,
+};
+
+// faded threats below this are forgotten
+const MIN_THREAT: f32 = 0.1;
+
+// What a villager holds against the units around, built up by the damage it
+// takes and the taunts of the chimeras, fading over time. It is not saved, it
+// builds up again within seconds
+#[derive(Component, Debug, Default, Clone)]
+pub struct ThreatTable {
+    pub entries: Vec<(Entity, f32)>,
+    // unit the villager goes after, picked every tick
+    pub target: Option<Entity>,
+}
+
+impl ThreatTable {
+    pub fn add(&mut self, entity: Entity, threat: f32) {
+        match self.entries.iter_mut().find(|(other, _)| *other == entity) {
+            Some((_, current)) => *current += threat,
+            None => self.entries.push((entity, threat)),
+        }
+    }
+
+    pub fn get(&self, entity: Entity) -> f32 {
+        self.entries
+            .iter()
+            .find(|(other, _)| *other == entity)
+            .map_or(0.0, |(_, threat)| *threat)
+    }
+}
+
+// Villagers remember who hurt them
+pub fn threat_from_damage_system(
+    mut damage_events: EventReader<DamageEvent>,
+    mut table_query: Query<&mut ThreatTable>,
+    balance: Res<GameBalance>,
+) {
+    for event in damage_events.iter() {
+        if let Ok(mut table) = table_query.get_mut(event.target) {
+            table.add(
+                event.source,
+                event.amount * balance.behaviors.villager_threat_per_damage,
+            );
+        }
+    }
+}
+
+// Fades the threats, then has every villager go after the most threatening of
+// the player, the chimeras and the animals
+pub fn villager_target_system(
+    mut villager_query: Query<(&VillagerComponent, &mut ThreatTable, &Transform)>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    chimera_query: Query<(Entity, &Transform), With<ChimeraComponent>>,
+    animal_query: Query<(Entity, &Transform), With<AnimalComponent>>,
+    balance: Res<GameBalance>,
+) {
+    let balance = &balance.behaviors;
+    let fade = (1.0 - balance.villager_threat_decay * tick_delta().as_secs_f32()).max(0.0);
+
+    for (villager, mut table, transform) in villager_query.iter_mut() {
+        for (_, threat) in table.entries.iter_mut() {
+            *threat *= fade;
+        }
+        table.entries.retain(|(_, threat)| *threat > MIN_THREAT);
+
+        let position = transform.translation.truncate();
+        let TargetPreferences {
+            player,
+            chimera,
+            animal,
+        } = villager.preferences;
+
+        // closer units are more threatening
+        let proximity = |target: &Transform| {
+            let distance = position.distance(target.translation.truncate());
+            balance.villager_threat_proximity
+                * (1.0 - distance / balance.villager_threat_range).max(0.0)
+        };
+
+        let players = player_query
+            .iter()
+            .map(|(entity, target)| (entity, balance.villager_player_threat, target, player));
+        let chimeras = chimera_query
+            .iter()
+            .map(|(entity, target)| (entity, 0.0, target, chimera));
+        // animals never hurt villagers, they are only a target when close
+        let animals = animal_query
+            .iter()
+            .filter(|_| animal > 0.0)
+            .map(|(entity, target)| (entity, 0.0, target, animal));
+
+        table.target = players
+            .chain(chimeras)
+            .chain(animals)
+            .map(|(entity, base, target, preference)| {
+                let threat = base + table.get(entity) + proximity(target);
+                (entity, threat * preference)
+            })
+            .filter(|(_, threat)| *threat > 0.0)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity);
+    }
+}